@@ -0,0 +1,110 @@
+//! The main benchmark suite, run with `cargo bench --bench criterion`. Reports
+//! are written to `target/criterion`.
+//!
+//! - `ok_path` compares the `StackableErr` methods on the `Ok` path with a
+//!   plain `map_err`, they should be indistinguishable
+//! - `construct` builds errors with 1, 4, and 16 frames
+//! - `format` displays a 16 frame error
+//! - `err_path` compares `StackableErr::stack` with
+//!   `StackableErrSame::stack_same` on a `Result<T, Error>`, the latter skips
+//!   the downcast
+//! - `is_timeout` checks the tags of a 16 frame error with a `TimeoutError` at
+//!   the root and one without
+//! - `cow_context` compares `StackableErr::stack_err_cow` with borrowed and
+//!   owned messages on the error path
+
+use std::{borrow::Cow, hint::black_box};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use stacked_errors::{Error, Result, StackableErr, StackableErrSame};
+
+fn parse(i: u64) -> core::result::Result<u64, std::num::TryFromIntError> {
+    u32::try_from(i & 0xffff).map(u64::from)
+}
+
+fn frames(n: usize, root: Error) -> Error {
+    let mut e = root;
+    for i in 1..n {
+        if (i % 2) == 0 {
+            e = e.add();
+        } else {
+            e = e.add_err("context");
+        }
+    }
+    e
+}
+
+fn ok_path(c: &mut Criterion) {
+    let mut g = c.benchmark_group("ok_path");
+    g.bench_function("map_err", |b| {
+        b.iter(|| -> Result<u64> { parse(black_box(7)).map_err(Error::from_err) })
+    });
+    g.bench_function("stack", |b| b.iter(|| parse(black_box(7)).stack()));
+    g.bench_function("stack_err_with", |b| {
+        b.iter(|| parse(black_box(7)).stack_err_with(|| format!("context for {}", 7)))
+    });
+    g.finish();
+}
+
+fn construct(c: &mut Criterion) {
+    let mut g = c.benchmark_group("construct");
+    for n in [1, 4, 16] {
+        g.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, &n| {
+            b.iter(|| frames(black_box(n), Error::from_err("root")))
+        });
+    }
+    g.finish();
+}
+
+fn format(c: &mut Criterion) {
+    let e = frames(16, Error::from_err("root"));
+    c.bench_function("format", |b| b.iter(|| format!("{}", black_box(&e))));
+}
+
+fn err_path(c: &mut Criterion) {
+    let mut g = c.benchmark_group("err_path");
+    g.bench_function("stack", |b| {
+        b.iter(|| Err::<u64, _>(black_box(Error::empty())).stack())
+    });
+    g.bench_function("stack_same", |b| {
+        b.iter(|| Err::<u64, _>(black_box(Error::empty())).stack_same())
+    });
+    g.finish();
+}
+
+fn is_timeout(c: &mut Criterion) {
+    let mut g = c.benchmark_group("is_timeout");
+    let e = frames(16, Error::timeout());
+    g.bench_function("timeout", |b| b.iter(|| black_box(&e).is_timeout()));
+    let e = frames(16, Error::from_err("root"));
+    g.bench_function("other", |b| b.iter(|| black_box(&e).is_timeout()));
+    g.finish();
+}
+
+fn cow_context(c: &mut Criterion) {
+    let mut g = c.benchmark_group("cow_context");
+    g.bench_function("borrowed", |b| {
+        b.iter(|| {
+            Err::<u64, _>(black_box(Error::with_capacity(2)))
+                .stack_err_cow(Cow::Borrowed("retrying"))
+        })
+    });
+    g.bench_function("owned", |b| {
+        b.iter(|| {
+            Err::<u64, _>(black_box(Error::with_capacity(2)))
+                .stack_err_cow(Cow::Owned(format!("retrying attempt {}", black_box(3))))
+        })
+    });
+    g.finish();
+}
+
+criterion_group!(
+    benches,
+    ok_path,
+    construct,
+    format,
+    err_path,
+    is_timeout,
+    cow_context
+);
+criterion_main!(benches);
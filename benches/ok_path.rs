@@ -0,0 +1,35 @@
+//! Measures the cost of the `StackableErr` methods on the `Ok` path, which
+//! should be the same as a plain `?`. Run with `cargo bench --bench ok_path`.
+
+use std::{hint::black_box, time::Instant};
+
+use stacked_errors::{Result, StackableErr};
+
+const ITERS: u32 = 100_000_000;
+
+fn bench(name: &str, f: impl Fn(u64) -> Result<u64>) {
+    let start = Instant::now();
+    let mut sum = 0u64;
+    for i in 0..u64::from(ITERS) {
+        sum = sum.wrapping_add(f(black_box(i)).unwrap());
+    }
+    black_box(sum);
+    let elapsed = start.elapsed();
+    println!("{name}: {:?} per call", elapsed / ITERS);
+}
+
+fn parse(i: u64) -> core::result::Result<u64, std::num::TryFromIntError> {
+    u32::try_from(i & 0xffff).map(u64::from)
+}
+
+fn main() {
+    bench("baseline", |i| Ok(parse(i).unwrap()));
+    bench("stack", |i| parse(i).stack());
+    bench("stack_err", |i| parse(i).stack_err("context"));
+    bench("stack_err_with", |i| {
+        parse(i).stack_err_with(|| format!("context for {i}"))
+    });
+    bench("Option::stack_err_with", |i| {
+        parse(i).ok().stack_err_with(|| format!("context for {i}"))
+    });
+}
@@ -0,0 +1,49 @@
+//! Compares constructing errors from payloads that fit inline in a frame with
+//! constructing them from payloads that need a separate heap allocation. Run
+//! with `cargo bench --bench small_payload`.
+
+use std::{fmt, hint::black_box, time::Instant};
+
+use stacked_errors::Error;
+
+const ITERS: u32 = 1_000_000;
+
+struct Words<const N: usize>([usize; N]);
+
+impl<const N: usize> fmt::Display for Words<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.0)
+    }
+}
+
+fn bench(name: &str, f: impl Fn() -> Error) {
+    let start = Instant::now();
+    for _ in 0..ITERS {
+        black_box(f());
+    }
+    let elapsed = start.elapsed();
+    println!("{name}: {:?} per error", elapsed / ITERS);
+}
+
+fn main() {
+    bench("inline (4 words)", || {
+        Error::from_err(Words(black_box([0usize; 4])))
+    });
+    bench("boxed (5 words)", || {
+        Error::from_err(Words(black_box([0usize; 5])))
+    });
+    bench("inline (4 words) with 8 stacks", || {
+        let mut e = Error::from_err(Words(black_box([0usize; 4])));
+        for _ in 0..8 {
+            e.push_err(Words(black_box([0usize; 4])));
+        }
+        e
+    });
+    bench("boxed (5 words) with 8 stacks", || {
+        let mut e = Error::from_err(Words(black_box([0usize; 5])));
+        for _ in 0..8 {
+            e.push_err(Words(black_box([0usize; 5])));
+        }
+        e
+    });
+}
@@ -0,0 +1,26 @@
+//! Compares constructing errors from `&'static str`s with constructing them
+//! from `String`s. Run with `cargo bench --bench static_str`.
+
+use std::{hint::black_box, time::Instant};
+
+use stacked_errors::Error;
+
+const ITERS: u32 = 1_000_000;
+
+fn bench(name: &str, f: impl Fn() -> Error) {
+    let start = Instant::now();
+    for _ in 0..ITERS {
+        black_box(f());
+    }
+    let elapsed = start.elapsed();
+    println!("{name}: {:?} per error", elapsed / ITERS);
+}
+
+fn main() {
+    bench("Error::static_str", || {
+        Error::static_str(black_box("unsupported operation"))
+    });
+    bench("Error::from_err(String)", || {
+        Error::from_err(String::from(black_box("unsupported operation")))
+    });
+}
@@ -0,0 +1,215 @@
+//! Global configuration, these are atomics so that they can be changed at
+//! any time from any thread
+
+use core::{
+    ptr,
+    sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering},
+};
+
+use crate::ErrorItem;
+
+/// The default of [max_operand_bytes]
+pub const DEFAULT_MAX_OPERAND_BYTES: usize = 4096;
+
+static MAX_OPERAND_BYTES: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_OPERAND_BYTES);
+
+/// Returns the maximum number of bytes of the `Debug` rendering of each
+/// operand that the default messages of `ensure_eq!`, `ensure_ne!`, and
+/// related macros include, see [set_max_operand_bytes]
+pub fn max_operand_bytes() -> usize {
+    MAX_OPERAND_BYTES.load(Ordering::Relaxed)
+}
+
+/// Sets the maximum number of bytes of the `Debug` rendering of each operand
+/// that the default messages of `ensure_eq!`, `ensure_ne!`, and related macros
+/// include. Renderings longer than this are truncated with a
+/// `... (N bytes elided)` marker, so that failures on huge values don't make
+/// logs unusable. The default is [DEFAULT_MAX_OPERAND_BYTES].
+///
+/// ```
+/// use stacked_errors::{ensure_eq, max_operand_bytes, set_max_operand_bytes, Result};
+///
+/// fn ex(lhs: &[u8], rhs: &[u8]) -> Result<()> {
+///     ensure_eq!(lhs, rhs);
+///     Ok(())
+/// }
+///
+/// let prev = max_operand_bytes();
+/// set_max_operand_bytes(8);
+/// let e = ex(&[0; 100], &[1; 4]).unwrap_err();
+/// set_max_operand_bytes(prev);
+/// assert!(format!("{e}").contains("lhs: [0, 0, 0... (292 bytes elided)"));
+/// ```
+pub fn set_max_operand_bytes(max: usize) {
+    MAX_OPERAND_BYTES.store(max, Ordering::Relaxed)
+}
+
+static MAX_FRAMES: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+/// Returns the maximum number of frames that an `Error` keeps, see
+/// [set_max_frames]
+pub fn max_frames() -> usize {
+    MAX_FRAMES.load(Ordering::Relaxed)
+}
+
+/// Sets the maximum number of frames that an `Error` keeps, so that a buggy
+/// loop that keeps stacking onto the same error can't use unbounded memory.
+/// The default is `usize::MAX` (unlimited), and values less than 3 are
+/// treated as 3.
+///
+/// Once a push or chain would go over the maximum, the frames just above the
+/// root frame are replaced by a single
+/// [TruncatedFrames](crate::TruncatedFrames) frame counting the dropped frames,
+/// so the root cause and the most recent frames are always kept. Dropped frames
+/// are gone entirely, including for queries like
+/// [Error::is_timeout](crate::Error::is_timeout).
+pub fn set_max_frames(max: usize) {
+    MAX_FRAMES.store(max, Ordering::Relaxed)
+}
+
+static LOCATION_WRAP_WIDTH: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns the width set by [set_location_wrap_width]
+pub fn location_wrap_width() -> Option<usize> {
+    match LOCATION_WRAP_WIDTH.load(Ordering::Relaxed) {
+        0 => None,
+        width => Some(width),
+    }
+}
+
+/// Sets the width past which the location of a frame is put on its own line
+/// instead of after the message. With `None` (the default) this is 80, or
+/// with the "terminal-width" feature the width of the terminal for styled
+/// `Debug` output if stderr or stdout is a terminal.
+pub fn set_location_wrap_width(width: Option<usize>) {
+    LOCATION_WRAP_WIDTH.store(width.unwrap_or(0), Ordering::Relaxed)
+}
+
+static STRICT_NESTING: AtomicBool = AtomicBool::new(false);
+
+/// Returns if nested errors panic, see [set_strict_nesting]
+pub fn strict_nesting() -> bool {
+    STRICT_NESTING.load(Ordering::Relaxed)
+}
+
+/// With debug assertions, frames that nest a whole `Error` (an `Arc<Error>`,
+/// `Box<Error>`, or `&'static Error`) are reported to the hook set by
+/// [set_nesting_hook], or get a [NestedErrorWarning](crate::NestedErrorWarning)
+/// frame pushed above them if there is no hook. With strict nesting they panic
+/// instead. None of this happens without debug assertions. The default is
+/// `false`.
+pub fn set_strict_nesting(strict: bool) {
+    STRICT_NESTING.store(strict, Ordering::Relaxed)
+}
+
+static NESTING_HOOK: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
+
+/// Sets the function that frames nesting a whole `Error` are reported to, see
+/// [set_strict_nesting]. The hook gets the nesting frame, and the error is left
+/// as is. With `None` (the default) a
+/// [NestedErrorWarning](crate::NestedErrorWarning) frame is pushed instead.
+///
+/// ```
+/// use std::sync::{
+///     atomic::{AtomicUsize, Ordering},
+///     Arc,
+/// };
+///
+/// use stacked_errors::{set_nesting_hook, Error, ErrorItem};
+///
+/// static NESTED: AtomicUsize = AtomicUsize::new(0);
+///
+/// fn hook(_: &ErrorItem) {
+///     NESTED.fetch_add(1, Ordering::Relaxed);
+/// }
+///
+/// set_nesting_hook(Some(hook));
+/// // with debug assertions this calls `hook`, the frame is kept as is
+/// let e = Error::from_err(Arc::new(Error::from_err("root")));
+/// assert_eq!(e.iter().len(), 1);
+/// set_nesting_hook(None);
+/// ```
+pub fn set_nesting_hook(hook: Option<fn(&ErrorItem)>) {
+    let hook = hook.map_or(ptr::null_mut(), |f| f as *mut ());
+    NESTING_HOOK.store(hook, Ordering::Release)
+}
+
+/// Reports `e` to the hook set by [set_nesting_hook], returns `false` if
+/// there is no hook
+#[cfg(debug_assertions)]
+pub(crate) fn report_nesting(e: &ErrorItem) -> bool {
+    let f = NESTING_HOOK.load(Ordering::Acquire);
+    if f.is_null() {
+        return false
+    }
+    // SAFETY: only `fn(&ErrorItem)`s are stored in the hook
+    let f = unsafe { core::mem::transmute::<*mut (), fn(&ErrorItem)>(f) };
+    f(e);
+    true
+}
+
+/// The maximum number of predicates that [register_timeout_predicate] keeps
+pub const MAX_TIMEOUT_PREDICATES: usize = 8;
+
+static TIMEOUT_PREDICATES: [AtomicPtr<()>; MAX_TIMEOUT_PREDICATES] =
+    [const { AtomicPtr::new(ptr::null_mut()) }; MAX_TIMEOUT_PREDICATES];
+
+/// Registers `f` for recognizing frames of foreign types as timeouts in
+/// [Error::is_timeout](crate::Error::is_timeout) and
+/// [Error::kind_at_root](crate::Error::kind_at_root). Frames are recognized as
+/// timeouts if any of these match, in order:
+///
+/// - a [TimeoutError](crate::TimeoutError)
+/// - with the "std" feature, a `std::io::Error` with the `TimedOut` or
+///   `WouldBlock` kind (the latter is what reads and writes with a timeout
+///   return on some platforms)
+/// - with the "tokio" feature, a `tokio::time::error::Elapsed`
+/// - the registered predicates, in the order they were registered
+///
+/// Frames are checked when they are pushed, so predicates should be
+/// registered at startup before the errors they are meant for are created.
+/// Registering the same function again does nothing. Returns `false` if
+/// [MAX_TIMEOUT_PREDICATES] different predicates are already registered.
+///
+/// ```
+/// use stacked_errors::{register_timeout_predicate, Error, StackedErrorDowncast};
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("deadline exceeded")]
+/// struct DeadlineExceeded;
+///
+/// assert!(register_timeout_predicate(|e| e
+///     .downcast_ref::<DeadlineExceeded>()
+///     .is_some()));
+/// let e = Error::from_err(DeadlineExceeded).add_err("calling the service");
+/// assert!(e.is_timeout());
+/// ```
+pub fn register_timeout_predicate(f: fn(&ErrorItem) -> bool) -> bool {
+    let f = f as *mut ();
+    for slot in &TIMEOUT_PREDICATES {
+        match slot.compare_exchange(ptr::null_mut(), f, Ordering::AcqRel, Ordering::Acquire) {
+            Ok(_) => return true,
+            Err(prev) if prev == f => return true,
+            Err(_) => (),
+        }
+    }
+    false
+}
+
+/// Returns if any predicate registered with [register_timeout_predicate]
+/// matches `e`
+pub(crate) fn matches_timeout_predicate(e: &ErrorItem) -> bool {
+    for slot in &TIMEOUT_PREDICATES {
+        let f = slot.load(Ordering::Acquire);
+        if f.is_null() {
+            // slots are filled in order
+            return false
+        }
+        // SAFETY: only `fn(&ErrorItem) -> bool`s are stored in the slots
+        let f = unsafe { core::mem::transmute::<*mut (), fn(&ErrorItem) -> bool>(f) };
+        if f(e) {
+            return true
+        }
+    }
+    false
+}
@@ -0,0 +1,175 @@
+use alloc::{string::String, vec, vec::Vec};
+use core::fmt::Write;
+
+use crate::fmt::capped_debug;
+
+/// Values with a `Debug` output longer than this get a line diff even if
+/// their output is on one line, since the alternate output of them usually
+/// is not
+const DIFF_THRESHOLD: usize = 80;
+
+/// The number of unchanged lines shown around changes
+const CONTEXT: usize = 2;
+
+/// The built in line diff is quadratic, so past this it falls back to showing
+/// everything between the common prefix and suffix as changed
+#[cfg(not(feature = "diff"))]
+const MAX_DIFF_CELLS: usize = 1 << 20;
+
+/// Splits the `Debug` output `s` into lines, with escaped newlines in string
+/// and char literals also counting as line endings
+fn debug_lines(s: &str) -> Vec<&str> {
+    let mut lines = vec![];
+    for line in s.split('\n') {
+        let mut start = 0;
+        let mut chars = line.char_indices();
+        while let Some((_, c)) = chars.next() {
+            if c == '\\' {
+                if let Some((i, 'n')) = chars.next() {
+                    // the escape stays at the end of its line
+                    lines.push(&line[start..(i + 1)]);
+                    start = i + 1;
+                }
+            }
+        }
+        if start < line.len() || start == 0 {
+            lines.push(&line[start..]);
+        }
+    }
+    lines
+}
+
+#[derive(Clone, Copy)]
+enum Change {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Returns the lines of `lhs` and `rhs` tagged with how they changed, using
+/// the Myers diff from `similar`
+#[cfg(feature = "diff")]
+fn diff_lines<'a>(lhs: &[&'a str], rhs: &[&'a str]) -> Vec<(Change, &'a str)> {
+    similar::TextDiff::from_slices(lhs, rhs)
+        .iter_all_changes()
+        .map(|change| {
+            let tag = match change.tag() {
+                similar::ChangeTag::Equal => Change::Equal,
+                similar::ChangeTag::Delete => Change::Delete,
+                similar::ChangeTag::Insert => Change::Insert,
+            };
+            (tag, change.value())
+        })
+        .collect()
+}
+
+/// Returns the lines of `lhs` and `rhs` tagged with how they changed, using
+/// the longest common subsequence of lines
+#[cfg(not(feature = "diff"))]
+fn diff_lines<'a>(lhs: &[&'a str], rhs: &[&'a str]) -> Vec<(Change, &'a str)> {
+    let prefix = lhs.iter().zip(rhs).take_while(|(l, r)| l == r).count();
+    let suffix = lhs[prefix..]
+        .iter()
+        .rev()
+        .zip(rhs[prefix..].iter().rev())
+        .take_while(|(l, r)| l == r)
+        .count();
+    let l = &lhs[prefix..(lhs.len() - suffix)];
+    let r = &rhs[prefix..(rhs.len() - suffix)];
+
+    let mut res: Vec<(Change, &str)> = lhs[..prefix].iter().map(|s| (Change::Equal, *s)).collect();
+    if l.len().saturating_mul(r.len()) > MAX_DIFF_CELLS {
+        res.extend(l.iter().map(|s| (Change::Delete, *s)));
+        res.extend(r.iter().map(|s| (Change::Insert, *s)));
+    } else {
+        // `lcs[i][j]` is the length of the longest common subsequence of `l[i..]`
+        // and `r[j..]`
+        let w = r.len() + 1;
+        let mut lcs = vec![0usize; (l.len() + 1) * w];
+        for i in (0..l.len()).rev() {
+            for j in (0..r.len()).rev() {
+                lcs[(i * w) + j] = if l[i] == r[j] {
+                    lcs[((i + 1) * w) + j + 1] + 1
+                } else {
+                    lcs[((i + 1) * w) + j].max(lcs[(i * w) + j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while (i < l.len()) || (j < r.len()) {
+            if (i < l.len()) && (j < r.len()) && (l[i] == r[j]) {
+                res.push((Change::Equal, l[i]));
+                i += 1;
+                j += 1;
+            } else if (j < r.len())
+                && ((i == l.len()) || (lcs[(i * w) + j + 1] > lcs[((i + 1) * w) + j]))
+            {
+                res.push((Change::Insert, r[j]));
+                j += 1;
+            } else {
+                res.push((Change::Delete, l[i]));
+                i += 1;
+            }
+        }
+    }
+    res.extend(
+        lhs[(lhs.len() - suffix)..]
+            .iter()
+            .map(|s| (Change::Equal, *s)),
+    );
+    res
+}
+
+/// Returns a line diff of the `Debug` outputs of `lhs` and `rhs` if they are
+/// large enough for one to be more readable than both full values
+pub(crate) fn debug_diff<L: core::fmt::Debug + ?Sized, R: core::fmt::Debug + ?Sized>(
+    lhs: &L,
+    rhs: &R,
+) -> Option<String> {
+    let large = |s: &str| (s.len() > DIFF_THRESHOLD) || (debug_lines(s).len() > 1);
+    if !(large(&capped_debug(lhs, false)) || large(&capped_debug(rhs, false))) {
+        return None
+    }
+    let lhs = capped_debug(lhs, true);
+    let rhs = capped_debug(rhs, true);
+    let lhs = debug_lines(&lhs);
+    let rhs = debug_lines(&rhs);
+    if (lhs.len() <= 1) && (rhs.len() <= 1) {
+        return None
+    }
+
+    let changes = diff_lines(&lhs, &rhs);
+    // only lines within `CONTEXT` of a change are shown
+    let mut shown = vec![false; changes.len()];
+    for (i, (change, _)) in changes.iter().enumerate() {
+        if !matches!(change, Change::Equal) {
+            let start = i.saturating_sub(CONTEXT);
+            let end = (i + CONTEXT + 1).min(changes.len());
+            for shown in &mut shown[start..end] {
+                *shown = true;
+            }
+        }
+    }
+    let mut s = String::new();
+    let mut skipped = false;
+    for (i, (change, line)) in changes.iter().enumerate() {
+        if !shown[i] {
+            skipped = true;
+            continue
+        }
+        if skipped {
+            s.push_str(" …\n");
+        }
+        skipped = false;
+        let sign = match change {
+            Change::Equal => ' ',
+            Change::Delete => '-',
+            Change::Insert => '+',
+        };
+        let _ = writeln!(s, " {sign}{line}");
+    }
+    if skipped {
+        s.push_str(" …\n");
+    }
+    Some(s)
+}
@@ -1,24 +1,139 @@
+use alloc::{boxed::Box, string::String};
 use core::{
     any::Any,
     fmt::{Debug, Display},
-    panic::Location,
+    mem::ManuallyDrop,
+    panic::{Location, RefUnwindSafe, UnwindSafe},
+    ptr,
     slice::{Iter, IterMut},
 };
 
 use smallbox::{smallbox, SmallBox};
 use thin_vec::{thin_vec, ThinVec};
 
-use crate::{ProbablyNotRootCauseError, TimeoutError, UnitError};
+use crate::{
+    AllocFailedError, MultipleErrors, PanicError, ProbablyNotRootCauseError, TimeoutError,
+    TruncatedFrames, UnitError,
+};
+
+/// The trait objects stored in an [ErrorItem]. The `core::error::Error` view
+/// of a frame is decided by the type it was created with, so that it does not
+/// have to be looked up later.
+///
+/// NOTE the `SmallBox` is itself `Display` and so implements this through
+/// the blanket impl, the methods have to be called on `*self.b`.
+trait Payload: Send + Sync + 'static {
+    fn display(&self) -> &(dyn Display + Send + Sync);
+
+    fn any(&self) -> &(dyn Any + Send + Sync);
+
+    fn any_mut(&mut self) -> &mut (dyn Any + Send + Sync);
+
+    fn std_error(&self) -> Option<&(dyn core::error::Error + 'static)>;
+}
+
+impl Display for dyn Payload {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(self.display(), f)
+    }
+}
+
+impl<T: Display + Send + Sync + 'static> Payload for T {
+    fn display(&self) -> &(dyn Display + Send + Sync) {
+        self
+    }
+
+    fn any(&self) -> &(dyn Any + Send + Sync) {
+        self
+    }
+
+    fn any_mut(&mut self) -> &mut (dyn Any + Send + Sync) {
+        self
+    }
+
+    fn std_error(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        known_std_error(self)
+    }
+}
+
+/// A payload from [Error::from_std_err] and related functions, which keeps
+/// the `core::error::Error` impl of `E`. This is not `Display` so that it
+/// does not overlap with the blanket impl, and downcasting goes to `E`
+/// directly.
+#[repr(transparent)]
+struct StdErrorPayload<E>(E);
+
+impl<E: core::error::Error + Send + Sync + 'static> Payload for StdErrorPayload<E> {
+    fn display(&self) -> &(dyn Display + Send + Sync) {
+        &self.0
+    }
+
+    fn any(&self) -> &(dyn Any + Send + Sync) {
+        &self.0
+    }
+
+    fn any_mut(&mut self) -> &mut (dyn Any + Send + Sync) {
+        &mut self.0
+    }
+
+    fn std_error(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+/// Returns if `top` has the same `Display` output as `e`. Strings are compared
+/// without allocating, a zero sized `E` is the same if `top` has the same
+/// type since it has nothing to differ in, and otherwise `e` is rendered once.
+fn same_message<E: Display + Send + Sync + 'static>(top: &ErrorItem, e: &E) -> bool {
+    let any: &dyn Any = e;
+    if let Some(s) = any.downcast_ref::<&'static str>() {
+        return crate::fmt::display_eq(top.get_err(), s)
+    }
+    if let Some(s) = any.downcast_ref::<String>() {
+        return crate::fmt::display_eq(top.get_err(), s)
+    }
+    if (core::mem::size_of::<E>() == 0) && top.downcast_ref::<E>().is_some() {
+        return true
+    }
+    crate::fmt::display_eq(top.get_err(), &alloc::format!("{e}"))
+}
+
+/// Returns `e` as a `core::error::Error` if it is one of the common error
+/// types from `core`, `alloc`, and `std`. `T` is known at compile time, so
+/// this folds away to a constant for each payload type.
+#[inline]
+fn known_std_error<T: 'static>(e: &T) -> Option<&(dyn core::error::Error + 'static)> {
+    let any: &dyn Any = e;
+    macro_rules! known {
+        ($($t:ty),*) => {
+            $(
+                if let Some(e) = any.downcast_ref::<$t>() {
+                    return Some(e)
+                }
+            )*
+        };
+    }
+    known!(
+        core::fmt::Error,
+        core::num::ParseIntError,
+        core::num::ParseFloatError,
+        core::num::TryFromIntError,
+        core::str::ParseBoolError,
+        core::str::Utf8Error,
+        alloc::string::FromUtf8Error
+    );
+    #[cfg(feature = "std")]
+    known!(std::io::Error);
+    None
+}
 
 /// Trait implemented for all `T: Display + Send + Sync + 'static`
 ///
-/// This is a clever workaround from
-/// <https://users.rust-lang.org/t/impossible-to-use-any-combined-with-any-other-trait/85949/5>
-/// needed to enable using a type in both `dyn Display` form for displaying and
-/// in `dyn Any + Send + Sync` form for later downcasting
+/// This was the trait object that payloads were stored as, it is no longer
+/// used by this crate. Downcasting is done through [StackedErrorDowncast] on
+/// [ErrorItem].
+#[deprecated(since = "0.8.0", note = "use `StackedErrorDowncast` on `ErrorItem`")]
 pub trait StackableErrorTrait: Display + Any + Send + Sync + 'static {
-    // put as underscores and with `#[doc(hidden)]` since this this a hack
-    // implemented for all `T` that we don't want in IDEs
     #[doc(hidden)]
     fn _as_any(&self) -> &(dyn Any + Send + Sync);
     #[doc(hidden)]
@@ -27,6 +142,7 @@ pub trait StackableErrorTrait: Display + Any + Send + Sync + 'static {
     fn _as_display(&self) -> &(dyn Display + Send + Sync);
 }
 
+#[allow(deprecated)]
 impl<T: Display + Send + Sync + 'static> StackableErrorTrait for T {
     fn _as_any(&self) -> &(dyn Any + Send + Sync) {
         self
@@ -41,7 +157,7 @@ impl<T: Display + Send + Sync + 'static> StackableErrorTrait for T {
     }
 }
 
-pub trait StackedErrorDowncast: StackableErrorTrait + Sized {
+pub trait StackedErrorDowncast: Sized {
     fn get_err(&self) -> &(impl Display + Send + Sync + 'static);
 
     fn get_location(&self) -> Option<&'static Location<'static>>;
@@ -64,18 +180,230 @@ pub trait StackedErrorDowncast: StackableErrorTrait + Sized {
 /// NOTE: this type is only public because `impl Trait` in associated types is
 /// unstable, only `StackedErrorDowncast` methods are intended to be used on
 /// this.
+///
+/// Payloads up to 4 `usize`s in size (which includes the zero sized markers
+/// like `UnitError`, `&'static str`s, and `String`s) are stored inline without
+/// a separate heap allocation.
 // The specific type that `Error` uses in its stack. NOTE the `error_kind_size`
 // should be updated whenever this is changed. pub type ErrorBox = Box<dyn
 // Display + Send + Sync + 'static>;
 pub struct ErrorItem {
-    b: SmallBox<dyn StackableErrorTrait, smallbox::space::S4>,
+    b: SmallBox<dyn Payload, smallbox::space::S4>,
+    m: FrameMeta,
+}
+
+// the built in tags cached by `ErrorItem::tags`
+const TAG_TIMEOUT: u8 = 1;
+const TAG_PROBABLY_NOT_ROOT_CAUSE: u8 = 1 << 1;
+const TAG_PANIC: u8 = 1 << 2;
+/// The cache is not up to date, set on all frames before mutable access to
+/// them is handed out and on new frames that are not in a stack yet
+const TAG_UNKNOWN: u8 = 1 << 7;
+
+/// Returns the `track_caller` location, or `None` with the "no-locations"
+/// feature
+#[inline]
+#[track_caller]
+pub(crate) fn caller() -> Option<&'static Location<'static>> {
+    #[cfg(not(feature = "no-locations"))]
+    {
+        Some(Location::caller())
+    }
+    #[cfg(feature = "no-locations")]
+    {
+        None
+    }
+}
+
+/// Takes the stack out of `e` if it is an `Error`, so that it can be merged
+/// instead of being nested as a single frame whose message is the whole
+/// report. `E` is known at compile time, so this folds away for other types.
+#[inline]
+fn take_error<E: 'static>(e: &mut E) -> Option<Error> {
+    (e as &mut dyn Any)
+        .downcast_mut::<Error>()
+        .map(|e| core::mem::replace(e, Error::empty()))
+}
+
+/// Returns the first `Ok` of `alts`, or if every alternative failed, an error
+/// with all of their failures. This is for the "try the primary, then the
+/// fallbacks" pattern. `alts` is consumed lazily, so alternatives after the
+/// first success are not evaluated if it is e.g. a `map` over closures.
+///
+/// The failures are chained in order with the first alternative at the bottom,
+/// and a [MultipleErrors] frame with the location is pushed on top.
+///
+/// ```
+/// use stacked_errors::{bail, try_alternatives, Result};
+///
+/// fn fetch(mirror: &str) -> Result<String> {
+///     if mirror == "backup" {
+///         return Ok(String::from("data"))
+///     }
+///     bail!("{mirror} is down")
+/// }
+///
+/// let data = try_alternatives(["primary", "backup"].iter().map(|m| fetch(m)));
+/// assert_eq!(data.unwrap(), "data");
+///
+/// let e = try_alternatives(["primary", "secondary"].iter().map(|m| fetch(m))).unwrap_err();
+/// let s = format!("{}", e.display_no_locations());
+/// assert_eq!(
+///     s,
+///     "\n    all 2 alternatives failed\n    secondary is down\n    primary is down"
+/// );
+/// ```
+#[track_caller]
+pub fn try_alternatives<T>(
+    alts: impl IntoIterator<Item = core::result::Result<T, Error>>,
+) -> core::result::Result<T, Error> {
+    let mut res = Error::empty();
+    let mut count = 0;
+    for alt in alts {
+        match alt {
+            Ok(o) => return Ok(o),
+            Err(e) => {
+                count += 1;
+                res = res.chain_errors(e);
+            }
+        }
+    }
+    Err(res.add_err(MultipleErrors { count }))
+}
+
+/// The classification of the root frame of an [Error] by the built in marker
+/// types, returned by [Error::kind_at_root]. More variants may be added as
+/// markers are added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum RootKind {
+    /// The root frame is a [TimeoutError] or a foreign timeout, see
+    /// [register_timeout_predicate](crate::register_timeout_predicate)
+    Timeout,
+    /// The root frame is a [ProbablyNotRootCauseError]
+    NotRootCause,
+    /// The root frame is a [UnitError], or the stack is empty
+    Unit,
+    /// The root frame is a [PanicError], or it is the payload of a caught
+    /// panic with a `PanicError` directly above it
+    Panic,
+    /// The root frame is an [AllocFailedError]
+    AllocFailed,
+    /// The root frame is anything else
+    Other,
+}
+
+/// Metadata of a frame in an [Error], accessed through
+/// [ErrorItem::meta]. More fields may be added in the future.
+#[derive(Clone)]
+#[non_exhaustive]
+pub struct FrameMeta {
     l: Option<&'static Location<'static>>,
+    /// The `TAG_*` bits of this frame and all frames below it in its stack,
+    /// so that the top frame can answer tag queries without a scan
+    tags: u8,
+    /// If the location is rendered with
+    /// [sanitize_location](crate::sanitize_location)
+    sanitize: bool,
+    /// The number of times this frame was pushed, see [Error::push_dedup]
+    n: u32,
+    /// The thread that the error originated on
+    #[cfg(feature = "thread-names")]
+    t: Option<std::thread::Thread>,
 }
 
-#[cfg(target_pointer_width = "64")]
+impl FrameMeta {
+    fn new(l: Option<&'static Location<'static>>) -> Self {
+        Self {
+            l,
+            tags: TAG_UNKNOWN,
+            sanitize: false,
+            n: 1,
+            #[cfg(feature = "thread-names")]
+            t: None,
+        }
+    }
+
+    /// Returns the location that the frame was pushed at, if it has one
+    pub fn location(&self) -> Option<&'static Location<'static>> {
+        self.l
+    }
+
+    /// Returns the number of times the frame was pushed in a row, see
+    /// [ErrorItem::occurrences]
+    pub fn occurrences(&self) -> u32 {
+        self.n
+    }
+
+    /// Returns the thread that this frame originated on. This is only
+    /// recorded by [Error::from_err], so that errors from different threads
+    /// that get chained together can be told apart.
+    #[cfg(feature = "thread-names")]
+    pub fn thread(&self) -> Option<&std::thread::Thread> {
+        self.t.as_ref()
+    }
+}
+
+impl Default for FrameMeta {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+// the tag cache is an implementation detail
+impl Debug for FrameMeta {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut f = f.debug_struct("FrameMeta");
+        f.field("location", &self.l).field("occurrences", &self.n);
+        #[cfg(feature = "thread-names")]
+        f.field("thread", &self.t);
+        f.finish_non_exhaustive()
+    }
+}
+
+// `ErrorItem` can't be packed into 3 words. The payload alone is 6 words, the
+// `SmallBox` has 4 words of inline space (so that `String`s, `&str`s, and the
+// markers do not need a separate allocation per frame) plus its fat pointer.
+// The location is one more word and can't be packed further, it already uses
+// the null niche of `&'static Location`. The `FrameMeta` fields besides the
+// location (tags, sanitize flag, and occurrence count) take the last word,
+// there are not enough alignment bits in the location pointer to hold them.
+// That is 8 words, one more than before the metadata was added. This is
+// checked at compile time so that further layout regressions fail the build.
+#[cfg(all(target_pointer_width = "64", not(feature = "thread-names")))]
+const _: () = assert!(core::mem::size_of::<ErrorItem>() == 64);
+
+#[cfg(all(target_pointer_width = "64", not(feature = "thread-names")))]
 #[test]
 fn error_kind_size() {
-    assert_eq!(core::mem::size_of::<ErrorItem>(), 56);
+    assert_eq!(core::mem::size_of::<ErrorItem>(), 64);
+    assert_eq!(core::mem::size_of::<FrameMeta>(), 16);
+}
+
+#[test]
+fn markers_inline() {
+    // `UnitError` is pushed on every `stack` call, make sure that it and the
+    // other zero sized markers never need a heap allocation
+    assert!(!ErrorItem::new(UnitError {}, None).b.is_heap());
+    assert!(!ErrorItem::new(TimeoutError {}, None).b.is_heap());
+    assert!(!ErrorItem::new(ProbablyNotRootCauseError {}, None)
+        .b
+        .is_heap());
+}
+
+#[test]
+fn small_payloads_inline() {
+    struct Words<const N: usize>([usize; N]);
+    impl<const N: usize> Display for Words<N> {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            write!(f, "{:?}", self.0)
+        }
+    }
+
+    assert!(!ErrorItem::new("static", None).b.is_heap());
+    assert!(!ErrorItem::new(String::new(), None).b.is_heap());
+    assert!(!ErrorItem::new(Words([0; 4]), None).b.is_heap());
+    assert!(ErrorItem::new(Words([0; 5]), None).b.is_heap());
 }
 
 impl ErrorItem {
@@ -83,7 +411,106 @@ impl ErrorItem {
         e: E,
         l: Option<&'static Location<'static>>,
     ) -> Self {
-        Self { b: smallbox!(e), l }
+        Self {
+            b: smallbox!(e),
+            m: FrameMeta::new(l),
+        }
+    }
+
+    /// Like [ErrorItem::new], but keeps the `core::error::Error` impl of `e`
+    /// for [ErrorItem::as_std_error]
+    fn new_std_err<E: core::error::Error + Send + Sync + 'static>(
+        e: E,
+        l: Option<&'static Location<'static>>,
+    ) -> Self {
+        Self {
+            b: smallbox!(StdErrorPayload(e)),
+            m: FrameMeta::new(l),
+        }
+    }
+
+    /// Returns if the location should be rendered with
+    /// [sanitize_location](crate::sanitize_location)
+    pub(crate) fn is_sanitized(&self) -> bool {
+        self.m.sanitize
+    }
+
+    /// Returns if this frame is a timeout, see
+    /// [register_timeout_predicate](crate::register_timeout_predicate)
+    fn is_timeout(&self) -> bool {
+        if self.downcast_ref::<TimeoutError>().is_some() {
+            return true
+        }
+        #[cfg(feature = "std")]
+        if self.downcast_ref::<std::io::Error>().is_some_and(|e| {
+            matches!(
+                e.kind(),
+                std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock
+            )
+        }) {
+            return true
+        }
+        #[cfg(feature = "tokio")]
+        if self.downcast_ref::<tokio::time::error::Elapsed>().is_some() {
+            return true
+        }
+        crate::config::matches_timeout_predicate(self)
+    }
+
+    /// Returns the `TAG_*` bits of just this frame
+    fn own_tags(&self) -> u8 {
+        let mut tags = 0;
+        if self.is_timeout() {
+            tags |= TAG_TIMEOUT;
+        }
+        if self.downcast_ref::<ProbablyNotRootCauseError>().is_some() {
+            tags |= TAG_PROBABLY_NOT_ROOT_CAUSE;
+        }
+        if self.downcast_ref::<PanicError>().is_some() {
+            tags |= TAG_PANIC;
+        }
+        tags
+    }
+
+    /// Returns the payload of this frame as a `core::error::Error`, if the
+    /// frame was created by [Error::from_std_err] or a related function, or
+    /// if it is one of the common error types from `core`, `alloc`, and
+    /// `std`. This is decided when the frame is created.
+    pub fn as_std_error(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        (*self.b).std_error()
+    }
+
+    /// Returns the metadata of this frame
+    pub fn meta(&self) -> &FrameMeta {
+        &self.m
+    }
+
+    /// Returns the number of times this frame was pushed in a row by
+    /// [Error::push_dedup] or [Error::push_err_dedup], this is 1 for all
+    /// other frames
+    pub fn occurrences(&self) -> u32 {
+        self.m.n
+    }
+
+    /// Returns the size of the error of this frame, not counting anything it
+    /// owns. This is only on the heap if the error was too large to be stored
+    /// inline.
+    pub fn payload_size(&self) -> usize {
+        core::mem::size_of_val::<dyn Payload>(&*self.b)
+    }
+
+    /// Heap bytes owned by this frame, see [Error::estimate_heap_usage]
+    fn estimate_heap_usage(&self) -> usize {
+        let mut res = 0;
+        if self.b.is_heap() {
+            res += self.payload_size();
+        }
+        if let Some(s) = self.downcast_ref::<String>() {
+            res += s.capacity();
+        } else if let Some(e) = self.downcast_ref::<Error>() {
+            res += e.estimate_heap_usage();
+        }
+        res
     }
 }
 
@@ -109,28 +536,25 @@ impl StackedErrorDowncast for ErrorItem {
     }
 
     fn get_location(&self) -> Option<&'static Location<'static>> {
-        self.l
+        self.m.location()
     }
 
     //fn downcast<E: Display + Send + Sync + 'static>(self) -> Result<E, Self> {
     //    self.0.as_any().
     //}
 
-    // the borrows here are actually needed for `_as_any` to get the correct thing
-    #[allow(clippy::needless_borrow)]
     fn downcast_ref<E>(&self) -> Option<&E>
     where
         E: Display + Send + Sync + 'static,
     {
-        (&*self.b)._as_any().downcast_ref()
+        (*self.b).any().downcast_ref()
     }
 
-    #[allow(clippy::needless_borrow)]
     fn downcast_mut<E>(&mut self) -> Option<&mut E>
     where
         E: Display + Send + Sync + 'static,
     {
-        (&mut self.b)._as_any_mut().downcast_mut()
+        (*self.b).any_mut().downcast_mut()
     }
 }
 
@@ -150,9 +574,24 @@ pub struct StackedError {
     /// possible on the stack (since we are commiting to some indirection at
     /// this point), and having the niche optimizations applied to things like
     /// `Result<(), Error>`.
+    ///
+    /// The help from [Error::with_help] is kept out of band as a hidden `Help`
+    /// item at index 0, below the frames, so that it does not take space in
+    /// `Error` itself. Everything that exposes frames goes through
+    /// `frames` to skip it.
     stack: ThinVec<ErrorItem>,
 }
 
+/// The payload of the hidden help item, see [Error::with_help]. This is
+/// private so that it can't be pushed as an ordinary frame.
+struct Help(String);
+
+impl Display for Help {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "help: {}", self.0)
+    }
+}
+
 pub type Error = StackedError;
 
 /// Note: in most cases you can use `Error::from` or a call from `StackableErr`
@@ -171,17 +610,202 @@ impl Error {
         Self::from_err(UnitError {})
     }
 
+    #[cold]
+    #[inline(never)]
     #[track_caller]
-    pub fn from_err<E: Display + Send + Sync + 'static>(e: E) -> Self {
-        Self {
-            stack: thin_vec![ErrorItem::new(e, Some(Location::caller()))],
+    pub fn from_err<E: Display + Send + Sync + 'static>(mut e: E) -> Self {
+        if let Some(mut res) = take_error(&mut e) {
+            res.push();
+            return res
         }
+        Self::from_root(ErrorItem::new(e, caller()))
     }
 
-    pub fn from_err_locationless<E: Display + Send + Sync + 'static>(e: E) -> Self {
-        Self {
-            stack: thin_vec![ErrorItem::new(e, None)],
+    /// Creates a stack with the root frame `item`, recording the thread
+    #[cfg_attr(not(feature = "thread-names"), allow(unused_mut))]
+    fn from_root(mut item: ErrorItem) -> Self {
+        #[cfg(feature = "thread-names")]
+        {
+            item.m.t = Some(std::thread::current());
+        }
+        Self::from_item(item)
+    }
+
+    fn from_item(item: ErrorItem) -> Self {
+        let mut res = Self {
+            stack: thin_vec![item],
+        };
+        res.retag(0);
+        #[cfg(debug_assertions)]
+        res.check_nested();
+        res
+    }
+
+    /// Pushes `e` with a location that was captured earlier, for when the
+    /// frame is pushed away from the `track_caller` call (e.g. in a future)
+    #[cfg(feature = "std")]
+    pub(crate) fn push_err_at<E: Display + Send + Sync + 'static>(
+        &mut self,
+        e: E,
+        l: Option<&'static Location<'static>>,
+    ) {
+        self.push_item(ErrorItem::new(e, l));
+    }
+
+    fn push_item(&mut self, item: ErrorItem) {
+        self.stack.push(item);
+        self.retag(self.stack.len() - 1);
+        #[cfg(debug_assertions)]
+        self.check_nested();
+        self.truncate_frames();
+    }
+
+    /// Handles the top frame nesting a whole `Error` in a way that the merging
+    /// in `push_err` can't see, see
+    /// [set_strict_nesting](crate::set_strict_nesting)
+    #[cfg(debug_assertions)]
+    fn check_nested(&mut self) {
+        let Some(top) = self.stack.last() else { return };
+        let nested = top.downcast_ref::<alloc::sync::Arc<Error>>().is_some()
+            || top.downcast_ref::<Box<Error>>().is_some()
+            || top.downcast_ref::<&'static Error>().is_some();
+        if !nested {
+            return
         }
+        if crate::strict_nesting() {
+            panic!(
+                "a whole `Error` was nested in a frame, pass the `Error` itself or use \
+                 `chain_errors` or `add_sub_error` to merge the stacks: {top:?}"
+            )
+        }
+        if !crate::config::report_nesting(top) {
+            self.stack
+                .push(ErrorItem::new(crate::NestedErrorWarning {}, None));
+            self.retag(self.stack.len() - 1);
+        }
+    }
+
+    /// Returns the index of the root frame, this is 1 if there is a hidden
+    /// help item
+    fn start(&self) -> usize {
+        usize::from(
+            self.stack
+                .first()
+                .is_some_and(|e| e.downcast_ref::<Help>().is_some()),
+        )
+    }
+
+    /// Returns the frames without the hidden help item
+    fn frames(&self) -> &[ErrorItem] {
+        &self.stack[self.start()..]
+    }
+
+    /// Returns the frames without the hidden help item
+    fn frames_mut(&mut self) -> &mut [ErrorItem] {
+        let start = self.start();
+        &mut self.stack[start..]
+    }
+
+    /// Removes the hidden help item if there is one
+    fn take_help(&mut self) -> Option<ErrorItem> {
+        (self.start() == 1).then(|| self.stack.remove(0))
+    }
+
+    /// Puts `help` as the hidden help item if there is none yet
+    fn set_help_if_none(&mut self, help: Option<ErrorItem>) {
+        if let Some(help) = help {
+            if self.start() == 0 {
+                self.stack.insert(0, help);
+                self.retag(0);
+            }
+        }
+    }
+
+    /// Enforces `max_frames` by dropping the frames just above the root frame
+    fn truncate_frames(&mut self) {
+        let max = crate::max_frames().max(3);
+        let start = self.start();
+        let len = self.stack.len() - start;
+        if len <= max {
+            return
+        }
+        let excess = len - max;
+        // the first frame above the root
+        let i = start + 1;
+        // markers from chained errors count all the frames they replaced
+        let count = |e: ErrorItem| e.downcast_ref::<TruncatedFrames>().map_or(1, |t| t.dropped);
+        if self.stack[i].downcast_ref::<TruncatedFrames>().is_some() {
+            let dropped: usize = self.stack.drain((i + 1)..(i + 1 + excess)).map(count).sum();
+            self.stack[i]
+                .downcast_mut::<TruncatedFrames>()
+                .unwrap()
+                .dropped += dropped;
+        } else {
+            // one more frame is dropped to make room for the marker
+            let dropped = self.stack.drain(i..(i + 1 + excess)).map(count).sum();
+            self.stack
+                .insert(i, ErrorItem::new(TruncatedFrames { dropped }, None));
+        }
+        self.retag(i);
+    }
+
+    /// Updates the cached tags of the frames from index `from` upwards
+    fn retag(&mut self, mut from: usize) {
+        let mut tags = 0;
+        if let Some(below) = from.checked_sub(1).map(|i| self.stack[i].m.tags) {
+            if (below & TAG_UNKNOWN) == 0 {
+                tags = below;
+            } else {
+                // everything was invalidated together
+                from = 0;
+            }
+        }
+        for e in &mut self.stack[from..] {
+            tags |= e.own_tags();
+            e.m.tags = tags;
+        }
+    }
+
+    /// Invalidates the cached tags before mutable access to the frames is
+    /// handed out, until the next push the tags are found by scanning
+    fn untag(&mut self) {
+        for e in &mut self.stack {
+            e.m.tags = TAG_UNKNOWN;
+        }
+    }
+
+    /// Returns the `TAG_*` bits of the whole stack
+    fn tags(&self) -> u8 {
+        match self.stack.last() {
+            None => 0,
+            Some(e) if (e.m.tags & TAG_UNKNOWN) == 0 => e.m.tags,
+            Some(_) => self.stack.iter().fold(0, |tags, e| tags | e.own_tags()),
+        }
+    }
+
+    /// Returns an error stack with `s` and location information. This is the
+    /// same as `from_err(s)`, `&'static str`s are stored inline so the only
+    /// allocation is that of the stack itself.
+    ///
+    /// ```
+    /// use stacked_errors::{Error, StackedErrorDowncast};
+    ///
+    /// let e = Error::static_str("unsupported operation");
+    /// let s = e.iter().next().unwrap().downcast_ref::<&str>().unwrap();
+    /// assert_eq!(*s, "unsupported operation");
+    /// ```
+    #[track_caller]
+    pub fn static_str(s: &'static str) -> Self {
+        Self::from_err(s)
+    }
+
+    #[cold]
+    #[inline(never)]
+    pub fn from_err_locationless<E: Display + Send + Sync + 'static>(mut e: E) -> Self {
+        if let Some(res) = take_error(&mut e) {
+            return res
+        }
+        Self::from_item(ErrorItem::new(e, None))
     }
 
     /// Only pushes `track_caller` location to the stack
@@ -196,10 +820,17 @@ impl Error {
         self.add_err(UnitError {})
     }
 
-    /// Pushes error `e` with location to the stack
+    /// Pushes error `e` with location to the stack. If `e` is an `Error`, its
+    /// frames are moved on top (like [Error::chain_errors]) followed by a
+    /// location-only frame, instead of nesting it as a single frame.
     #[track_caller]
-    pub fn push_err<E: Display + Send + Sync + 'static>(&mut self, e: E) {
-        self.stack.push(ErrorItem::new(e, Some(Location::caller())));
+    pub fn push_err<E: Display + Send + Sync + 'static>(&mut self, mut e: E) {
+        if let Some(other) = take_error(&mut e) {
+            self.chain_in_place(other);
+            self.push();
+            return
+        }
+        self.push_item(ErrorItem::new(e, caller()));
     }
 
     /// Adds error `e` with location to the stack
@@ -209,9 +840,50 @@ impl Error {
         self
     }
 
-    /// Pushes error `e` without location information to the stack
-    pub fn push_err_locationless<E: Display + Send + Sync + 'static>(&mut self, e: E) {
-        self.stack.push(ErrorItem::new(e, None));
+    /// Pushes error `e` without location information to the stack. If `e` is
+    /// an `Error`, its frames are moved on top like [Error::chain_errors].
+    pub fn push_err_locationless<E: Display + Send + Sync + 'static>(&mut self, mut e: E) {
+        if let Some(other) = take_error(&mut e) {
+            self.chain_in_place(other);
+            return
+        }
+        self.push_item(ErrorItem::new(e, None));
+    }
+
+    /// Like [Error::push], except that if the newest frame is a location-only
+    /// frame at the same location, its [ErrorItem::occurrences] is
+    /// incremented instead of pushing a new frame. This keeps retry loops from
+    /// growing the stack, the frame is rendered with a `(xN)` count.
+    ///
+    /// ```
+    /// use stacked_errors::Error;
+    ///
+    /// let mut e = Error::from_err("root");
+    /// for _ in 0..3 {
+    ///     e.push_dedup();
+    /// }
+    /// assert_eq!(e.iter().len(), 2);
+    /// assert_eq!(e.iter().last().unwrap().occurrences(), 3);
+    /// assert!(format!("{e}").contains(" (x3)"));
+    /// ```
+    #[track_caller]
+    pub fn push_dedup(&mut self) {
+        self.push_err_dedup(UnitError {})
+    }
+
+    /// Like [Error::push_err], except that if the newest frame has the same
+    /// location and the same `Display`ed message as `e`, its
+    /// [ErrorItem::occurrences] is incremented instead of pushing a new frame
+    #[track_caller]
+    pub fn push_err_dedup<E: Display + Send + Sync + 'static>(&mut self, e: E) {
+        let l = caller();
+        if let Some(top) = self.frames_mut().last_mut() {
+            if (top.get_location() == l) && same_message(top, &e) {
+                top.m.n = top.m.n.saturating_add(1);
+                return
+            }
+        }
+        self.push_item(ErrorItem::new(e, l));
     }
 
     /// Adds error `e` without location information to the stack
@@ -220,12 +892,216 @@ impl Error {
         self
     }
 
-    /// Moves the stack of `other` onto `self`
-    pub fn chain_errors(mut self, mut other: Self) -> Self {
-        self.stack.append(&mut other.stack);
+    /// Pushes each of `msgs` as a frame without location information, for
+    /// importing a list of messages (e.g. accumulated validation failures)
+    /// into one error. The first message becomes the lowest of the new
+    /// frames.
+    ///
+    /// ```
+    /// use stacked_errors::Error;
+    ///
+    /// let mut e = Error::from_err("validation failed");
+    /// e.push_messages(["name is empty", "age is negative"]);
+    /// assert_eq!(e.iter().len(), 3);
+    /// ```
+    pub fn push_messages<I: IntoIterator<Item = S>, S: Into<String>>(&mut self, msgs: I) {
+        let msgs = msgs.into_iter();
+        self.stack.reserve(msgs.size_hint().0);
+        for msg in msgs {
+            self.push_item(ErrorItem::new(msg.into(), None));
+        }
+    }
+
+    /// Returns an empty error stack with space for `capacity` frames, for
+    /// avoiding reallocations when many frames are about to be pushed.
+    /// Together with [Error::try_push_err] this also allows building errors
+    /// without allocating at the time of the error, see there for more.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            stack: ThinVec::with_capacity(capacity),
+        }
+    }
+
+    /// Reserves space for at least `additional` more frames, see
+    /// [Error::with_capacity]
+    pub fn reserve(&mut self, additional: usize) {
+        self.stack.reserve(additional)
+    }
+
+    /// Returns the number of frames that the stack can hold without
+    /// reallocating
+    pub fn capacity(&self) -> usize {
+        self.stack.capacity()
+    }
+
+    /// Returns an estimate of the number of heap bytes owned by this error,
+    /// for diagnosing the memory retained by long lived errors. This counts
+    /// the stack buffer, errors that were too large to be stored inline, and
+    /// the buffers of `String` errors. Other allocations owned by errors (e.g.
+    /// the inside of a `Box` or `Vec` payload) and allocator overhead are not
+    /// counted.
+    ///
+    /// ```
+    /// use stacked_errors::Error;
+    ///
+    /// let small = Error::from_err("root");
+    /// let large = Error::from_err("root").add_err("x".repeat(1000));
+    /// assert!(large.estimate_heap_usage() > small.estimate_heap_usage() + 1000);
+    /// ```
+    pub fn estimate_heap_usage(&self) -> usize {
+        if self.stack.capacity() == 0 {
+            return 0
+        }
+        // the length and capacity header
+        let mut res = 2 * core::mem::size_of::<usize>();
+        res += self.stack.capacity() * core::mem::size_of::<ErrorItem>();
+        for e in &self.stack {
+            res += e.estimate_heap_usage();
+        }
+        res
+    }
+
+    /// Shrinks the capacity of the stack as much as possible, for giving back
+    /// memory after removing frames
+    ///
+    /// ```
+    /// use stacked_errors::Error;
+    ///
+    /// let mut e = Error::with_capacity(64);
+    /// e.push_err("root");
+    /// assert!(e.capacity() >= 64);
+    /// e.shrink_to_fit();
+    /// assert_eq!(e.capacity(), 1);
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        self.stack.shrink_to_fit()
+    }
+
+    /// Like [Error::push], but never allocates, see [Error::try_push_err]
+    #[track_caller]
+    pub fn try_push(&mut self) -> bool {
+        self.try_push_err(UnitError {})
+    }
+
+    /// Like [Error::push_err], but never allocates. Returns `true` if `e` was
+    /// pushed. If the stack has no spare capacity nothing is pushed, and if
+    /// `e` is too large to be stored inline (more than 4 `usize`s) an
+    /// [AllocFailedError] with the location is pushed instead of `e`.
+    ///
+    /// This is for heap constrained targets where failing to allocate aborts,
+    /// with enough space reserved ahead of time with [Error::with_capacity] or
+    /// [Error::reserve], frames can be pushed even after the heap runs out.
+    /// Note that every other way of creating an `Error` or adding to it can
+    /// allocate, and so can formatting.
+    ///
+    /// ```
+    /// use stacked_errors::{AllocFailedError, Error, StackedErrorDowncast};
+    ///
+    /// struct Large([u64; 8]);
+    /// impl std::fmt::Display for Large {
+    ///     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    ///         write!(f, "{:?}", self.0)
+    ///     }
+    /// }
+    ///
+    /// // reserved while memory is available
+    /// let mut e = Error::with_capacity(2);
+    ///
+    /// assert!(e.try_push_err("static message"));
+    /// assert!(!e.try_push_err(Large([0; 8])));
+    /// assert!(!e.try_push());
+    /// assert_eq!(e.iter().len(), 2);
+    /// let top = e.iter().last().unwrap();
+    /// assert!(top.downcast_ref::<AllocFailedError>().is_some());
+    /// ```
+    #[track_caller]
+    pub fn try_push_err<E: Display + Send + Sync + 'static>(&mut self, e: E) -> bool {
+        if self.stack.len() >= self.stack.capacity() {
+            return false
+        }
+        // matches the `S4` space of `ErrorItem`
+        let inline = (core::mem::size_of::<E>() <= core::mem::size_of::<[usize; 4]>())
+            && (core::mem::align_of::<E>() <= core::mem::align_of::<[usize; 4]>());
+        if inline {
+            self.push_item(ErrorItem::new(e, caller()));
+        } else {
+            self.push_item(ErrorItem::new(AllocFailedError {}, caller()));
+        }
+        inline
+    }
+
+    /// Moves the stack of `other` onto `self`. The frames of `other` end up on
+    /// top, so `self` contains the root cause and is displayed last. If both
+    /// have a help message (see [Error::with_help]), the one of `self` is
+    /// kept.
+    ///
+    /// ```
+    /// use stacked_errors::Error;
+    ///
+    /// let cause = Error::from_err_locationless("root cause");
+    /// let context = Error::from_err_locationless("context");
+    /// assert_eq!(
+    ///     format!("{}", cause.chain_errors(context)),
+    ///     "\n    context\n    root cause"
+    /// );
+    /// ```
+    pub fn chain_errors(mut self, other: Self) -> Self {
+        self.chain_in_place(other);
         self
     }
 
+    fn chain_in_place(&mut self, mut other: Self) {
+        self.set_help_if_none(other.take_help());
+        let len = self.stack.len();
+        self.stack.append(&mut other.stack);
+        self.retag(len);
+        self.truncate_frames();
+    }
+
+    /// Moves the stack of `cause` under `self`. This is the reverse of
+    /// [Error::chain_errors], the frames of `cause` end up at the bottom, so
+    /// `cause` contains the root cause and is displayed last. If both have a
+    /// help message, the one of `self` is kept.
+    ///
+    /// ```
+    /// use stacked_errors::Error;
+    ///
+    /// let cause = Error::from_err_locationless("root cause");
+    /// let context = Error::from_err_locationless("context");
+    /// assert_eq!(
+    ///     format!("{}", context.chain_errors_under(cause)),
+    ///     "\n    context\n    root cause"
+    /// );
+    /// ```
+    pub fn chain_errors_under(mut self, mut cause: Self) -> Self {
+        if let Some(help) = self.take_help() {
+            drop(cause.take_help());
+            cause.set_help_if_none(Some(help));
+        }
+        let len = cause.stack.len();
+        cause.stack.append(&mut self.stack);
+        cause.retag(len);
+        cause.truncate_frames();
+        cause
+    }
+
+    /// Adds the frames of `sub` on top of `self` followed by a location-only
+    /// frame. This is what [Error::add_err] does when given an `Error`, use it
+    /// to attach an `Error` that was taken out of an `Arc` or `Box` instead of
+    /// nesting the `Arc` or `Box` in a single frame.
+    ///
+    /// ```
+    /// use stacked_errors::Error;
+    ///
+    /// let sub = Error::from_err_locationless("sub error");
+    /// let e = Error::from_err_locationless("main error").add_sub_error(sub);
+    /// assert_eq!(e.iter().len(), 3);
+    /// ```
+    #[track_caller]
+    pub fn add_sub_error(self, sub: Error) -> Self {
+        self.add_err(sub)
+    }
+
     /// Returns a base `TimeoutError` error
     #[track_caller]
     pub fn timeout() -> Self {
@@ -238,34 +1114,395 @@ impl Error {
         Self::from_err(ProbablyNotRootCauseError {})
     }
 
-    /// Returns if a `TimeoutError` is in the error stack
+    /// Returns if a `TimeoutError` or a foreign timeout (see
+    /// [register_timeout_predicate](crate::register_timeout_predicate)) is in
+    /// the error stack. This is O(1), the built in tags are cached as frames
+    /// are pushed.
     pub fn is_timeout(&self) -> bool {
-        for e in &self.stack {
-            if e.downcast_ref::<TimeoutError>().is_some() {
-                return true
+        (self.tags() & TAG_TIMEOUT) != 0
+    }
+
+    /// Returns if a `ProbablyNotRootCauseError` is in the error stack. This is
+    /// O(1) like [Error::is_timeout].
+    pub fn is_probably_not_root_cause(&self) -> bool {
+        (self.tags() & TAG_PROBABLY_NOT_ROOT_CAUSE) != 0
+    }
+
+    /// Like [Error::from_err], but the frame keeps the `core::error::Error`
+    /// impl of `e`, so that it is yielded by [Error::iter_std_errors]
+    #[cold]
+    #[inline(never)]
+    #[track_caller]
+    pub fn from_std_err<E: core::error::Error + Send + Sync + 'static>(mut e: E) -> Self {
+        if let Some(mut res) = take_error(&mut e) {
+            res.push();
+            return res
+        }
+        Self::from_root(ErrorItem::new_std_err(e, caller()))
+    }
+
+    /// Like [Error::push_err], but the frame keeps the `core::error::Error`
+    /// impl of `e`, so that it is yielded by [Error::iter_std_errors]
+    #[track_caller]
+    pub fn push_std_err<E: core::error::Error + Send + Sync + 'static>(&mut self, mut e: E) {
+        if let Some(other) = take_error(&mut e) {
+            self.chain_in_place(other);
+            self.push();
+            return
+        }
+        self.push_item(ErrorItem::new_std_err(e, caller()));
+    }
+
+    /// Like [Error::add_err], but the frame keeps the `core::error::Error`
+    /// impl of `e`, so that it is yielded by [Error::iter_std_errors]
+    #[track_caller]
+    pub fn add_std_err<E: core::error::Error + Send + Sync + 'static>(mut self, e: E) -> Self {
+        self.push_std_err(e);
+        self
+    }
+
+    /// Iteration over the frames of `self` that are `core::error::Error`s,
+    /// starting from the bottom of the stack. See [ErrorItem::as_std_error]
+    /// for which frames are recognized, other error types have to be added
+    /// with [Error::add_std_err] or a related function to be yielded.
+    ///
+    /// ```
+    /// use stacked_errors::{Error, StackableErr};
+    ///
+    /// #[derive(Debug, thiserror::Error)]
+    /// #[error("bad request")]
+    /// struct BadRequest;
+    ///
+    /// let e = "x"
+    ///     .parse::<u8>()
+    ///     .stack_err("parsing the port")
+    ///     .unwrap_err()
+    ///     .add_std_err(BadRequest)
+    ///     .add_err(BadRequest);
+    /// let errs: Vec<String> = e.iter_std_errors().map(|e| e.to_string()).collect();
+    /// // the last `BadRequest` was added with `add_err`
+    /// assert_eq!(errs, ["invalid digit found in string", "bad request"]);
+    /// ```
+    pub fn iter_std_errors(&self) -> impl Iterator<Item = &(dyn core::error::Error + 'static)> {
+        self.frames().iter().filter_map(|e| e.as_std_error())
+    }
+
+    /// Returns if a `PanicError` is in the error stack. This is O(1) like
+    /// [Error::is_timeout].
+    pub fn is_panic(&self) -> bool {
+        (self.tags() & TAG_PANIC) != 0
+    }
+
+    /// Returns if an error of type `E` is in the error stack. Unlike the
+    /// queries for the built in tags, this scans the whole stack.
+    ///
+    /// ```
+    /// use stacked_errors::Error;
+    ///
+    /// let e = Error::from_err(String::from("root")).add_err(42u64);
+    /// assert!(e.contains::<u64>());
+    /// assert!(!e.contains::<u32>());
+    /// ```
+    pub fn contains<E: Display + Send + Sync + 'static>(&self) -> bool {
+        self.frames()
+            .iter()
+            .any(|e| e.downcast_ref::<E>().is_some())
+    }
+
+    /// Returns if every frame in the error stack is a `UnitError`, meaning
+    /// that the error only has locations and no actual message (e.g. from a
+    /// `None.stack()`). This is also true for an empty stack. Callers can use
+    /// this to add a better message before propagating the error.
+    pub fn is_unit_only(&self) -> bool {
+        for e in self.frames() {
+            if e.downcast_ref::<UnitError>().is_none() {
+                return false
             }
         }
-        false
+        true
     }
 
-    /// Returns if a `ProbablyNotRootCauseError` is in the error stack
-    pub fn is_probably_not_root_cause(&self) -> bool {
-        for e in &self.stack {
-            if e.downcast_ref::<ProbablyNotRootCauseError>().is_some() {
-                return true
+    /// Returns the kind of the root frame (the bottom of the stack), for
+    /// branching on the built in markers without `downcast_ref` chains
+    ///
+    /// ```
+    /// use stacked_errors::{Error, RootKind};
+    ///
+    /// fn retry_delay(e: &Error) -> Option<u64> {
+    ///     match e.kind_at_root() {
+    ///         RootKind::Timeout => Some(100),
+    ///         RootKind::NotRootCause => Some(1000),
+    ///         _ => None,
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(retry_delay(&Error::timeout().add_err("polling")), Some(100));
+    /// assert_eq!(retry_delay(&Error::from_err("bad input")), None);
+    /// ```
+    pub fn kind_at_root(&self) -> RootKind {
+        let frames = self.frames();
+        let Some(root) = frames.first() else {
+            return RootKind::Unit
+        };
+        if root.is_timeout() {
+            RootKind::Timeout
+        } else if root.downcast_ref::<ProbablyNotRootCauseError>().is_some() {
+            RootKind::NotRootCause
+        } else if root.downcast_ref::<UnitError>().is_some() {
+            RootKind::Unit
+        } else if root.downcast_ref::<PanicError>().is_some()
+            || frames
+                .get(1)
+                .is_some_and(|e| e.downcast_ref::<PanicError>().is_some())
+        {
+            RootKind::Panic
+        } else if root.downcast_ref::<AllocFailedError>().is_some() {
+            RootKind::AllocFailed
+        } else {
+            RootKind::Other
+        }
+    }
+
+    /// Returns the number of distinct source locations in the error stack,
+    /// ignoring locationless frames. Frames pushed from the same code path
+    /// (e.g. by retries) count once, which is useful for metrics and alert
+    /// deduplication.
+    pub fn distinct_locations(&self) -> usize {
+        let mut locations: alloc::vec::Vec<(&str, u32, u32)> = self
+            .frames()
+            .iter()
+            .filter_map(|e| e.get_location())
+            .map(|l| (l.file(), l.line(), l.column()))
+            .collect();
+        locations.sort_unstable();
+        locations.dedup();
+        locations.len()
+    }
+
+    /// Replaces the error of the frame at `index` (with 0 being the bottom of
+    /// the stack) with `e`, keeping the location of the frame. Returns `false`
+    /// and does nothing if `index` is out of bounds.
+    ///
+    /// ```
+    /// use stacked_errors::Error;
+    ///
+    /// let mut e = Error::from_err_locationless("root").add_err_locationless("context");
+    /// assert!(e.replace_err(1, "localized context"));
+    /// assert!(!e.replace_err(2, "out of bounds"));
+    /// assert_eq!(format!("{e}"), "\n    localized context\n    root");
+    /// ```
+    pub fn replace_err<E: Display + Send + Sync + 'static>(&mut self, index: usize, e: E) -> bool {
+        let index = index + self.start();
+        if let Some(item) = self.stack.get_mut(index) {
+            item.b = smallbox!(e);
+            self.retag(index);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns the owned error if `self` consists of exactly one frame and
+    /// the error of that frame is of type `E`, otherwise `self` is returned
+    /// unchanged.
+    ///
+    /// ```
+    /// use stacked_errors::Error;
+    ///
+    /// let e = Error::from_err(String::from("single"));
+    /// assert_eq!(e.try_into_single::<String>().unwrap(), "single");
+    ///
+    /// let e = Error::from_err(String::from("root")).add();
+    /// assert_eq!(e.try_into_single::<String>().unwrap_err().iter().len(), 2);
+    /// ```
+    pub fn try_into_single<E: Display + Send + Sync + 'static>(mut self) -> Result<E, Self> {
+        if (self.frames().len() != 1) || self.frames()[0].downcast_ref::<E>().is_none() {
+            return Err(self)
+        }
+        let item = self.stack.pop().unwrap();
+        let mut b = ManuallyDrop::new(item.b);
+        let is_heap = b.is_heap();
+        let ptr: *mut E = (**b).any_mut().downcast_mut::<E>().unwrap();
+        // SAFETY: the type was checked above, and `b` is never dropped so the value is
+        // only moved out once. If the value is on the heap, `SmallBox` allocated it
+        // with the global allocator and `Layout::new::<E>()` like a `Box<E>` (this is
+        // the same assumption that `SmallBox::into_inner` makes, and a
+        // `StdErrorPayload<E>` is `repr(transparent)` so it has the same layout), so
+        // rebuilding the `Box` frees the allocation. Otherwise the value is stored
+        // inline in `b` and there is nothing else to free.
+        unsafe {
+            if is_heap {
+                Ok(*Box::from_raw(ptr))
+            } else {
+                Ok(ptr::read(ptr))
             }
         }
-        false
+    }
+
+    /// Removes location-only frames (frames with a `UnitError`) whose file
+    /// starts with `file_prefix`. This is for cleaning up frames captured
+    /// inside of wrapper functions, frames with other errors are never
+    /// removed.
+    ///
+    /// ```
+    /// use stacked_errors::{Error, StackableErr};
+    ///
+    /// let mut e = Error::from_err_locationless("root").add();
+    /// assert_eq!(e.iter().len(), 2);
+    /// e.strip_frames_in("src/");
+    /// assert_eq!(e.iter().len(), 1);
+    /// ```
+    pub fn strip_frames_in(&mut self, file_prefix: &str) {
+        self.stack.retain(|e| {
+            !(e.downcast_ref::<UnitError>().is_some()
+                && e.get_location()
+                    .is_some_and(|l| l.file().starts_with(file_prefix)))
+        });
+        self.retag(0);
+    }
+
+    /// Removes frames that are a `UnitError` without a location, these render
+    /// as nothing and are left behind by e.g. `stack_locationless` on a `None`.
+    /// Location-only frames and frames with other errors are kept.
+    ///
+    /// ```
+    /// use stacked_errors::{Error, UnitError};
+    ///
+    /// let mut e = Error::from_err_locationless("root")
+    ///     .add_err_locationless(UnitError {})
+    ///     .add();
+    /// e.retain_meaningful();
+    /// assert_eq!(e.iter().len(), 2);
+    /// ```
+    pub fn retain_meaningful(&mut self) {
+        self.stack
+            .retain(|e| !(e.downcast_ref::<UnitError>().is_some() && e.get_location().is_none()));
+        self.retag(0);
+    }
+
+    /// Attaches a user-facing `help` suggestion to `self`, replacing any
+    /// previous one. The help is not a frame, it is not returned by
+    /// [Error::iter] or the other frame accessors and is not counted by
+    /// `max_frames`. `Display` and `Debug` render it last as `help: ...`.
+    ///
+    /// ```
+    /// use stacked_errors::Error;
+    ///
+    /// let e = Error::from_err_locationless("no config").with_help("run `init` first");
+    /// assert_eq!(e.help(), Some("run `init` first"));
+    /// assert_eq!(format!("{e}"), "\n    no config\n  help: run `init` first");
+    /// ```
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        drop(self.take_help());
+        self.set_help_if_none(Some(ErrorItem::new(Help(help.into()), None)));
+        self
+    }
+
+    /// Returns the help attached by [Error::with_help], if any
+    pub fn help(&self) -> Option<&str> {
+        self.stack
+            .first()
+            .and_then(|e| e.downcast_ref::<Help>())
+            .map(|h| h.0.as_str())
+    }
+
+    /// Makes the formatters render the locations of the current frames with
+    /// [sanitize_location](crate::sanitize_location), so that home directories
+    /// of the machine the binary was built on are not leaked in shipped error
+    /// messages. `Location`s can't be changed, so
+    /// [StackedErrorDowncast::get_location] still returns the original
+    /// location. Frames pushed afterwards are not affected.
+    ///
+    /// ```
+    /// use stacked_errors::{Error, StackableErr};
+    ///
+    /// // locations of the current crate are relative, only dependencies built
+    /// // from source elsewhere have absolute paths
+    /// let mut e = Error::from_err("root").add();
+    /// let before = format!("{e}");
+    /// e.sanitize_locations();
+    /// assert_eq!(format!("{e}"), before);
+    /// ```
+    pub fn sanitize_locations(&mut self) {
+        for e in &mut self.stack {
+            e.m.sanitize = true;
+        }
+    }
+
+    /// Returns the [StackedErrorDowncast] items of `self` as a slice, the
+    /// first item is the bottom of the stack
+    pub fn as_slice(&self) -> &[ErrorItem] {
+        self.frames()
+    }
+
+    /// Finds the first frame from the bottom of the stack with an error of
+    /// type `E`, and returns the frames below it and the frames above it.
+    /// For example, splitting at a `TimeoutError` separates the underlying
+    /// error from the context added after the timeout.
+    ///
+    /// ```
+    /// use stacked_errors::{Error, StackedErrorDowncast, TimeoutError};
+    ///
+    /// let e = Error::from_err("underlying")
+    ///     .add_err(TimeoutError {})
+    ///     .add_err("context");
+    /// let (below, above) = e.split_at_marker::<TimeoutError>().unwrap();
+    /// assert_eq!(below.len(), 1);
+    /// assert_eq!(above.len(), 1);
+    /// assert_eq!(*above[0].downcast_ref::<&str>().unwrap(), "context");
+    /// assert!(e.split_at_marker::<u64>().is_none());
+    /// ```
+    pub fn split_at_marker<E: Display + Send + Sync + 'static>(
+        &self,
+    ) -> Option<(&[ErrorItem], &[ErrorItem])> {
+        let frames = self.frames();
+        let i = frames
+            .iter()
+            .position(|e| e.downcast_ref::<E>().is_some())?;
+        Some((&frames[..i], &frames[(i + 1)..]))
+    }
+
+    /// Returns the [StackedErrorDowncast] items of `self` as a mutable slice,
+    /// the first item is the bottom of the stack
+    pub fn as_mut_slice(&mut self) -> &mut [ErrorItem] {
+        self.untag();
+        self.frames_mut()
     }
 
     /// Iteration over the [StackedErrorDowncast] items of `self`
-    pub fn iter(&self) -> Iter<ErrorItem> {
-        self.stack.iter()
+    pub fn iter(&self) -> Iter<'_, ErrorItem> {
+        self.frames().iter()
     }
 
     /// Mutable iteration over the [StackedErrorDowncast] items of `self`
-    pub fn iter_mut(&mut self) -> IterMut<ErrorItem> {
-        self.stack.iter_mut()
+    pub fn iter_mut(&mut self) -> IterMut<'_, ErrorItem> {
+        self.untag();
+        self.frames_mut().iter_mut()
+    }
+
+    /// Iteration over the errors of `self` and their locations, skipping the
+    /// location-only frames (frames with a `UnitError`). The first item is the
+    /// bottom of the stack.
+    ///
+    /// ```
+    /// use stacked_errors::{Error, StackableErr};
+    ///
+    /// let e = Error::from_err("root").add().add_err("context");
+    /// let messages: Vec<String> = e.messages().map(|(e, _)| e.to_string()).collect();
+    /// assert_eq!(messages, ["root", "context"]);
+    /// ```
+    pub fn messages(
+        &self,
+    ) -> impl Iterator<
+        Item = (
+            &(dyn Display + Send + Sync),
+            Option<&'static Location<'static>>,
+        ),
+    > {
+        self.frames()
+            .iter()
+            .filter(|e| e.downcast_ref::<UnitError>().is_none())
+            .map(|e| ((*e.b).display(), e.m.location()))
     }
 }
 
@@ -287,6 +1524,18 @@ impl<'a> IntoIterator for &'a mut Error {
     }
 }
 
+impl AsRef<[ErrorItem]> for Error {
+    fn as_ref(&self) -> &[ErrorItem] {
+        self.as_slice()
+    }
+}
+
+impl AsMut<[ErrorItem]> for Error {
+    fn as_mut(&mut self) -> &mut [ErrorItem] {
+        self.as_mut_slice()
+    }
+}
+
 impl Default for Error {
     #[track_caller]
     fn default() -> Self {
@@ -296,6 +1545,66 @@ impl Default for Error {
 
 impl core::error::Error for Error {}
 
+// Like `anyhow::Error`, errors are unwind safe regardless of their payloads, so
+// that this does not change with the auto traits of payload types from other
+// crates. Payloads are only reachable through `&self` and `&mut self` methods,
+// so the only thing a panic can leave behind is a payload that was halfway
+// through being mutated through `downcast_mut`, which is the same as for any
+// owned value moved across `catch_unwind`.
+impl UnwindSafe for ErrorItem {}
+impl RefUnwindSafe for ErrorItem {}
+impl UnwindSafe for Error {}
+impl RefUnwindSafe for Error {}
+
+#[cfg(feature = "std")]
+impl Error {
+    /// Wraps `self` in a `std::io::Error`, for returning from functions with
+    /// `io::Result` signatures. The kind is that of the bottommost
+    /// `io::Error` frame if there is one, and `ErrorKind::Other` otherwise.
+    /// [Error::from_io_error] recovers `self`.
+    ///
+    /// ```
+    /// use std::io;
+    ///
+    /// use stacked_errors::{Error, StackableErr};
+    ///
+    /// fn open() -> io::Result<()> {
+    ///     let e = Error::from_err(io::Error::from(io::ErrorKind::NotFound));
+    ///     Err(e.add_err("while opening the config").into_io_error())
+    /// }
+    ///
+    /// let e = open().unwrap_err();
+    /// assert_eq!(e.kind(), io::ErrorKind::NotFound);
+    /// let e = Error::from_io_error(e);
+    /// assert_eq!(e.iter().len(), 2);
+    /// ```
+    pub fn into_io_error(self) -> std::io::Error {
+        let kind = self
+            .iter()
+            .find_map(|e| e.downcast_ref::<std::io::Error>())
+            .map_or(std::io::ErrorKind::Other, |e| e.kind());
+        std::io::Error::new(kind, self)
+    }
+
+    /// Converts `e` into an `Error`. If `e` was created by
+    /// [Error::into_io_error], the original `Error` is returned unchanged,
+    /// otherwise this is the same as [Error::from_err].
+    #[track_caller]
+    pub fn from_io_error(e: std::io::Error) -> Self {
+        match e.downcast::<Error>() {
+            Ok(e) => e,
+            Err(e) => Error::from_err(e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<Error> for std::io::Error {
+    fn from(e: Error) -> Self {
+        e.into_io_error()
+    }
+}
+
 // there is a blanket impl collision, but I don't think we want to impl this
 // anyway since without it it makes sure we have `stack` calls at the error
 // origin
@@ -1,10 +1,12 @@
-use alloc::{fmt, fmt::Debug};
-use core::fmt::Display;
-use std::fmt::Write;
+use alloc::{borrow::Cow, fmt, fmt::Debug, string::String, vec, vec::Vec};
+use core::fmt::{Display, Write};
 
 use owo_colors::{CssColors, OwoColorize, Style};
 
-use crate::{error::StackedErrorDowncast, Error, UnitError};
+use crate::{
+    error::{ErrorItem, StackedErrorDowncast},
+    Error, UnitError,
+};
 
 /// For implementing `Debug`, this wrapper makes strings use their `Display`
 /// impl rather than `Debug` impl
@@ -15,74 +17,460 @@ impl Debug for DisplayStr<'_> {
     }
 }
 
+/// The patterns used by [shorten_location]
+#[cfg(not(windows))]
+pub const DEFAULT_LOCATION_PATTERNS: &[&str] = &["/.cargo/registry/src/*/"];
+/// The patterns used by [shorten_location]
+#[cfg(windows)]
+pub const DEFAULT_LOCATION_PATTERNS: &[&str] = &["\\.cargo\\registry\\src\\*\\"];
+
 /// Intended for shortening the file field of `Location`s.
 ///
-/// If this finds "/.cargo/registry/src/", it truncates that and all previous
-/// characters, and the following "/" group if it exists (it is alternately
-/// configured to do this with "\\" on Windows). For example, "/home/admin/.
-/// cargo/registry/src/index.crates.io-6f17d22bba15001f/ super_orchestrator-0.5.
-/// 1/src/misc.rs" gets truncated to "super_orchestrator-0.5.1/src/misc.rs"
-pub fn shorten_location(mut s: &str) -> &str {
-    #[cfg(not(windows))]
-    {
-        let find = "/.cargo/registry/src/";
-        if let Some(i) = s.find(find) {
-            s = &s[(i + find.len())..];
-            if let Some(i) = s.find('/') {
-                s = &s[(i + 1)..];
+/// This is [shorten_location_with] using [DEFAULT_LOCATION_PATTERNS], which
+/// truncates "/.cargo/registry/src/", all previous characters, and the
+/// following "/" group (it is alternately configured to do this with "\\" on
+/// Windows). For example, "/home/admin/.cargo/registry/src/index.crates.
+/// io-6f17d22bba15001f/super_orchestrator-0.5.1/src/misc.rs" gets truncated to
+/// "super_orchestrator-0.5.1/src/misc.rs"
+pub fn shorten_location(s: &str) -> &str {
+    shorten_location_with(s, DEFAULT_LOCATION_PATTERNS)
+}
+
+/// Replaces a leading home directory in `s` with "~", for example
+/// "/home/alice/projects/app/src/main.rs" becomes "~/projects/app/src/main.rs".
+/// Home directories are recognized by their standard locations ("/home/*/",
+/// "/Users/*/", "/root/", and "C:\\Users\\*\\" with any drive letter), `s`
+/// is returned unchanged otherwise. See [Error::sanitize_locations].
+///
+/// ```
+/// use stacked_errors::sanitize_location;
+///
+/// assert_eq!(
+///     sanitize_location("/home/alice/projects/app/src/main.rs"),
+///     "~/projects/app/src/main.rs"
+/// );
+/// assert_eq!(
+///     sanitize_location("C:\\Users\\bob\\app\\src\\main.rs"),
+///     "~\\app\\src\\main.rs"
+/// );
+/// assert_eq!(sanitize_location("src/main.rs"), "src/main.rs");
+/// ```
+pub fn sanitize_location(s: &str) -> Cow<'_, str> {
+    // the length of the home directory including the trailing separator
+    let mut home = None;
+    for prefix in ["/home/", "/Users/"] {
+        if let Some(rest) = s.strip_prefix(prefix) {
+            home = rest.find('/').map(|i| prefix.len() + i + 1);
+        }
+    }
+    if s.starts_with("/root/") {
+        home = Some("/root/".len());
+    }
+    let b = s.as_bytes();
+    if (b.len() > 3) && b[0].is_ascii_alphabetic() && (&b[1..3] == b":\\") {
+        if let Some(rest) = s[3..].strip_prefix("Users\\") {
+            home = rest.find('\\').map(|i| 3 + "Users\\".len() + i + 1);
+        }
+    }
+    match home {
+        // keep the separator
+        Some(end) => Cow::Owned(alloc::format!("~{}", &s[(end - 1)..])),
+        None => Cow::Borrowed(s),
+    }
+}
+
+/// Finds the first of `patterns` that matches somewhere in `s`, and returns
+/// the part of `s` after the earliest match. `s` is returned unchanged if
+/// nothing matches. A `*` in a pattern matches any characters other than "/"
+/// and "\\", so it can be used for a single path component.
+///
+/// ```
+/// use stacked_errors::shorten_location_with;
+///
+/// let patterns = &["/vendor/*/", "/workspace/"];
+/// assert_eq!(
+///     shorten_location_with("/home/ci/workspace/app/src/main.rs", patterns),
+///     "app/src/main.rs"
+/// );
+/// assert_eq!(
+///     shorten_location_with("/build/vendor/serde-1.0/src/de.rs", patterns),
+///     "src/de.rs"
+/// );
+/// assert_eq!(shorten_location_with("src/lib.rs", patterns), "src/lib.rs");
+/// ```
+pub fn shorten_location_with<'a>(s: &'a str, patterns: &[&str]) -> &'a str {
+    for pattern in patterns {
+        if let Some(end) = match_end(s, pattern) {
+            return &s[end..]
+        }
+    }
+    s
+}
+
+/// Returns the end of the earliest match of `pattern` in `s`
+fn match_end(s: &str, pattern: &str) -> Option<usize> {
+    let mut pieces = pattern.split('*');
+    let first = pieces.next().unwrap_or("");
+    let mut start = 0;
+    while let Some(i) = s[start..].find(first) {
+        let mut end = start + i + first.len();
+        let mut matched = true;
+        for piece in pieces.clone() {
+            // the wildcard can't cross a path separator
+            let gap = s[end..].find(['/', '\\']).unwrap_or(s.len() - end);
+            if piece.is_empty() {
+                // a trailing `*` takes the rest of the component
+                end += gap;
+                continue
             }
+            match s[end..].find(piece) {
+                Some(j) if j <= gap => end += j + piece.len(),
+                _ => {
+                    matched = false;
+                    break
+                }
+            }
+        }
+        if matched {
+            return Some(end)
+        }
+        match s[(start + i)..].chars().next() {
+            Some(c) => start += i + c.len_utf8(),
+            None => break,
+        }
+    }
+    None
+}
+
+/// Records up to `cap` bytes of what is written to it, and counts the rest
+struct Capped {
+    s: String,
+    cap: usize,
+    elided: usize,
+}
+
+impl Write for Capped {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let room = self.cap - self.s.len();
+        if s.len() <= room {
+            self.s.push_str(s);
+        } else {
+            // stop at a char boundary so that multi-byte characters are not split
+            let mut end = room;
+            while !s.is_char_boundary(end) {
+                end -= 1;
+            }
+            self.s.push_str(&s[..end]);
+            // nothing is pushed after the first truncation
+            self.cap = self.s.len();
+            self.elided += s.len() - end;
+        }
+        Ok(())
+    }
+}
+
+/// Checks what is written to it against `rest`, failing at the first
+/// difference
+struct EqWriter<'a> {
+    rest: &'a str,
+    differs: bool,
+}
+
+impl Write for EqWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        // a `Display` impl could ignore the error and keep writing
+        if !self.differs {
+            if let Some(rest) = self.rest.strip_prefix(s) {
+                self.rest = rest;
+                return Ok(())
+            }
+        }
+        self.differs = true;
+        Err(fmt::Error)
+    }
+}
+
+/// Returns if the `Display` output of `d` is `s`, without allocating
+pub(crate) fn display_eq<D: Display + ?Sized>(d: &D, s: &str) -> bool {
+    let mut w = EqWriter {
+        rest: s,
+        differs: false,
+    };
+    let _ = write!(w, "{d}");
+    !w.differs && w.rest.is_empty()
+}
+
+/// Renders `t` with `Debug`, or alternate `Debug` if `alternate`, truncated to
+/// [max_operand_bytes](crate::max_operand_bytes) with a `... (N bytes
+/// elided)` marker. This does not allocate more than the cap even for huge
+/// values.
+pub(crate) fn capped_debug<T: Debug + ?Sized>(t: &T, alternate: bool) -> String {
+    let mut capped = Capped {
+        s: String::new(),
+        cap: crate::max_operand_bytes(),
+        elided: 0,
+    };
+    let _ = if alternate {
+        write!(capped, "{t:#?}")
+    } else {
+        write!(capped, "{t:?}")
+    };
+    let mut s = capped.s;
+    if capped.elided > 0 {
+        let _ = write!(s, "... ({} bytes elided)", capped.elided);
+    }
+    s
+}
+
+/// Options for `common_format`
+#[derive(Clone, Copy)]
+struct FormatOptions<'a> {
+    /// Applies terminal styling
+    style: bool,
+    /// If set, frames are separated by this without indentation or leading
+    /// newlines, otherwise every frame starts on a new indented line
+    separator: Option<&'a str>,
+    /// The number of most recent frames that are shown
+    max_frames: usize,
+    /// Lists frames from the bottom of the stack up instead of reversed
+    storage_order: bool,
+    /// Shows the locations of frames, location-only frames are skipped
+    /// entirely without this
+    locations: bool,
+    /// Word wraps messages to this many columns
+    wrap: Option<usize>,
+    /// Prefixes frames with the thread they originated on
+    #[cfg(feature = "thread-names")]
+    threads: bool,
+    /// Shows the source line of located frames
+    #[cfg(feature = "std")]
+    snippets: bool,
+}
+
+impl FormatOptions<'static> {
+    /// The options for the `Display` impl
+    const DISPLAY: Self = Self {
+        style: false,
+        separator: None,
+        max_frames: usize::MAX,
+        storage_order: false,
+        locations: true,
+        wrap: None,
+        #[cfg(feature = "thread-names")]
+        threads: false,
+        #[cfg(feature = "std")]
+        snippets: false,
+    };
+}
+
+/// The last source file read for snippets, and its contents if it was readable
+#[cfg(feature = "std")]
+type SnippetCache = Option<(&'static str, Option<String>)>;
+
+/// Writes the line of source at `l` with a caret under the column, nothing is
+/// written if the file is not readable
+#[cfg(feature = "std")]
+fn write_snippet(
+    s: &mut String,
+    l: &'static core::panic::Location<'static>,
+    cache: &mut SnippetCache,
+) -> fmt::Result {
+    if cache.as_ref().map(|(file, _)| *file) != Some(l.file()) {
+        *cache = Some((l.file(), std::fs::read_to_string(l.file()).ok()));
+    }
+    let Some((_, Some(src))) = cache else {
+        return Ok(())
+    };
+    let Some(line) = (l.line() as usize)
+        .checked_sub(1)
+        .and_then(|i| src.lines().nth(i))
+    else {
+        return Ok(())
+    };
+    let line_num = alloc::format!("{}", l.line());
+    write!(s, "\n    {line_num} | {}", line.trim_end())?;
+    write!(s, "\n    {:w$} | ", "", w = line_num.len())?;
+    // keep tabs so that the caret lines up with the source
+    for c in line.chars().take((l.column() as usize).saturating_sub(1)) {
+        s.push(if c == '\t' { '\t' } else { ' ' });
+    }
+    s.push('^');
+    Ok(())
+}
+
+/// The width past which locations are put on their own line when the options
+/// do not wrap, see [set_location_wrap_width](crate::set_location_wrap_width)
+fn location_wrap_width(style: bool) -> usize {
+    if let Some(width) = crate::location_wrap_width() {
+        return width
+    }
+    #[cfg(feature = "terminal-width")]
+    if style {
+        if let Some(width) = terminal_width() {
+            return width
         }
-        s
     }
-    #[cfg(windows)]
-    {
-        let find = "\\.cargo\\registry\\src\\";
-        if let Some(i) = s.find(find) {
-            s = &s[(i + find.len())..];
-            if let Some(i) = s.find('\\') {
-                s = &s[(i + 1)..];
+    let _ = style;
+    80
+}
+
+/// Returns the width of the terminal that stderr or stdout is, `Debug` output
+/// usually goes to stderr through panics and `eprintln`
+#[cfg(feature = "terminal-width")]
+fn terminal_width() -> Option<usize> {
+    let (terminal_size::Width(width), _) =
+        terminal_size::terminal_size_of(std::io::stderr()).or_else(terminal_size::terminal_size)?;
+    Some(usize::from(width))
+}
+
+/// The indentation of continuation lines of wrapped messages
+const WRAP_INDENT: &str = "      ";
+
+/// Word wraps `s` into `out`, the first line has at most `first` columns and
+/// continuation lines are indented by `WRAP_INDENT` and have at most `rest`
+/// columns. Words longer than a line are broken between characters.
+fn wrap_into(out: &mut String, s: &str, first: usize, rest: usize) {
+    let mut width = first.max(1);
+    for (i, line) in s.split('\n').enumerate() {
+        if i != 0 {
+            out.push('\n');
+            out.push_str(WRAP_INDENT);
+            width = rest.max(1);
+        }
+        let mut col = 0;
+        for word in line.split(' ') {
+            let len = word.chars().count();
+            if (col != 0) && ((col + 1 + len) > width) {
+                out.push('\n');
+                out.push_str(WRAP_INDENT);
+                width = rest.max(1);
+                col = 0;
+            } else if col != 0 {
+                out.push(' ');
+                col += 1;
+            }
+            for c in word.chars() {
+                if col >= width {
+                    out.push('\n');
+                    out.push_str(WRAP_INDENT);
+                    width = rest.max(1);
+                    col = 0;
+                }
+                out.push(c);
+                col += 1;
             }
         }
-        s
     }
 }
 
-fn common_format(this: &Error, style: bool, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+/// The length of the message that the location is put after, for wrapped
+/// messages this is the length of the last line
+fn msg_len(msg: &str, wrapped: bool) -> usize {
+    if wrapped {
+        msg.rsplit('\n').next().map_or(0, |l| l.chars().count())
+    } else {
+        msg.len()
+    }
+}
+
+fn common_format(this: &Error, opts: FormatOptions, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let FormatOptions {
+        style,
+        separator,
+        max_frames,
+        ..
+    } = opts;
+    let location_width = opts.wrap.unwrap_or_else(|| location_wrap_width(style));
+    // no separator is written before the first frame
+    let mut first = true;
+    #[cfg(feature = "std")]
+    let mut snippet_cache: SnippetCache = None;
     // in reverse order of a typical stack, I don't want to have to scroll up to see
     // the more specific errors
     let mut s = String::new();
     let mut tmp = String::new();
-    let mut first = true;
-    for (i, e) in this.iter().enumerate().rev() {
-        s.clear();
-        if first {
-            // this we do to better interact with `Error: ` etc since this is going to be a
-            // list anyways, some other libraries do this as well
-            writeln!(s)?;
+    // the number of frames at the bottom of the stack that are not shown
+    let hidden = this.iter().len().saturating_sub(max_frames);
+    // the root cause is the bottom frame with a message, it is styled distinctly
+    let root = this
+        .iter()
+        .position(|e| e.downcast_ref::<UnitError>().is_none());
+    let mut forward;
+    let mut reverse;
+    let frames: &mut dyn Iterator<Item = (usize, &ErrorItem)> = if opts.storage_order {
+        forward = this.iter().enumerate();
+        &mut forward
+    } else {
+        reverse = this.iter().enumerate().rev();
+        &mut reverse
+    };
+    for (i, e) in frames {
+        if i < hidden {
+            continue;
         }
+        s.clear();
         let is_unit_err = e.downcast_ref::<UnitError>().is_some();
-        let is_last = i == 0;
-        if is_unit_err {
-            if e.get_location().is_none() {
-                continue;
+        if is_unit_err && (e.get_location().is_none() || !opts.locations) {
+            continue;
+        }
+        match separator {
+            Some(sep) => {
+                if !first {
+                    s.push_str(sep);
+                }
             }
-        } else {
+            // every frame starts on a new line, for the first one this we do to better
+            // interact with `Error: ` etc since this is going to be a list anyways, some
+            // other libraries do this as well
+            None => writeln!(s)?,
+        }
+        first = false;
+        if !is_unit_err {
             // TODO can we get rid of the allocated temporaries?
             tmp.clear();
             write!(tmp, "{}", e.get_err())?;
+            if let Some(width) = opts.wrap.filter(|_| separator.is_none()) {
+                let mut wrapped = String::new();
+                wrap_into(
+                    &mut wrapped,
+                    &tmp,
+                    width.saturating_sub(4),
+                    width.saturating_sub(6),
+                );
+                tmp = wrapped;
+            }
+            if separator.is_none() {
+                write!(s, "    ")?;
+            }
+            #[cfg(feature = "thread-names")]
+            if opts.threads {
+                if let Some(t) = e.meta().thread() {
+                    match t.name() {
+                        Some(name) => write!(s, "[thread: {name}] ")?,
+                        None => write!(s, "[thread: {:?}] ", t.id())?,
+                    }
+                }
+            }
             // if there are vt100 styling characters already in the output, do not apply
             // styling
             if (!style) || tmp.contains('\u{1b}') {
-                write!(s, "    {}", tmp)?;
+                write!(s, "{}", tmp)?;
+            } else if root == Some(i) {
+                let color = Style::new().color(CssColors::IndianRed).bold();
+                write!(s, "{}", tmp.style(color))?;
             } else {
                 let color = Style::new().color(CssColors::IndianRed);
-                write!(s, "    {}", tmp.style(color))?;
+                write!(s, "{}", tmp.style(color))?;
             }
         }
-        if let Some(l) = e.get_location() {
+        if let Some(l) = e.get_location().filter(|_| opts.locations) {
             // if the current length plus the location length (the +8 is from the space,
-            // colon, and 4 digits for line and 2 for column) is more than 80 then split up
-            if (tmp.len() + l.file().len() + 8) > 80 {
+            // colon, and 4 digits for line and 2 for column) is more than the width then
+            // split up
+            if separator.is_some() {
+                // frames are kept on one line when there is a separator
+                write!(s, "{}", if is_unit_err { "at " } else { " at " })?;
+            } else if (msg_len(&tmp, opts.wrap.is_some()) + l.file().len() + 8) > location_width {
                 // split up
                 write!(s, "\n  at ")?;
             } else if !is_unit_err {
@@ -96,36 +484,564 @@ fn common_format(this: &Error, style: bool, f: &mut fmt::Formatter<'_>) -> fmt::
             tmp.clear();
             write!(tmp, "{}:{}", l.line(), l.column())?;
 
+            let file = if e.is_sanitized() {
+                sanitize_location(shorten_location(l.file()))
+            } else {
+                Cow::Borrowed(shorten_location(l.file()))
+            };
             if style {
-                write!(
-                    s,
-                    "{} {}",
-                    shorten_location(l.file()).style(dimmed),
-                    tmp.style(bold)
-                )?;
+                write!(s, "{} {}", file.style(dimmed), tmp.style(bold))?;
             } else {
-                write!(s, "{} {}", shorten_location(l.file()), tmp)?;
+                write!(s, "{} {}", file, tmp)?;
             }
         }
-        if !is_last {
-            writeln!(s)?;
+        if e.occurrences() > 1 {
+            write!(s, " (x{})", e.occurrences())?;
+        }
+        #[cfg(feature = "std")]
+        if let Some(l) = e.get_location().filter(|_| opts.snippets && opts.locations) {
+            write_snippet(&mut s, l, &mut snippet_cache)?;
         }
         f.write_fmt(format_args!("{s}"))?;
-        first = false;
+    }
+    // the prefixes of the lines after the frames
+    let (hidden_prefix, help_prefix) = match separator {
+        Some(_) if first => ("", ""),
+        Some(sep) => (sep, sep),
+        None => ("\n    ", "\n  "),
+    };
+    if hidden > 0 {
+        f.write_fmt(format_args!("{hidden_prefix}… ({hidden} more frames)"))?;
+    }
+    if let Some(help) = this.help() {
+        if style {
+            let color = Style::new().color(CssColors::MediumSeaGreen);
+            f.write_fmt(format_args!(
+                "{help_prefix}{}",
+                format_args!("help: {help}").style(color)
+            ))?;
+        } else {
+            f.write_fmt(format_args!("{help_prefix}help: {help}"))?;
+        }
     }
     Ok(())
 }
 
 impl Debug for Error {
-    /// Has terminal styling
+    /// Has terminal styling, and like `Display` lists the frames in reverse
+    /// order with the most recent frame first. See
+    /// [Error::debug_storage_order] for an unstyled version in the order that
+    /// frames were pushed.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        common_format(self, true, f)
+        common_format(
+            self,
+            FormatOptions {
+                style: true,
+                ..FormatOptions::DISPLAY
+            },
+            f,
+        )
     }
 }
 
 impl Display for Error {
     /// Same as `Debug` but without terminal styling
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        common_format(self, false, f)
+        common_format(self, FormatOptions::DISPLAY, f)
+    }
+}
+
+/// Returned by [Error::display_limited]
+struct DisplayLimited<'a> {
+    err: &'a Error,
+    max_frames: usize,
+}
+
+impl Display for DisplayLimited<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        common_format(
+            self.err,
+            FormatOptions {
+                max_frames: self.max_frames,
+                ..FormatOptions::DISPLAY
+            },
+            f,
+        )
+    }
+}
+
+/// Returned by [Error::display_with_threads]
+#[cfg(feature = "thread-names")]
+struct DisplayWithThreads<'a> {
+    err: &'a Error,
+}
+
+#[cfg(feature = "thread-names")]
+impl Display for DisplayWithThreads<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        common_format(
+            self.err,
+            FormatOptions {
+                threads: true,
+                ..FormatOptions::DISPLAY
+            },
+            f,
+        )
+    }
+}
+
+/// Returned by [Error::display_with_snippets]
+#[cfg(feature = "std")]
+struct DisplayWithSnippets<'a> {
+    err: &'a Error,
+}
+
+#[cfg(feature = "std")]
+impl Display for DisplayWithSnippets<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        common_format(
+            self.err,
+            FormatOptions {
+                snippets: true,
+                ..FormatOptions::DISPLAY
+            },
+            f,
+        )
+    }
+}
+
+/// Returned by [Error::display_with_separator]
+struct DisplayWithSeparator<'a> {
+    err: &'a Error,
+    separator: &'a str,
+}
+
+impl Display for DisplayWithSeparator<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        common_format(
+            self.err,
+            FormatOptions {
+                separator: Some(self.separator),
+                ..FormatOptions::DISPLAY
+            },
+            f,
+        )
+    }
+}
+
+/// Collects written output into lines for [Error::report_lines]
+struct Lines(Vec<String>);
+
+impl Write for Lines {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut pieces = s.split('\n');
+        if let Some(piece) = pieces.next() {
+            self.0.last_mut().unwrap().push_str(piece);
+        }
+        for piece in pieces {
+            self.0.push(String::from(piece));
+        }
+        Ok(())
+    }
+}
+
+/// Returned by [Error::display_wrapped]
+struct DisplayWrapped<'a> {
+    err: &'a Error,
+    width: usize,
+}
+
+impl Display for DisplayWrapped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        common_format(
+            self.err,
+            FormatOptions {
+                wrap: Some(self.width),
+                ..FormatOptions::DISPLAY
+            },
+            f,
+        )
+    }
+}
+
+/// Returned by [Error::display_no_locations]
+struct DisplayNoLocations<'a>(&'a Error);
+
+impl Display for DisplayNoLocations<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        common_format(
+            self.0,
+            FormatOptions {
+                locations: false,
+                ..FormatOptions::DISPLAY
+            },
+            f,
+        )
+    }
+}
+
+/// Returned by [Error::debug_storage_order]
+struct DebugStorageOrder<'a>(&'a Error);
+
+impl Debug for DebugStorageOrder<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        common_format(
+            self.0,
+            FormatOptions {
+                storage_order: true,
+                ..FormatOptions::DISPLAY
+            },
+            f,
+        )
+    }
+}
+
+/// Returned by [Error::display_github_annotations]
+struct GithubAnnotations<'a> {
+    err: &'a Error,
+}
+
+/// Writes `s` with the percent encoding that workflow command data requires,
+/// `properties` additionally encodes `:` and `,`
+fn write_github_escaped(f: &mut fmt::Formatter<'_>, s: &str, property: bool) -> fmt::Result {
+    for c in s.chars() {
+        match c {
+            '%' => f.write_str("%25")?,
+            '\r' => f.write_str("%0D")?,
+            '\n' => f.write_str("%0A")?,
+            ':' if property => f.write_str("%3A")?,
+            ',' if property => f.write_str("%2C")?,
+            c => f.write_char(c)?,
+        }
+    }
+    Ok(())
+}
+
+impl Display for GithubAnnotations<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut tmp = String::new();
+        let mut first = true;
+        for e in self.err.iter().rev() {
+            let l = e.get_location();
+            if l.is_none() && e.downcast_ref::<UnitError>().is_some() {
+                continue;
+            }
+            if !first {
+                f.write_char('\n')?;
+            }
+            first = false;
+            f.write_str("::error")?;
+            if let Some(l) = l {
+                f.write_str(" file=")?;
+                if e.is_sanitized() {
+                    write_github_escaped(f, &sanitize_location(l.file()), true)?;
+                } else {
+                    write_github_escaped(f, l.file(), true)?;
+                }
+                write!(f, ",line={},col={}", l.line(), l.column())?;
+            }
+            f.write_str("::")?;
+            tmp.clear();
+            write!(tmp, "{}", e.get_err())?;
+            write_github_escaped(f, &tmp, false)?;
+        }
+        if let Some(help) = self.err.help() {
+            if !first {
+                f.write_char('\n')?;
+            }
+            f.write_str("::notice::help: ")?;
+            write_github_escaped(f, help, false)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returned by [Error::display_json_lines]
+struct JsonLines<'a> {
+    err: &'a Error,
+}
+
+/// Writes `s` as a JSON string literal
+fn write_json_str(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+impl Display for JsonLines<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut tmp = String::new();
+        let mut first = true;
+        for e in self.err.iter().rev() {
+            let l = e.get_location();
+            if l.is_none() && e.downcast_ref::<UnitError>().is_some() {
+                continue;
+            }
+            if !first {
+                f.write_char('\n')?;
+            }
+            first = false;
+            f.write_str("{\"message\":")?;
+            if e.downcast_ref::<UnitError>().is_some() {
+                f.write_str("null")?;
+            } else {
+                tmp.clear();
+                write!(tmp, "{}", e.get_err())?;
+                write_json_str(f, &tmp)?;
+            }
+            if let Some(l) = l {
+                f.write_str(",\"file\":")?;
+                if e.is_sanitized() {
+                    write_json_str(f, &sanitize_location(l.file()))?;
+                } else {
+                    write_json_str(f, l.file())?;
+                }
+                write!(f, ",\"line\":{},\"col\":{}}}", l.line(), l.column())?;
+            } else {
+                f.write_str(",\"file\":null,\"line\":null,\"col\":null}")?;
+            }
+        }
+        if let Some(help) = self.err.help() {
+            if !first {
+                f.write_char('\n')?;
+            }
+            f.write_str("{\"help\":")?;
+            write_json_str(f, help)?;
+            f.write_char('}')?;
+        }
+        Ok(())
+    }
+}
+
+impl Error {
+    /// Returns a `Display`able that is the same as the `Display` impl of
+    /// `Error`, except that only the `max_frames` most recent frames are shown
+    /// followed by a `… (N more frames)` line. The error itself is left intact.
+    ///
+    /// ```
+    /// use stacked_errors::Error;
+    ///
+    /// let e = Error::from_err_locationless("root")
+    ///     .add_err_locationless("middle")
+    ///     .add_err_locationless("top");
+    /// assert_eq!(
+    ///     format!("{}", e.display_limited(1)),
+    ///     "\n    top\n    … (2 more frames)"
+    /// );
+    /// ```
+    pub fn display_limited(&self, max_frames: usize) -> impl Display + '_ {
+        DisplayLimited {
+            err: self,
+            max_frames,
+        }
+    }
+
+    /// Returns a `Display`able that is the same as the `Display` impl of
+    /// `Error`, except that frames are separated by `separator` instead of
+    /// each starting on a new indented line, and locations are always kept on
+    /// the same line as their message. This is for log consumers that need
+    /// a particular format, e.g. `" | "` for keeping the error on one line.
+    ///
+    /// ```
+    /// use stacked_errors::Error;
+    ///
+    /// let e = Error::from_err_locationless("root")
+    ///     .add_err_locationless("middle")
+    ///     .add_err_locationless("top");
+    /// assert_eq!(
+    ///     format!("{}", e.display_with_separator(" | ")),
+    ///     "top | middle | root"
+    /// );
+    /// assert_eq!(
+    ///     format!("{}", e.display_with_separator("\n")),
+    ///     "top\nmiddle\nroot"
+    /// );
+    /// ```
+    pub fn display_with_separator<'a>(&'a self, separator: &'a str) -> impl Display + 'a {
+        DisplayWithSeparator {
+            err: self,
+            separator,
+        }
+    }
+
+    /// Returns the lines of the `Display` impl of `Error` without the leading
+    /// newline, so that custom log sinks can emit every line as a separate
+    /// record. Multi-line messages result in multiple lines like they do in
+    /// the `Display` output.
+    ///
+    /// ```
+    /// use stacked_errors::Error;
+    ///
+    /// let e = Error::from_err_locationless("root").add_err_locationless("context");
+    /// assert_eq!(e.report_lines(), ["    context", "    root"]);
+    /// assert_eq!(format!("\n{}", e.report_lines().join("\n")), format!("{e}"));
+    /// ```
+    pub fn report_lines(&self) -> Vec<String> {
+        let mut lines = Lines(vec![String::new()]);
+        // `Lines` never errors
+        let _ = write!(lines, "{self}");
+        let mut lines = lines.0;
+        if lines.first().is_some_and(|l| l.is_empty()) {
+            lines.remove(0);
+        }
+        lines
+    }
+
+    /// Returns a `Display`able that is the same as the `Display` impl of
+    /// `Error`, except that messages are word wrapped so that lines are at
+    /// most `width` columns (counted in `char`s) where possible, with
+    /// continuation lines indented further. Words longer than a line are
+    /// broken between characters.
+    ///
+    /// ```
+    /// use stacked_errors::Error;
+    ///
+    /// let e = Error::from_err_locationless("a message that is too long for one line");
+    /// assert_eq!(
+    ///     format!("{}", e.display_wrapped(30)),
+    ///     "\n    a message that is too long\n      for one line"
+    /// );
+    /// ```
+    pub fn display_wrapped(&self, width: usize) -> impl Display + '_ {
+        DisplayWrapped { err: self, width }
+    }
+
+    /// Returns a `Display`able that is the same as the `Display` impl of
+    /// `Error`, except that locations are omitted entirely and location-only
+    /// frames are skipped. This is for user facing errors where source
+    /// locations are noise.
+    ///
+    /// ```
+    /// use stacked_errors::Error;
+    ///
+    /// let e = Error::from_err("could not read the config")
+    ///     .add()
+    ///     .add_err("could not start");
+    /// assert_eq!(
+    ///     format!("{}", e.display_no_locations()),
+    ///     "\n    could not start\n    could not read the config"
+    /// );
+    /// ```
+    pub fn display_no_locations(&self) -> impl Display + '_ {
+        DisplayNoLocations(self)
+    }
+
+    /// Returns a `Debug`able that renders the frames in storage order, with
+    /// the root frame first and the most recently pushed frame last, and
+    /// without terminal styling. The `Debug` and `Display` impls of `Error`
+    /// are reversed for humans, this is for tests and programmatic diffing
+    /// that expect the order of [Error::iter].
+    ///
+    /// ```
+    /// use stacked_errors::Error;
+    ///
+    /// let e = Error::from_err_locationless("root").add_err_locationless("top");
+    /// assert_eq!(format!("{e}"), "\n    top\n    root");
+    /// assert_eq!(
+    ///     format!("{:?}", e.debug_storage_order()),
+    ///     "\n    root\n    top"
+    /// );
+    /// ```
+    pub fn debug_storage_order(&self) -> impl Debug + '_ {
+        DebugStorageOrder(self)
+    }
+
+    /// Returns a `Display`able that renders each frame of `self` as a GitHub
+    /// Actions workflow command, newest frame first. Frames with a location are
+    /// rendered as `::error file={file},line={line},col={col}::{message}` so
+    /// that they show up as annotations on the source, and locationless frames
+    /// are rendered as `::error::{message}`. A help message from
+    /// [Error::with_help] is rendered last as a `::notice`.
+    ///
+    /// ```
+    /// use stacked_errors::Error;
+    ///
+    /// let e = Error::from_err_locationless("root").add_err("context");
+    /// assert_eq!(
+    ///     format!("{}", e.display_github_annotations()),
+    ///     "::error file=src/fmt.rs,line=7,col=46::context\n::error::root"
+    /// );
+    /// ```
+    pub fn display_github_annotations(&self) -> impl Display + '_ {
+        GithubAnnotations { err: self }
+    }
+
+    /// Returns a `Display`able that renders each frame of `self` as a JSON
+    /// object on its own line (newline delimited JSON), newest frame first, so
+    /// that log shippers get one record per frame. Each object has a
+    /// `"message"` and the `"file"`, `"line"`, and `"col"` of the location,
+    /// which are `null` for locationless frames. Location-only frames have a
+    /// `null` message, and are skipped if they also have no location. A help
+    /// message from [Error::with_help] is rendered last as a `{"help":...}`
+    /// object. This does not need the "serde_json" feature.
+    ///
+    /// ```
+    /// use stacked_errors::Error;
+    ///
+    /// let e = Error::from_err_locationless("root \"cause\"").add_err("context");
+    /// let s = format!("{}", e.display_json_lines());
+    /// let mut lines = s.lines();
+    /// assert_eq!(
+    ///     lines.next().unwrap(),
+    ///     r#"{"message":"context","file":"src/fmt.rs","line":7,"col":56}"#
+    /// );
+    /// assert_eq!(
+    ///     lines.next().unwrap(),
+    ///     r#"{"message":"root \"cause\"","file":null,"line":null,"col":null}"#
+    /// );
+    /// ```
+    pub fn display_json_lines(&self) -> impl Display + '_ {
+        JsonLines { err: self }
+    }
+
+    /// Returns a `Display`able that is the same as the `Display` impl of
+    /// `Error`, except that frames that recorded the thread they originated on
+    /// are prefixed with `[thread: name]` (or the thread id if the thread is
+    /// unnamed). Requires the "thread-names" feature.
+    ///
+    /// ```
+    /// use stacked_errors::Error;
+    ///
+    /// let e = std::thread::Builder::new()
+    ///     .name("worker-3".to_owned())
+    ///     .spawn(|| Error::from_err("connection reset"))
+    ///     .unwrap()
+    ///     .join()
+    ///     .unwrap();
+    /// assert!(format!("{}", e.display_with_threads())
+    ///     .starts_with("\n    [thread: worker-3] connection reset at"));
+    /// ```
+    #[cfg(feature = "thread-names")]
+    pub fn display_with_threads(&self) -> impl Display + '_ {
+        DisplayWithThreads { err: self }
+    }
+
+    /// Returns a `Display`able that is the same as the `Display` impl of
+    /// `Error`, except that located frames are followed by the line of source
+    /// they point to with a caret under the column, like `rustc` does. This is
+    /// intended for development and test environments, the files are read at
+    /// runtime relative to the current directory and frames with files that
+    /// are not readable are rendered without a snippet. Requires the "std"
+    /// feature.
+    ///
+    /// ```no_run
+    /// use stacked_errors::Error;
+    ///
+    /// let e = Error::from_err("connection reset");
+    /// eprintln!("{}", e.display_with_snippets());
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn display_with_snippets(&self) -> impl Display + '_ {
+        DisplayWithSnippets { err: self }
     }
 }
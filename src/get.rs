@@ -0,0 +1,209 @@
+use alloc::string::String;
+use core::fmt::Debug;
+
+use crate::Error;
+
+/// An index that can be used with [StackedGet], either a key for maps or a
+/// position for sequences
+pub trait StackedIndex: Debug {
+    /// Returns the key if this can index into maps
+    fn as_key(&self) -> Option<&str> {
+        None
+    }
+
+    /// Returns the position if this can index into sequences
+    fn as_position(&self) -> Option<usize> {
+        None
+    }
+}
+
+impl StackedIndex for str {
+    fn as_key(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl StackedIndex for String {
+    fn as_key(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl<T: StackedIndex + ?Sized> StackedIndex for &T {
+    fn as_key(&self) -> Option<&str> {
+        (**self).as_key()
+    }
+
+    fn as_position(&self) -> Option<usize> {
+        (**self).as_position()
+    }
+}
+
+macro_rules! impl_stacked_index_position {
+    ($($ty:ty)*) => {
+        $(
+            impl StackedIndex for $ty {
+                fn as_position(&self) -> Option<usize> {
+                    usize::try_from(*self).ok()
+                }
+            }
+        )*
+    };
+}
+
+impl_stacked_index_position!(usize u8 u16 u32 u64 i32 i64);
+
+/// Trait for tree-like values that [stacked_get](crate::stacked_get) and
+/// [stacked_get_mut](crate::stacked_get_mut) can index into. Implementations
+/// for `serde_json`, `toml`, and `serde_yaml` values are behind features of
+/// the same names.
+pub trait StackedGet {
+    /// Describes the kind of container `self` is for error messages, e.g.
+    /// "json object" or "toml array"
+    fn stacked_kind(&self) -> &'static str;
+
+    /// Returns the value at `inx` if `self` is a container that has it
+    fn stacked_get(&self, inx: &dyn StackedIndex) -> Option<&Self>;
+
+    /// Returns the value at `inx` if `self` is a container that has it
+    fn stacked_get_mut(&mut self, inx: &dyn StackedIndex) -> Option<&mut Self>;
+
+    /// Used by `stacked_get`, `f` is given the kind of `self` and returns the
+    /// error message
+    #[doc(hidden)]
+    #[track_caller]
+    fn __stacked_get<F: FnOnce(&'static str) -> String>(
+        &self,
+        inx: &dyn StackedIndex,
+        f: F,
+    ) -> Result<&Self, Error>
+    where
+        Self: Sized,
+    {
+        match self.stacked_get(inx) {
+            Some(v) => Ok(v),
+            None => Err(Error::from_err(f(self.stacked_kind()))),
+        }
+    }
+
+    /// Used by `stacked_get_mut`
+    #[doc(hidden)]
+    #[track_caller]
+    fn __stacked_get_mut<F: FnOnce(&'static str) -> String>(
+        &mut self,
+        inx: &dyn StackedIndex,
+        f: F,
+    ) -> Result<&mut Self, Error>
+    where
+        Self: Sized,
+    {
+        let kind = self.stacked_kind();
+        match self.stacked_get_mut(inx) {
+            Some(v) => Ok(v),
+            None => Err(Error::from_err(f(kind))),
+        }
+    }
+}
+
+#[cfg(feature = "serde_json")]
+impl StackedGet for serde_json::Value {
+    fn stacked_kind(&self) -> &'static str {
+        use serde_json::Value;
+        match self {
+            Value::Null => "json null",
+            Value::Bool(_) => "json bool",
+            Value::Number(_) => "json number",
+            Value::String(_) => "json string",
+            Value::Array(_) => "json array",
+            Value::Object(_) => "json object",
+        }
+    }
+
+    fn stacked_get(&self, inx: &dyn StackedIndex) -> Option<&Self> {
+        use serde_json::Value;
+        match self {
+            Value::Object(map) => map.get(inx.as_key()?),
+            Value::Array(array) => array.get(inx.as_position()?),
+            _ => None,
+        }
+    }
+
+    fn stacked_get_mut(&mut self, inx: &dyn StackedIndex) -> Option<&mut Self> {
+        use serde_json::Value;
+        match self {
+            Value::Object(map) => map.get_mut(inx.as_key()?),
+            Value::Array(array) => array.get_mut(inx.as_position()?),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "toml")]
+impl StackedGet for toml::Value {
+    fn stacked_kind(&self) -> &'static str {
+        use toml::Value;
+        match self {
+            Value::String(_) => "toml string",
+            Value::Integer(_) => "toml integer",
+            Value::Float(_) => "toml float",
+            Value::Boolean(_) => "toml boolean",
+            Value::Datetime(_) => "toml datetime",
+            Value::Array(_) => "toml array",
+            Value::Table(_) => "toml table",
+        }
+    }
+
+    fn stacked_get(&self, inx: &dyn StackedIndex) -> Option<&Self> {
+        use toml::Value;
+        match self {
+            Value::Table(table) => table.get(inx.as_key()?),
+            Value::Array(array) => array.get(inx.as_position()?),
+            _ => None,
+        }
+    }
+
+    fn stacked_get_mut(&mut self, inx: &dyn StackedIndex) -> Option<&mut Self> {
+        use toml::Value;
+        match self {
+            Value::Table(table) => table.get_mut(inx.as_key()?),
+            Value::Array(array) => array.get_mut(inx.as_position()?),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "serde_yaml")]
+impl StackedGet for serde_yaml::Value {
+    fn stacked_kind(&self) -> &'static str {
+        use serde_yaml::Value;
+        match self {
+            Value::Null => "yaml null",
+            Value::Bool(_) => "yaml bool",
+            Value::Number(_) => "yaml number",
+            Value::String(_) => "yaml string",
+            Value::Sequence(_) => "yaml sequence",
+            Value::Mapping(_) => "yaml mapping",
+            Value::Tagged(_) => "yaml tagged value",
+        }
+    }
+
+    fn stacked_get(&self, inx: &dyn StackedIndex) -> Option<&Self> {
+        use serde_yaml::Value;
+        match self {
+            Value::Mapping(mapping) => mapping.get(inx.as_key()?),
+            Value::Sequence(sequence) => sequence.get(inx.as_position()?),
+            Value::Tagged(tagged) => tagged.value.stacked_get(inx),
+            _ => None,
+        }
+    }
+
+    fn stacked_get_mut(&mut self, inx: &dyn StackedIndex) -> Option<&mut Self> {
+        use serde_yaml::Value;
+        match self {
+            Value::Mapping(mapping) => mapping.get_mut(inx.as_key()?),
+            Value::Sequence(sequence) => sequence.get_mut(inx.as_position()?),
+            Value::Tagged(tagged) => tagged.value.stacked_get_mut(inx),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,151 @@
+//! A global interner for context messages, so that errors with the same
+//! dynamic message share one allocation
+
+use alloc::{string::String, sync::Arc};
+use core::{
+    cell::RefCell,
+    fmt::{self, Write},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use std::{
+    collections::HashSet,
+    sync::{Mutex, OnceLock},
+};
+
+use crate::Error;
+
+/// The default of [set_interner_max_entries]
+pub const DEFAULT_INTERNER_MAX_ENTRIES: usize = 4096;
+
+static MAX_ENTRIES: AtomicUsize = AtomicUsize::new(DEFAULT_INTERNER_MAX_ENTRIES);
+
+#[derive(Default)]
+struct Interner {
+    table: HashSet<Arc<str>>,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+fn interner() -> &'static Mutex<Interner> {
+    static INTERNER: OnceLock<Mutex<Interner>> = OnceLock::new();
+    INTERNER.get_or_init(Default::default)
+}
+
+/// Counters of the global interner, returned by [interner_stats]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct InternerStats {
+    /// The number of messages currently in the table
+    pub entries: usize,
+    /// The maximum number of entries, see [set_interner_max_entries]
+    pub max_entries: usize,
+    /// The number of lookups that reused an existing entry
+    pub hits: u64,
+    /// The number of lookups that inserted a new entry
+    pub misses: u64,
+    /// The number of entries removed to stay under `max_entries`
+    pub evictions: u64,
+}
+
+/// Returns the counters of the global interner
+pub fn interner_stats() -> InternerStats {
+    let interner = interner().lock().unwrap_or_else(|e| e.into_inner());
+    InternerStats {
+        entries: interner.table.len(),
+        max_entries: MAX_ENTRIES.load(Ordering::Relaxed),
+        hits: interner.hits,
+        misses: interner.misses,
+        evictions: interner.evictions,
+    }
+}
+
+/// Sets the maximum number of messages that the global interner keeps. When
+/// a new message would go over the maximum, entries not used by any live
+/// error are evicted first, and if that is not enough the whole table is
+/// cleared. Evicted messages stay valid in the errors using them, they just
+/// stop being shared with new errors. The default is
+/// [DEFAULT_INTERNER_MAX_ENTRIES].
+pub fn set_interner_max_entries(max: usize) {
+    MAX_ENTRIES.store(max, Ordering::Relaxed)
+}
+
+/// Returns the shared `Arc<str>` for `s`, inserting it into the global
+/// interner if it is not there yet
+pub fn intern(s: &str) -> Arc<str> {
+    let max = MAX_ENTRIES.load(Ordering::Relaxed);
+    let mut guard = interner().lock().unwrap_or_else(|e| e.into_inner());
+    let interner = &mut *guard;
+    if let Some(res) = interner.table.get(s) {
+        interner.hits += 1;
+        return Arc::clone(res)
+    }
+    interner.misses += 1;
+    let res: Arc<str> = Arc::from(s);
+    if max == 0 {
+        return res
+    }
+    if interner.table.len() >= max {
+        let len = interner.table.len();
+        // the table itself holds one reference
+        interner.table.retain(|e| Arc::strong_count(e) > 1);
+        if interner.table.len() >= max {
+            interner.table.clear();
+        }
+        interner.evictions += (len - interner.table.len()) as u64;
+    }
+    interner.table.insert(Arc::clone(&res));
+    res
+}
+
+/// Used by `intern!`, renders `args` into a reused buffer so that messages
+/// that are already interned do not allocate
+pub fn intern_fmt(args: fmt::Arguments<'_>) -> Arc<str> {
+    if let Some(s) = args.as_str() {
+        return intern(s)
+    }
+    std::thread_local! {
+        static BUF: RefCell<String> = const { RefCell::new(String::new()) };
+    }
+    BUF.with(|buf| match buf.try_borrow_mut() {
+        Ok(mut buf) => {
+            buf.clear();
+            let _ = buf.write_fmt(args);
+            intern(&buf)
+        }
+        // reentrant use from a `Display` impl in `args`
+        Err(_) => intern(&alloc::fmt::format(args)),
+    })
+}
+
+impl Error {
+    /// Like [Error::add_err] but with the message shared through the global
+    /// interner, see [intern]
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use stacked_errors::{Error, StackedErrorDowncast};
+    ///
+    /// let host = "db0";
+    /// let e0 = Error::new().add_err_interned(&format!("connecting to {host}"));
+    /// let e1 = Error::new().add_err_interned(&format!("connecting to {host}"));
+    /// let msg0 = e0
+    ///     .iter()
+    ///     .last()
+    ///     .unwrap()
+    ///     .downcast_ref::<Arc<str>>()
+    ///     .unwrap();
+    /// let msg1 = e1
+    ///     .iter()
+    ///     .last()
+    ///     .unwrap()
+    ///     .downcast_ref::<Arc<str>>()
+    ///     .unwrap();
+    /// assert!(Arc::ptr_eq(msg0, msg1));
+    /// ```
+    #[track_caller]
+    pub fn add_err_interned(self, msg: &str) -> Self {
+        self.add_err(intern(msg))
+    }
+}
@@ -0,0 +1,166 @@
+use alloc::{format, string::String, vec::Vec};
+
+use serde_json::Value;
+
+use crate::Error;
+
+/// Splits `path` on unescaped `.`s, a `\` escapes the following character and
+/// a trailing `\` is a literal backslash
+fn split_path(path: &str) -> Vec<String> {
+    let mut segments = Vec::new();
+    if path.is_empty() {
+        return segments
+    }
+    let mut segment = String::new();
+    let mut chars = path.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => segment.push(chars.next().unwrap_or('\\')),
+            '.' => segments.push(core::mem::take(&mut segment)),
+            c => segment.push(c),
+        }
+    }
+    segments.push(segment);
+    segments
+}
+
+/// Parses an array index segment, only plain ASCII digits without leading
+/// zeros are accepted so that each index has one spelling
+fn parse_index(segment: &str) -> Option<usize> {
+    let canonical = !segment.is_empty()
+        && segment.bytes().all(|b| b.is_ascii_digit())
+        && ((segment == "0") || !segment.starts_with('0'));
+    if canonical {
+        segment.parse().ok()
+    } else {
+        None
+    }
+}
+
+/// Gets the value at the dotted `path` in `value`, for when the path comes from
+/// configuration and the bracket form of [stacked_get](crate::stacked_get)
+/// cannot be used. Segments of plain digits without leading zeros index into
+/// arrays, and `\.` can be used for keys that contain a literal dot (`\\` for
+/// a literal backslash, a trailing `\` is also literal). An empty path returns
+/// `value` itself.
+///
+/// The error names the full path and the segment that failed.
+///
+/// ```
+/// use serde_json::json;
+/// use stacked_errors::json_path_get;
+///
+/// let value = json!({"state": {"status": "running", "args": ["a", "b"]}, "a.b": 1});
+/// assert_eq!(json_path_get(&value, "state.status").unwrap(), "running");
+/// assert_eq!(json_path_get(&value, "state.args.1").unwrap(), "b");
+/// assert_eq!(json_path_get(&value, "a\\.b").unwrap(), 1);
+///
+/// let e = json_path_get(&value, "state.missing.x").unwrap_err();
+/// assert_eq!(
+///     format!("{e}"),
+///     "\n    json_path_get(\"state.missing.x\") -> indexing failed at segment \"missing\"\n  at \
+///      src/json.rs 13:9"
+/// );
+/// ```
+#[track_caller]
+pub fn json_path_get<'a>(value: &'a Value, path: &str) -> Result<&'a Value, Error> {
+    let mut value = value;
+    for segment in split_path(path) {
+        let next = match value {
+            Value::Object(map) => map.get(&segment),
+            Value::Array(array) => parse_index(&segment).and_then(|i| array.get(i)),
+            _ => None,
+        };
+        match next {
+            Some(next) => value = next,
+            None => {
+                return Err(Error::from_err(format!(
+                    "json_path_get({path:?}) -> indexing failed at segment {segment:?}"
+                )))
+            }
+        }
+    }
+    Ok(value)
+}
+
+/// Describes the JSON type of `value` and small values themselves
+fn describe(value: &Value) -> String {
+    match value {
+        Value::Null => String::from("null"),
+        Value::Bool(b) => format!("bool {b}"),
+        Value::Number(n) => format!("number {n}"),
+        Value::String(s) => format!("string {s:?}"),
+        Value::Array(_) => String::from("array"),
+        Value::Object(_) => String::from("object"),
+    }
+}
+
+/// Used by `stacked_get_as`
+pub trait JsonAs<'a>: Sized {
+    const EXPECTED: &'static str;
+
+    fn json_as(value: &'a Value) -> Option<Self>;
+}
+
+macro_rules! impl_json_as {
+    ($($ty:ty, $expected:literal, $f:ident);*;) => {
+        $(
+            impl<'a> JsonAs<'a> for $ty {
+                const EXPECTED: &'static str = $expected;
+
+                fn json_as(value: &'a Value) -> Option<Self> {
+                    value.$f()
+                }
+            }
+        )*
+    };
+}
+
+impl_json_as!(
+    u64, "u64", as_u64;
+    i64, "i64", as_i64;
+    f64, "f64", as_f64;
+    bool, "bool", as_bool;
+    &'a str, "str", as_str;
+);
+
+/// Used by `stacked_get_as`
+#[track_caller]
+pub fn json_as<'a, T: JsonAs<'a>>(value: &'a Value) -> Result<T, Error> {
+    match T::json_as(value) {
+        Some(t) => Ok(t),
+        None => Err(Error::from_err(format!(
+            "expected {}, found {}",
+            T::EXPECTED,
+            describe(value)
+        ))),
+    }
+}
+
+/// Used by `stacked_get_as`
+#[track_caller]
+pub fn json_deserialize<'a, T: serde::Deserialize<'a>>(value: &'a Value) -> Result<T, Error> {
+    match T::deserialize(value) {
+        Ok(t) => Ok(t),
+        Err(e) => Err(Error::from_err(format!(
+            "expected {}, found {}: {e}",
+            core::any::type_name::<T>(),
+            describe(value)
+        ))),
+    }
+}
+
+/// Used by `stacked_get_deserialize`, `get` is the stringified indexing
+#[track_caller]
+pub fn json_deserialize_at<'a, T: serde::Deserialize<'a>>(
+    value: &'a Value,
+    get: &str,
+) -> Result<T, Error> {
+    match T::deserialize(value) {
+        Ok(t) => Ok(t),
+        Err(e) => Err(Error::from_err_locationless(e).add_err(format!(
+            "stacked_get_deserialize({get}) -> deserializing into {} failed",
+            core::any::type_name::<T>()
+        ))),
+    }
+}
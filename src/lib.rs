@@ -151,7 +151,7 @@
 //! ```text
 //! // in commonly used functions you may want `_locationless` to avoid adding
 //! // on unnecessary information if the location is already being added on
-//! return Err(e.add_err_locationless(ErrorKind::TimeoutError)).stack_err_with(|| {
+//! return Err(e.add_err_locationless(TimeoutError {})).stack_err_with(|| {
 //!     format!(
 //!         "wait_for_ok(num_retries: {num_retries}, delay: {delay:?}) timeout, \
 //!          last error stack was:"
@@ -159,17 +159,58 @@
 //! })
 //! ```
 
+#![no_std]
+
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+mod config;
+mod diff;
 mod error;
 mod fmt;
+mod get;
+#[cfg(feature = "intern")]
+mod intern;
+#[cfg(feature = "serde_json")]
+mod json;
 mod macros;
+#[cfg(feature = "std")]
+mod panic;
+mod shared;
 mod special;
 mod stackable_err;
 
-pub use error::{Error, StackableErrorTrait, StackedError, StackedErrorDowncast};
-pub use fmt::{shorten_location, DisplayStr};
+pub use config::{
+    location_wrap_width, max_frames, max_operand_bytes, register_timeout_predicate,
+    set_location_wrap_width, set_max_frames, set_max_operand_bytes, set_nesting_hook,
+    set_strict_nesting, strict_nesting, DEFAULT_MAX_OPERAND_BYTES, MAX_TIMEOUT_PREDICATES,
+};
+#[allow(deprecated)]
+pub use error::{
+    try_alternatives, Error, ErrorItem, FrameMeta, RootKind, StackableErrorTrait, StackedError,
+    StackedErrorDowncast,
+};
+pub use fmt::{
+    sanitize_location, shorten_location, shorten_location_with, DisplayStr,
+    DEFAULT_LOCATION_PATTERNS,
+};
+pub use get::{StackedGet, StackedIndex};
+#[cfg(feature = "intern")]
+pub use intern::{
+    intern, interner_stats, set_interner_max_entries, InternerStats, DEFAULT_INTERNER_MAX_ENTRIES,
+};
+#[cfg(feature = "serde_json")]
+pub use json::json_path_get;
+#[cfg(feature = "futures")]
+pub use panic::catch_stacked_future;
+#[cfg(feature = "std")]
+pub use panic::{catch_stacked, catch_stacked_result, install_panic_hook, install_panic_hook_with};
+pub use shared::SharedError;
 pub use special::*;
-pub use stackable_err::StackableErr;
+pub use stackable_err::{
+    ErrorAccumulator, IntoError, OkOrStack, StackMapErr, StackableErr, StackableErrSame,
+};
 
 /// A shorthand for [core::result::Result<T, stacked_errors::Error>]
 pub type Result<T> = core::result::Result<T, Error>;
@@ -178,8 +219,46 @@ pub type Result<T> = core::result::Result<T, Error>;
 #[doc(hidden)]
 pub mod __private {
     pub use alloc::format;
-    pub use core::{concat, format_args, stringify};
+    pub use core::{concat, fmt::Write, format_args, module_path, panic, stringify};
+
+    #[cfg(feature = "intern")]
+    pub use crate::intern::intern_fmt;
+    #[cfg(feature = "serde_json")]
+    pub use crate::json::{json_as, json_deserialize, json_deserialize_at, JsonAs};
+    #[cfg(feature = "std")]
+    pub use crate::panic::unwrap_failed;
+    pub use crate::stackable_err::{stack, stack_err};
+
+    /// Used by `stack_try` to discard the error without converting it
+    pub trait StackTry {
+        type Ok;
+        fn into_ok(self) -> Option<Self::Ok>;
+    }
+
+    impl<T, E> StackTry for core::result::Result<T, E> {
+        type Ok = T;
+
+        #[inline]
+        fn into_ok(self) -> Option<T> {
+            self.ok()
+        }
+    }
+
+    impl<T> StackTry for Option<T> {
+        type Ok = T;
 
+        #[inline]
+        fn into_ok(self) -> Option<T> {
+            self
+        }
+    }
+
+    /// Used by `__message`, this is a constant rather than a `cfg` in the macro
+    /// because the macros are expanded with the features of the calling crate
+    pub const LOCATIONS_ONLY: bool = cfg!(feature = "locations-only");
+
+    #[cold]
+    #[inline(never)]
     #[track_caller]
     pub fn format_err(args: core::fmt::Arguments<'_>) -> crate::Error {
         let fmt_arguments_as_str = args.as_str();
@@ -193,6 +272,27 @@ pub mod __private {
         }
     }
 
+    /// Used by the `bail*` macros
+    #[cold]
+    #[inline(never)]
+    #[track_caller]
+    pub fn stack_err_fmt<E: core::fmt::Display + Send + Sync + 'static>(
+        err: E,
+        args: core::fmt::Arguments<'_>,
+    ) -> crate::Error {
+        let fmt_arguments_as_str = args.as_str();
+
+        if let Some(message) = fmt_arguments_as_str {
+            // &'static str
+            stack_err(err, message)
+        } else {
+            // interpolation
+            stack_err(err, alloc::fmt::format(args))
+        }
+    }
+
+    #[cold]
+    #[inline(never)]
     pub fn format_err_locationless(args: core::fmt::Arguments<'_>) -> crate::Error {
         let fmt_arguments_as_str = args.as_str();
 
@@ -204,4 +304,200 @@ pub mod __private {
             crate::Error::from_err_locationless(alloc::fmt::format(args))
         }
     }
+
+    /// Used by the `stacked_assert*` macros
+    #[track_caller]
+    pub fn assert_failed(e: crate::Error) -> ! {
+        panic!("{e}")
+    }
+
+    /// Used by `stacked_get` and `stacked_get_mut`, `prev` are the indexes that
+    /// were successfully applied before `inx` and `kind` is the kind of the
+    /// container that `inx` failed on
+    pub fn format_get_failure(
+        name: &str,
+        head: &str,
+        kind: &str,
+        prev: &[&dyn core::fmt::Debug],
+        inx: &dyn core::fmt::Debug,
+    ) -> alloc::string::String {
+        use core::fmt::Write;
+
+        let mut path = alloc::string::String::new();
+        for inx in prev {
+            let _ = write!(path, "[{inx:?}]");
+        }
+        let at = if path.is_empty() {
+            "the root"
+        } else {
+            path.as_str()
+        };
+        alloc::format!("{name}({head}{path}[{inx:?}]) -> indexing failed at {at} ({kind})")
+    }
+
+    /// Used by `stack_dbg`
+    pub fn format_dbg(vars: &[(&str, &dyn core::fmt::Debug)]) -> alloc::string::String {
+        use core::fmt::Write;
+
+        let mut s = alloc::string::String::new();
+        for (i, (name, value)) in vars.iter().enumerate() {
+            if i != 0 {
+                s.push_str(", ");
+            }
+            let _ = write!(s, "{name} = {value:?}");
+        }
+        s
+    }
+
+    /// Used by `ensure`, for getting the `Debug` of operands that implement it
+    /// and skipping the others
+    pub struct EnsureOperand<'a, T: ?Sized>(pub &'a T);
+
+    /// Used by `ensure`, see [EnsureOperand]
+    pub trait EnsureDebug {
+        fn ensure_debug(&self) -> Option<&dyn core::fmt::Debug>;
+    }
+
+    // method resolution finds this first through the extra reference if `T: Debug`
+    impl<T: core::fmt::Debug + ?Sized> EnsureDebug for &EnsureOperand<'_, T> {
+        fn ensure_debug(&self) -> Option<&dyn core::fmt::Debug> {
+            Some(&self.0)
+        }
+    }
+
+    /// Used by `ensure`, see [EnsureOperand]
+    pub trait EnsureNoDebug {
+        fn ensure_debug(&self) -> Option<&dyn core::fmt::Debug>;
+    }
+
+    impl<T: ?Sized> EnsureNoDebug for EnsureOperand<'_, T> {
+        fn ensure_debug(&self) -> Option<&dyn core::fmt::Debug> {
+            None
+        }
+    }
+
+    /// Used by `ensure` and `stacked_assert`, operands without `Debug` are
+    /// skipped
+    pub fn format_ensure(
+        name: &str,
+        expr: &str,
+        vars: &[(&str, Option<&dyn core::fmt::Debug>)],
+    ) -> alloc::string::String {
+        use core::fmt::Write;
+
+        let mut s = alloc::format!("{name}({expr}) -> assertion failed");
+        let mut first = true;
+        for (name, value) in vars {
+            if let Some(value) = value {
+                s.push_str(if first { " (" } else { ", " });
+                first = false;
+                let _ = write!(s, "{name} = {}", crate::fmt::capped_debug(value, false));
+            }
+        }
+        if !first {
+            s.push(')');
+        }
+        s
+    }
+
+    /// Used by `ensure_eq`
+    pub fn format_ensure_eq<L: core::fmt::Debug + ?Sized, R: core::fmt::Debug + ?Sized>(
+        lhs: &L,
+        rhs: &R,
+    ) -> alloc::string::String {
+        if let Some(diff) = crate::diff::debug_diff(lhs, rhs) {
+            return alloc::format!(
+                "ensure_eq(\n diff (-lhs +rhs):\n{diff}) -> equality assertion failed"
+            )
+        }
+        alloc::format!(
+            "ensure_eq(\n lhs: {}\n rhs: {}\n) -> equality assertion failed",
+            crate::fmt::capped_debug(lhs, false),
+            crate::fmt::capped_debug(rhs, false),
+        )
+    }
+
+    /// Used by `ensure_ne`
+    pub fn format_ensure_ne<L: core::fmt::Debug + ?Sized, R: core::fmt::Debug + ?Sized>(
+        lhs: &L,
+        rhs: &R,
+    ) -> alloc::string::String {
+        alloc::format!(
+            "ensure_ne(\n lhs: {}\n rhs: {}\n) -> inequality assertion failed",
+            crate::fmt::capped_debug(lhs, false),
+            crate::fmt::capped_debug(rhs, false),
+        )
+    }
+
+    /// Used by `matches_stack` and `matches_stack_bool` so that they can take
+    /// `Error`s and `Result`s
+    pub trait MatchesStackTarget {
+        fn matches_stack_target(&self) -> Option<&crate::Error>;
+    }
+
+    impl MatchesStackTarget for crate::Error {
+        fn matches_stack_target(&self) -> Option<&crate::Error> {
+            Some(self)
+        }
+    }
+
+    impl<T> MatchesStackTarget for core::result::Result<T, crate::Error> {
+        fn matches_stack_target(&self) -> Option<&crate::Error> {
+            self.as_ref().err()
+        }
+    }
+
+    impl<T: MatchesStackTarget + ?Sized> MatchesStackTarget for &T {
+        fn matches_stack_target(&self) -> Option<&crate::Error> {
+            (**self).matches_stack_target()
+        }
+    }
+
+    /// Used by `matches_stack` and `matches_stack_bool`, returns a report if
+    /// the needles are not found in distinct frames from newest to oldest
+    pub fn matches_stack<E: MatchesStackTarget + ?Sized>(
+        err: &E,
+        needles: &[&str],
+    ) -> core::result::Result<(), alloc::string::String> {
+        use core::fmt::Write;
+
+        use crate::StackedErrorDowncast;
+
+        let Some(err) = err.matches_stack_target() else {
+            return Err(alloc::string::String::from(
+                "matches_stack failed, the `Result` was `Ok`",
+            ))
+        };
+        let frames: alloc::vec::Vec<alloc::string::String> = err
+            .iter()
+            .rev()
+            .map(|e| alloc::format!("{}", e.get_err()))
+            .collect();
+        let mut frames_iter = frames.iter();
+        let mut failed = None;
+        for (i, needle) in needles.iter().enumerate() {
+            if !frames_iter.any(|frame| frame.contains(needle)) {
+                failed = Some(i);
+                break
+            }
+        }
+        let Some(failed) = failed else { return Ok(()) };
+        let mut s = alloc::format!(
+            "matches_stack failed, needle {:?} was not found in order\n needles:\n",
+            needles[failed]
+        );
+        for (i, needle) in needles.iter().enumerate() {
+            let sign = match i.cmp(&failed) {
+                core::cmp::Ordering::Less => ' ',
+                core::cmp::Ordering::Equal => '-',
+                core::cmp::Ordering::Greater => '?',
+            };
+            let _ = writeln!(s, "  {sign} {needle:?}");
+        }
+        s.push_str(" frames (newest first):");
+        for (i, frame) in frames.iter().enumerate() {
+            let _ = write!(s, "\n  {i}: {frame}");
+        }
+        Err(s)
+    }
 }
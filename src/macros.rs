@@ -1,16 +1,52 @@
 /// Equivalent to `return Err(Error::from_err(format_args!(...)))` if a string
 /// literal, `return Err(Error::from_err(expr))` if a single expression, or
 /// `return Err(Error::from_err(format!(...)))` otherwise.
+///
+/// `bail!(source; ...)` with a semicolon separator is equivalent to
+/// `return Err(source).stack_err(format!(...))`, with `source` as the
+/// locationless root and the message with the location on top of it.
+///
+/// ```
+/// use stacked_errors::{bail, Result};
+///
+/// fn ex(s: &str) -> Result<bool> {
+///     match ron::from_str(s) {
+///         Ok(b) => Ok(b),
+///         Err(e) => bail!(e; "failed to parse \"{s}\""),
+///     }
+/// }
+///
+/// assert_eq!(
+///     format!("{}", ex("invalid").unwrap_err()),
+///     r#"
+///     failed to parse "invalid" at src/macros.rs 10:19
+///     1:1: Expected boolean"#
+/// );
+/// ```
 #[macro_export]
 macro_rules! bail {
     ($msg:literal $(,)?) => {
-        return Err($crate::__private::format_err($crate::__private::format_args!($msg)));
+        return Err($crate::__message!($crate::__private::format_err(
+            $crate::__private::format_args!($msg)
+        )));
     };
     ($err:expr $(,)?) => {
         return Err($crate::Error::from_err($err));
     };
     ($fmt:expr, $($arg:tt)*) => {
-        return Err($crate::Error::from_err($crate::__private::format!($fmt, $($arg)*)));
+        return Err($crate::__message!($crate::Error::from_err(
+            $crate::__private::format!($fmt, $($arg)*)
+        )));
+    };
+    ($source:expr; $($arg:tt)+) => {
+        return Err(if $crate::__private::LOCATIONS_ONLY {
+            $crate::__private::stack($source)
+        } else {
+            $crate::__private::stack_err_fmt(
+                $source,
+                $crate::__private::format_args!($($arg)+)
+            )
+        });
     };
 }
 
@@ -18,46 +54,629 @@ macro_rules! bail {
 #[macro_export]
 macro_rules! bail_locationless {
     ($msg:literal $(,)?) => {
-        return Err($crate::__private::format_err_locationless(
+        return Err($crate::__message!(@locationless $crate::__private::format_err_locationless(
             $crate::__private::format_args!($msg)
-        ));
+        )));
     };
     ($err:expr $(,)?) => {
         return Err($crate::Error::from_err_locationless($err));
     };
     ($fmt:expr, $($arg:tt)*) => {
-        return Err($crate::Error::from_err_locationless(
+        return Err($crate::__message!(@locationless $crate::Error::from_err_locationless(
             $crate::__private::format!($fmt, $($arg)*)
+        )));
+    };
+}
+
+/// Equivalent to `return Err(Error::timeout())`, or if there are arguments,
+/// returns an error with a locationless `TimeoutError` with the `format!`ed
+/// message and location on top of it.
+///
+/// ```
+/// use stacked_errors::{bail_timeout, Result};
+///
+/// fn ex(name: &str) -> Result<()> {
+///     bail_timeout!("waiting for {name}")
+/// }
+///
+/// let e = ex("server").unwrap_err();
+/// assert!(e.is_timeout());
+/// assert_eq!(
+///     format!("{e}"),
+///     r#"
+///     waiting for server at src/macros.rs 8:5
+///     TimeoutError"#
+/// );
+/// ```
+#[macro_export]
+macro_rules! bail_timeout {
+    () => {
+        return Err($crate::Error::timeout());
+    };
+    ($($arg:tt)+) => {
+        return Err($crate::__private::stack_err_fmt(
+            $crate::Error::from_err_locationless($crate::TimeoutError {}),
+            $crate::__private::format_args!($($arg)+)
+        ));
+    };
+}
+
+/// Equivalent to `return Err(Error::probably_not_root_cause())`, or if there
+/// are arguments, returns an error with a locationless
+/// `ProbablyNotRootCauseError` with the `format!`ed message and location on
+/// top of it.
+#[macro_export]
+macro_rules! bail_probably_not_root_cause {
+    () => {
+        return Err($crate::Error::probably_not_root_cause());
+    };
+    ($($arg:tt)+) => {
+        return Err($crate::__private::stack_err_fmt(
+            $crate::Error::from_err_locationless($crate::ProbablyNotRootCauseError {}),
+            $crate::__private::format_args!($($arg)+)
+        ));
+    };
+}
+
+/// Stacks a message onto an existing error and returns it.
+///
+/// `bail_from!(err)` is equivalent to `return Err(err).stack()` and
+/// `bail_from!(err, "...", ...)` is equivalent to
+/// `return Err(err).stack_err(format!("...", ...))`, `err` can be a
+/// [stacked_errors::Error](crate::Error) (which will have the message pushed
+/// onto its stack instead of being nested) or anything implementing
+/// `Display + Send + Sync + 'static`.
+///
+/// ```
+/// use stacked_errors::{bail_from, Result};
+///
+/// fn inner() -> Result<()> {
+///     stacked_errors::bail!("inner failure")
+/// }
+///
+/// fn outer(host: &str) -> Result<()> {
+///     match inner() {
+///         Ok(()) => Ok(()),
+///         Err(e) => bail_from!(e, "outer(.., host: {host})"),
+///     }
+/// }
+///
+/// assert_eq!(outer("localhost").unwrap_err().iter().len(), 2);
+/// ```
+#[macro_export]
+macro_rules! bail_from {
+    ($err:expr $(,)?) => {
+        return Err($crate::__private::stack($err));
+    };
+    ($err:expr, $($arg:tt)+) => {
+        return Err($crate::__private::stack_err_fmt(
+            $err,
+            $crate::__private::format_args!($($arg)+)
         ));
     };
 }
 
+/// The same as `bail!` except that it expands to `break 'label Err(...)`
+/// instead of `return Err(...)`, for short-circuiting a labeled block or loop
+/// instead of the whole function.
+///
+/// ```
+/// use stacked_errors::{break_err, Result};
+///
+/// fn ex(args: &[&str]) -> (usize, Result<u64>) {
+///     let mut parsed = 0;
+///     let res = 'parse: loop {
+///         let mut sum = 0u64;
+///         for arg in args {
+///             match arg.parse::<u64>() {
+///                 Ok(x) => sum += x,
+///                 Err(e) => break_err!('parse, e; "failed to parse {arg:?}"),
+///             }
+///             parsed += 1;
+///         }
+///         if sum == 0 {
+///             break_err!('parse, "sum was zero")
+///         }
+///         break Ok(sum)
+///     };
+///     // the rest of the function still runs
+///     (parsed, res)
+/// }
+///
+/// assert_eq!(ex(&["1", "2"]).1.unwrap(), 3);
+/// let (parsed, res) = ex(&["1", "x", "2"]);
+/// assert_eq!(parsed, 1);
+/// assert_eq!(
+///     format!("{}", res.unwrap_err()),
+///     r#"
+///     failed to parse "x" at src/macros.rs 14:27
+///     invalid digit found in string"#
+/// );
+/// assert_eq!(
+///     format!("{}", ex(&["0"]).1.unwrap_err()),
+///     "\n    sum was zero at src/macros.rs 19:13"
+/// );
+///
+/// // labeled blocks also work
+/// let res: Result<()> = 'block: {
+///     break_err!('block, "early");
+/// };
+/// assert!(res.is_err());
+/// ```
+#[macro_export]
+macro_rules! break_err {
+    ($label:lifetime, $msg:literal $(,)?) => {
+        break $label Err($crate::__message!($crate::__private::format_err(
+            $crate::__private::format_args!($msg)
+        )));
+    };
+    ($label:lifetime, $err:expr $(,)?) => {
+        break $label Err($crate::Error::from_err($err));
+    };
+    ($label:lifetime, $fmt:expr, $($arg:tt)*) => {
+        break $label Err($crate::__message!($crate::Error::from_err(
+            $crate::__private::format!($fmt, $($arg)*)
+        )));
+    };
+    ($label:lifetime, $source:expr; $($arg:tt)+) => {
+        break $label Err(if $crate::__private::LOCATIONS_ONLY {
+            $crate::__private::stack($source)
+        } else {
+            $crate::__private::stack_err_fmt(
+                $source,
+                $crate::__private::format_args!($($arg)+)
+            )
+        });
+    };
+}
+
+/// The expression counterpart to `bail!`. Evaluates to
+/// `Error::from_err(format_args!(...))` if a string literal,
+/// `Error::from_err(expr)` if a single expression, or
+/// `Error::from_err(format!(...))` otherwise.
+///
+/// ```
+/// use stacked_errors::{format_err, Error, Result};
+///
+/// fn ex(x: u8) -> Result<()> {
+///     if x == 0 {
+///         return Err(format_err!("x was zero"))
+///     }
+///     let e: Error = format_err!("x was {x}");
+///     Err(e)
+/// }
+///
+/// assert!(ex(0).is_err());
+/// assert!(ex(1).is_err());
+/// ```
+#[macro_export]
+macro_rules! format_err {
+    ($msg:literal $(,)?) => {
+        $crate::__message!($crate::__private::format_err($crate::__private::format_args!($msg)))
+    };
+    ($err:expr $(,)?) => {
+        $crate::Error::from_err($err)
+    };
+    ($fmt:expr, $($arg:tt)*) => {
+        $crate::__message!($crate::Error::from_err($crate::__private::format!($fmt, $($arg)*)))
+    };
+}
+
+/// The `format_err` macro but with `_locationless` variations
+#[macro_export]
+macro_rules! format_err_locationless {
+    ($msg:literal $(,)?) => {
+        $crate::__message!(@locationless $crate::__private::format_err_locationless(
+            $crate::__private::format_args!($msg)
+        ))
+    };
+    ($err:expr $(,)?) => {
+        $crate::Error::from_err_locationless($err)
+    };
+    ($fmt:expr, $($arg:tt)*) => {
+        $crate::__message!(@locationless $crate::Error::from_err_locationless(
+            $crate::__private::format!($fmt, $($arg)*)
+        ))
+    };
+}
+
 /// For ease of translating from the `eyre` crate, but also the recommended
 /// macro to use if you use this kind of macro
+///
+/// This evaluates to a [stacked_errors::Error](crate::Error) value and does
+/// not return anything by itself, the result should be used (e.g.
+/// `return Err(eyre!(...))`) or else the error is silently dropped.
+///
+/// ```
+/// use stacked_errors::{eyre, Error, Result};
+///
+/// fn ex(x: u8) -> Result<()> {
+///     if x == 0 {
+///         return Err(eyre!("x was zero"))
+///     }
+///     let e: Error = eyre!("x was {x}");
+///     Err(e)
+/// }
+///
+/// assert!(ex(0).is_err());
+/// assert!(ex(1).is_err());
+/// ```
 #[macro_export]
 macro_rules! eyre {
     ($msg:literal $(,)?) => {
-        $crate::__private::format_err($crate::__private::format_args!($msg));
+        $crate::__message!($crate::__private::format_err($crate::__private::format_args!($msg)))
     };
     ($err:expr $(,)?) => {
-        $crate::Error::from_err($err);
+        $crate::Error::from_err($err)
     };
     ($fmt:expr, $($arg:tt)*) => {
-        $crate::Error::from_err($crate::__private::format!($fmt, $($arg)*));
+        $crate::__message!($crate::Error::from_err($crate::__private::format!($fmt, $($arg)*)))
     };
 }
 
 /// For ease of translating from the `anyhow` crate
+///
+/// This evaluates to a [stacked_errors::Error](crate::Error) value and does
+/// not return anything by itself, the result should be used (e.g.
+/// `return Err(anyhow!(...))`) or else the error is silently dropped.
 #[macro_export]
 macro_rules! anyhow {
     ($msg:literal $(,)?) => {
-        $crate::__private::format_err($crate::__private::format_args!($msg));
+        $crate::__message!($crate::__private::format_err($crate::__private::format_args!($msg)))
     };
     ($err:expr $(,)?) => {
-        $crate::Error::from_err($err);
+        $crate::Error::from_err($err)
     };
     ($fmt:expr, $($arg:tt)*) => {
-        $crate::Error::from_err($crate::__private::format!($fmt, $($arg)*));
+        $crate::__message!($crate::Error::from_err($crate::__private::format!($fmt, $($arg)*)))
+    };
+}
+
+/// Captures the `module_path!()` of the call site in a
+/// [ModuleContext](crate::ModuleContext), which is useful when the same file
+/// is used in multiple places or when generated code reuses file names.
+///
+/// `stack_here!()` evaluates to just the payload. `stack_here!(res)` and
+/// `stack_here!(res, "label")` behave like `res.stack()` and
+/// `res.stack_err("label")` except that the pushed frame includes the module
+/// path.
+///
+/// ```
+/// use stacked_errors::{stack_here, Error, Result};
+///
+/// mod net {
+///     use stacked_errors::{stack_here, Error, Result};
+///
+///     pub fn fetch() -> Result<()> {
+///         let res: Result<()> = Err(Error::from_err_locationless("timed out"));
+///         stack_here!(res, "fetching")
+///     }
+/// }
+///
+/// assert_eq!(
+///     format!("{}", net::fetch().unwrap_err()),
+///     "\n    fetching in rust_out::net at src/macros.rs 12:9\n    timed out"
+/// );
+///
+/// let e = Error::from_err(stack_here!());
+/// assert_eq!(format!("{e}"), "\n    in rust_out at src/macros.rs 21:9");
+/// ```
+#[macro_export]
+macro_rules! stack_here {
+    () => {
+        $crate::ModuleContext {
+            module: $crate::__private::module_path!(),
+            label: None,
+        }
+    };
+    ($res:expr) => {
+        $crate::StackableErr::stack_err($res, $crate::stack_here!())
+    };
+    ($res:expr, $label:expr) => {
+        $crate::StackableErr::stack_err($res, $crate::ModuleContext {
+            module: $crate::__private::module_path!(),
+            label: Some($label),
+        })
+    };
+}
+
+/// Snapshots local state into the error. `stack_dbg!(res, a, b.c)` behaves
+/// like `res.stack()` except that on the error path the pushed frame lists
+/// each argument as `name = {:?}`. `Debug` formatting only happens on the error
+/// path. `stack_dbg!(err; a, b.c)` pushes the same frame onto an existing
+/// `Error`.
+///
+/// ```
+/// use stacked_errors::{stack_dbg, Error, Result};
+///
+/// struct Request {
+///     id: u64,
+/// }
+///
+/// fn send(req: &Request, attempt: u8) -> Result<()> {
+///     let res: Result<()> = Err(Error::from_err_locationless("timed out"));
+///     stack_dbg!(res, req.id, attempt)
+/// }
+///
+/// let e = send(&Request { id: 7 }, 2).unwrap_err();
+/// assert_eq!(
+///     format!("{e}"),
+///     "\n    req.id = 7, attempt = 2 at src/macros.rs 13:5\n    timed out"
+/// );
+///
+/// let name = "cfg.toml";
+/// let e = stack_dbg!(Error::from_err_locationless("not found"); name);
+/// assert_eq!(
+///     format!("{e}"),
+///     "\n    name = \"cfg.toml\" at src/macros.rs 23:9\n    not found"
+/// );
+/// ```
+#[macro_export]
+macro_rules! stack_dbg {
+    ($err:expr; $($var:expr),+ $(,)?) => {
+        $crate::Error::add_err($err, $crate::__private::format_dbg(&[
+            $(($crate::__private::stringify!($var), &$var)),+
+        ]))
+    };
+    ($res:expr, $($var:expr),+ $(,)?) => {
+        $crate::StackableErr::stack_err_with($res, || $crate::__private::format_dbg(&[
+            $(($crate::__private::stringify!($var), &$var)),+
+        ]))
+    };
+}
+
+/// Writes the plain (unstyled) multi-line error stack into any
+/// [core::fmt::Write], returning a [core::fmt::Result]. This is the same as
+/// the `Display` output and does not require "std", so it can be used with
+/// e.g. a UART or semihosting writer in embedded contexts.
+///
+/// ```
+/// use stacked_errors::{stacked_log, Error};
+///
+/// let e = Error::from_err_locationless("sensor timed out").add_err_locationless("in poll");
+/// let mut uart = String::new();
+/// stacked_log!(uart, e).unwrap();
+/// assert_eq!(uart, "\n    in poll\n    sensor timed out");
+/// ```
+#[macro_export]
+macro_rules! stacked_log {
+    ($writer:expr, $err:expr $(,)?) => {{
+        use $crate::__private::Write as _;
+        // method syntax so that both `W` and `&mut W` writers work
+        $writer.write_fmt($crate::__private::format_args!("{}", $err))
+    }};
+}
+
+/// Like `unwrap` but for anything implementing
+/// [StackableErr](crate::StackableErr). On the error path the whole styled
+/// error stack is printed to stderr, and then a panic happens with just the
+/// root cause as the message. Requires the "std" feature.
+///
+/// ```should_panic
+/// use stacked_errors::{stacked_unwrap, Error};
+///
+/// let x: Option<u8> = Some(1);
+/// assert_eq!(stacked_unwrap!(x), 1);
+///
+/// stacked_unwrap!(Err::<(), Error>(Error::from_err("root cause")));
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! stacked_unwrap {
+    ($expr:expr $(,)?) => {
+        match $crate::StackableErr::stack($expr) {
+            Ok(o) => o,
+            Err(e) => $crate::__private::unwrap_failed(e),
+        }
+    };
+}
+
+/// The same as `stacked_unwrap` except that `msg` is pushed to the error stack
+/// first. Requires the "std" feature.
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! stacked_expect {
+    ($expr:expr, $msg:expr $(,)?) => {
+        match $crate::StackableErr::stack_err($expr, $msg) {
+            Ok(o) => o,
+            Err(e) => $crate::__private::unwrap_failed(e),
+        }
+    };
+}
+
+/// For contexts where `?` can't be used, such as closures with other return
+/// types, loops, and `Drop` impls.
+///
+/// `stack_try!(expr, flow...)` evaluates to the `Ok` or `Some` value of `expr`,
+/// otherwise `flow...` is evaluated, which can be something like `continue`,
+/// `break`, or `return default`. `stack_try!(expr => acc, flow...)` also
+/// converts the error path with [StackableErr::stack](crate::StackableErr)
+/// and adds it to `acc` beforehand, where `acc` is an
+/// [ErrorAccumulator](crate::ErrorAccumulator) such as a `Vec<Error>`. Without
+/// an accumulator the error is dropped without being converted.
+///
+/// ```
+/// use stacked_errors::{stack_try, Error};
+///
+/// let mut errs: Vec<Error> = vec![];
+/// let mut sum = 0u64;
+/// for s in ["1", "2", "invalid", "4"] {
+///     sum += stack_try!(s.parse::<u64>() => errs, continue);
+/// }
+/// assert_eq!(sum, 7);
+/// assert_eq!(errs.len(), 1);
+///
+/// let f = |x: Option<u64>| -> u64 { stack_try!(x, return 0) + 1 };
+/// assert_eq!(f(Some(1)), 2);
+/// assert_eq!(f(None), 0);
+/// ```
+#[macro_export]
+macro_rules! stack_try {
+    ($expr:expr => $acc:expr, $($flow:tt)+) => {
+        match $crate::StackableErr::stack($expr) {
+            Ok(o) => o,
+            Err(e) => {
+                $crate::ErrorAccumulator::accumulate(&mut $acc, e);
+                $($flow)+
+            }
+        }
+    };
+    ($expr:expr, $($flow:tt)+) => {
+        match $crate::__private::StackTry::into_ok($expr) {
+            Some(o) => o,
+            None => {
+                $($flow)+
+            }
+        }
+    };
+}
+
+/// Defines a marker error type along with an extension trait for
+/// [stacked_errors::Error](crate::Error) that has a constructor and a
+/// predicate for the marker, similar to [Error::timeout](crate::Error::timeout)
+/// and [Error::is_timeout](crate::Error::is_timeout).
+///
+/// A unit struct (which derives `Debug` and `Default`) or a struct with one
+/// `Debug` field can be used. The `Display` impl is the name of the struct,
+/// followed by the `Debug` of the field in parenthesis if there is one.
+///
+/// ```
+/// use stacked_errors::{define_tag_error, Error, Result};
+///
+/// define_tag_error! {
+///     /// Used to signal rate limiting
+///     pub struct RateLimitedError;
+///     pub trait RateLimited { fn rate_limited; fn is_rate_limited; }
+/// }
+///
+/// define_tag_error! {
+///     /// Used to signal that a request should be retried after some number of
+///     /// seconds
+///     pub struct RetryAfterError(pub u64);
+///     pub trait RetryAfter { fn retry_after; fn is_retry_after; }
+/// }
+///
+/// fn ex(limited: bool) -> Result<()> {
+///     if limited {
+///         Err(Error::rate_limited())
+///     } else {
+///         Err(Error::retry_after(5).add_err("request failed"))
+///     }
+/// }
+///
+/// let e = ex(true).unwrap_err();
+/// assert!(e.is_rate_limited());
+/// assert!(!e.is_retry_after());
+/// let e = ex(false).unwrap_err();
+/// assert!(!e.is_rate_limited());
+/// assert!(e.is_retry_after());
+/// assert!(format!("{e}").contains("RetryAfterError(5)"));
+/// ```
+#[macro_export]
+macro_rules! define_tag_error {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident;
+        $tr_vis:vis trait $tr:ident { fn $ctor:ident; fn $is:ident; }
+    ) => {
+        $(#[$attr])*
+        #[derive(Debug, Default)]
+        $vis struct $name;
+
+        impl ::core::fmt::Display for $name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                f.write_str($crate::__private::stringify!($name))
+            }
+        }
+
+        impl ::core::error::Error for $name {}
+
+        #[doc = $crate::__private::concat!(
+            "Extension trait for `",
+            $crate::__private::stringify!($name),
+            "`"
+        )]
+        $tr_vis trait $tr {
+            #[doc = $crate::__private::concat!(
+                "Returns a base `",
+                $crate::__private::stringify!($name),
+                "` error"
+            )]
+            fn $ctor() -> Self;
+
+            #[doc = $crate::__private::concat!(
+                "Returns if a `",
+                $crate::__private::stringify!($name),
+                "` is in the error stack"
+            )]
+            fn $is(&self) -> bool;
+        }
+
+        impl $tr for $crate::Error {
+            #[track_caller]
+            fn $ctor() -> Self {
+                $crate::Error::from_err($name)
+            }
+
+            fn $is(&self) -> bool {
+                self.iter()
+                    .any(|e| $crate::StackedErrorDowncast::downcast_ref::<$name>(e).is_some())
+            }
+        }
+    };
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident($field_vis:vis $field:ty);
+        $tr_vis:vis trait $tr:ident { fn $ctor:ident; fn $is:ident; }
+    ) => {
+        $(#[$attr])*
+        #[derive(Debug)]
+        $vis struct $name($field_vis $field);
+
+        impl ::core::fmt::Display for $name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                f.write_fmt($crate::__private::format_args!(
+                    "{}({:?})",
+                    $crate::__private::stringify!($name),
+                    self.0
+                ))
+            }
+        }
+
+        impl ::core::error::Error for $name {}
+
+        #[doc = $crate::__private::concat!(
+            "Extension trait for `",
+            $crate::__private::stringify!($name),
+            "`"
+        )]
+        $tr_vis trait $tr {
+            #[doc = $crate::__private::concat!(
+                "Returns a base `",
+                $crate::__private::stringify!($name),
+                "` error"
+            )]
+            fn $ctor(x: $field) -> Self;
+
+            #[doc = $crate::__private::concat!(
+                "Returns if a `",
+                $crate::__private::stringify!($name),
+                "` is in the error stack"
+            )]
+            fn $is(&self) -> bool;
+        }
+
+        impl $tr for $crate::Error {
+            #[track_caller]
+            fn $ctor(x: $field) -> Self {
+                $crate::Error::from_err($name(x))
+            }
+
+            fn $is(&self) -> bool {
+                self.iter()
+                    .any(|e| $crate::StackedErrorDowncast::downcast_ref::<$name>(e).is_some())
+            }
+        }
     };
 }
 
@@ -95,28 +714,57 @@ macro_rules! anyhow {
 ///     val1 was false at src/macros.rs 12:5"#
 /// );
 /// ```
+///
+/// If the expression is a comparison (`==`, `!=`, `<`, `<=`, `>`, or `>=`) of
+/// identifiers (or an identifier and a literal), the default message includes
+/// the `Debug` of the identifiers that implement it, truncated like the
+/// operands of [ensure_eq](crate::ensure_eq). Other expressions are only
+/// stringified.
+///
+/// ```
+/// use stacked_errors::{ensure, Result};
+///
+/// fn ex(a: u64, b: u64) -> Result<()> {
+///     ensure!(a < b);
+///     ensure!(a != 0);
+///     ensure!(a.pow(2) < b * 10);
+///     Ok(())
+/// }
+///
+/// assert_eq!(
+///     format!("{}", ex(3, 2).unwrap_err().display_no_locations()),
+///     "\n    ensure(a < b) -> assertion failed (a = 3, b = 2)"
+/// );
+/// assert_eq!(
+///     format!("{}", ex(0, 2).unwrap_err().display_no_locations()),
+///     "\n    ensure(a != 0) -> assertion failed (a = 0)"
+/// );
+/// assert_eq!(
+///     format!("{}", ex(20, 21).unwrap_err().display_no_locations()),
+///     "\n    ensure(a.pow(2) < b * 10) -> assertion failed"
+/// );
+/// ```
 #[macro_export]
 macro_rules! ensure {
-    ($expr:expr) => {
-        if !$expr {
-            return Err($crate::Error::from_err($crate::__private::concat!(
-                "ensure(",
-                $crate::__private::stringify!($expr),
-                ") -> assertion failed"
-            )))
-        }
-    };
     ($expr:expr, $msg:expr) => {
         if !$expr {
-            return Err($crate::Error::from_err($msg))
+            return Err($crate::__message!($crate::Error::from_err($msg)))
         }
     };
+    ($($expr:tt)+) => {
+        $crate::__ensure_cmp!(ensure; $($expr)+)
+    };
 }
 
 /// Asserts that two expressions are equal to each other (with [PartialEq]),
 /// returning a stackable error if they are equal. [Debug] is also required if
 /// there is no custom message.
 ///
+/// When the `Debug` output of either side is long or spans multiple lines
+/// (including escaped newlines in strings), a line diff of the alternate
+/// `Debug` outputs with some context is included in the message instead of
+/// both full values. The "diff" feature computes it with `similar`.
+///
 /// Has `return Err(...)` with a [stacked_errors::Error](crate::Error) and
 /// attached location if the expressions are unequal. A custom message can be
 /// attached that is used as an [Error::from_err](crate::Error::from_err)
@@ -160,10 +808,8 @@ macro_rules! ensure_eq {
             (lhs, rhs) => {
                 // use the double inversion because we are relying on `PartialEq`
                 if !(*lhs == *rhs) {
-                    return Err($crate::Error::from_err($crate::__private::format!(
-                        "ensure_eq(\n lhs: {:?}\n rhs: {:?}\n) -> equality assertion failed",
-                        lhs,
-                        rhs,
+                    return Err($crate::__message!($crate::Error::from_err(
+                        $crate::__private::format_ensure_eq(lhs, rhs),
                     )))
                 }
             }
@@ -173,7 +819,7 @@ macro_rules! ensure_eq {
         match (&$lhs, &$rhs) {
             (lhs, rhs) => {
                 if !(*lhs == *rhs) {
-                    return Err($crate::Error::from_err($msg))
+                    return Err($crate::__message!($crate::Error::from_err($msg)))
                 }
             }
         }
@@ -227,10 +873,8 @@ macro_rules! ensure_ne {
             (lhs, rhs) => {
                 // use the double inversion because we are relying on `PartialEq`
                 if !(*lhs != *rhs) {
-                    return Err($crate::Error::from_err($crate::__private::format!(
-                        "ensure_ne(\n lhs: {:?}\n rhs: {:?}\n) -> inequality assertion failed",
-                        lhs,
-                        rhs,
+                    return Err($crate::__message!($crate::Error::from_err(
+                        $crate::__private::format_ensure_ne(lhs, rhs),
                     )))
                 }
             }
@@ -240,23 +884,410 @@ macro_rules! ensure_ne {
         match (&$lhs, &$rhs) {
             (lhs, rhs) => {
                 if !(*lhs != *rhs) {
-                    return Err($crate::Error::from_err($msg))
+                    return Err($crate::__message!($crate::Error::from_err($msg)))
                 }
             }
         }
     };
 }
 
-/// Applies `get` and `stack_err_with(...)?` in a chain, this is compatible with
-/// many things.
-///
-/// ```
-/// use serde_json::Value;
-/// use stacked_errors::{ensure, stacked_get, Result, StackableErr};
-///
-/// let s = r#"{
-///     "Id": "id example",
-///     "Created": 2023,
+/// The `ensure` macro but without location information, for use in hot helper
+/// functions whose callers add their own location
+#[macro_export]
+macro_rules! ensure_locationless {
+    ($expr:expr) => {
+        if !$expr {
+            return Err($crate::__message!(@locationless $crate::Error::from_err_locationless(
+                $crate::__private::concat!(
+                    "ensure(",
+                    $crate::__private::stringify!($expr),
+                    ") -> assertion failed"
+                ),
+            )))
+        }
+    };
+    ($expr:expr, $msg:expr) => {
+        if !$expr {
+            return Err($crate::__message!(@locationless $crate::Error::from_err_locationless($msg)))
+        }
+    };
+}
+
+/// The `ensure_eq` macro but without location information
+#[macro_export]
+macro_rules! ensure_eq_locationless {
+    ($lhs:expr, $rhs:expr) => {
+        match (&$lhs, &$rhs) {
+            (lhs, rhs) => {
+                if !(*lhs == *rhs) {
+                    return Err($crate::__message!(
+                        @locationless $crate::Error::from_err_locationless(
+                            $crate::__private::format_ensure_eq(lhs, rhs),
+                        )
+                    ))
+                }
+            }
+        }
+    };
+    ($lhs:expr, $rhs:expr, $msg:expr) => {
+        match (&$lhs, &$rhs) {
+            (lhs, rhs) => {
+                if !(*lhs == *rhs) {
+                    return Err($crate::__message!(
+                        @locationless $crate::Error::from_err_locationless($msg)
+                    ))
+                }
+            }
+        }
+    };
+}
+
+/// The `ensure_ne` macro but without location information
+#[macro_export]
+macro_rules! ensure_ne_locationless {
+    ($lhs:expr, $rhs:expr) => {
+        match (&$lhs, &$rhs) {
+            (lhs, rhs) => {
+                if !(*lhs != *rhs) {
+                    return Err($crate::__message!(
+                        @locationless $crate::Error::from_err_locationless(
+                            $crate::__private::format_ensure_ne(lhs, rhs),
+                        )
+                    ))
+                }
+            }
+        }
+    };
+    ($lhs:expr, $rhs:expr, $msg:expr) => {
+        match (&$lhs, &$rhs) {
+            (lhs, rhs) => {
+                if !(*lhs != *rhs) {
+                    return Err($crate::__message!(
+                        @locationless $crate::Error::from_err_locationless($msg)
+                    ))
+                }
+            }
+        }
+    };
+}
+
+/// The same as [ensure] except that it panics with the `Display` rendering of
+/// the error instead of returning it, for use in tests and other places that
+/// do not return a `Result`. The default message starts with
+/// `stacked_assert(...)` and includes the operands like that of `ensure`. A
+/// format string with arguments can also be used as the message.
+///
+/// ```should_panic
+/// use stacked_errors::stacked_assert;
+///
+/// stacked_assert!(1 + 1 == 2);
+/// stacked_assert!(true, "custom message");
+/// let x = 3;
+/// // panics with "x was 3"
+/// stacked_assert!(x < 3, "x was {x}");
+/// ```
+#[macro_export]
+macro_rules! stacked_assert {
+    ($expr:expr, $msg:literal $(,)?) => {
+        if !$expr {
+            $crate::__private::assert_failed($crate::__private::format_err(
+                $crate::__private::format_args!($msg),
+            ))
+        }
+    };
+    ($expr:expr, $msg:expr $(,)?) => {
+        if !$expr {
+            $crate::__private::assert_failed($crate::Error::from_err($msg))
+        }
+    };
+    ($expr:expr, $($arg:tt)+) => {
+        if !$expr {
+            $crate::__private::assert_failed($crate::__private::format_err(
+                $crate::__private::format_args!($($arg)+),
+            ))
+        }
+    };
+    ($($expr:tt)+) => {
+        $crate::__ensure_cmp!(stacked_assert; $($expr)+)
+    };
+}
+
+/// The same as [ensure_eq] except that it panics with the `Display` rendering
+/// of the error instead of returning it. A format string with arguments can
+/// also be used as the message.
+///
+/// ```should_panic
+/// use stacked_errors::stacked_assert_eq;
+///
+/// stacked_assert_eq!(42, 42);
+/// // panics with a message including " lhs: 8\n rhs: 0"
+/// stacked_assert_eq!(8, 0);
+/// ```
+#[macro_export]
+macro_rules! stacked_assert_eq {
+    ($lhs:expr, $rhs:expr) => {
+        match (&$lhs, &$rhs) {
+            (lhs, rhs) => {
+                if !(*lhs == *rhs) {
+                    $crate::__private::assert_failed($crate::Error::from_err(
+                        $crate::__private::format_ensure_eq(lhs, rhs),
+                    ))
+                }
+            }
+        }
+    };
+    ($lhs:expr, $rhs:expr, $msg:literal $(,)?) => {
+        match (&$lhs, &$rhs) {
+            (lhs, rhs) => {
+                if !(*lhs == *rhs) {
+                    $crate::__private::assert_failed($crate::__private::format_err(
+                        $crate::__private::format_args!($msg),
+                    ))
+                }
+            }
+        }
+    };
+    ($lhs:expr, $rhs:expr, $msg:expr $(,)?) => {
+        match (&$lhs, &$rhs) {
+            (lhs, rhs) => {
+                if !(*lhs == *rhs) {
+                    $crate::__private::assert_failed($crate::Error::from_err($msg))
+                }
+            }
+        }
+    };
+    ($lhs:expr, $rhs:expr, $($arg:tt)+) => {
+        match (&$lhs, &$rhs) {
+            (lhs, rhs) => {
+                if !(*lhs == *rhs) {
+                    $crate::__private::assert_failed($crate::__private::format_err(
+                        $crate::__private::format_args!($($arg)+),
+                    ))
+                }
+            }
+        }
+    };
+}
+
+/// The same as [ensure_ne] except that it panics with the `Display` rendering
+/// of the error instead of returning it. A format string with arguments can
+/// also be used as the message.
+///
+/// ```should_panic
+/// use stacked_errors::stacked_assert_ne;
+///
+/// stacked_assert_ne!(42, 8);
+/// stacked_assert_ne!(8, 8, "values were both {}", 8);
+/// ```
+#[macro_export]
+macro_rules! stacked_assert_ne {
+    ($lhs:expr, $rhs:expr) => {
+        match (&$lhs, &$rhs) {
+            (lhs, rhs) => {
+                if !(*lhs != *rhs) {
+                    $crate::__private::assert_failed($crate::Error::from_err(
+                        $crate::__private::format_ensure_ne(lhs, rhs),
+                    ))
+                }
+            }
+        }
+    };
+    ($lhs:expr, $rhs:expr, $msg:literal $(,)?) => {
+        match (&$lhs, &$rhs) {
+            (lhs, rhs) => {
+                if !(*lhs != *rhs) {
+                    $crate::__private::assert_failed($crate::__private::format_err(
+                        $crate::__private::format_args!($msg),
+                    ))
+                }
+            }
+        }
+    };
+    ($lhs:expr, $rhs:expr, $msg:expr $(,)?) => {
+        match (&$lhs, &$rhs) {
+            (lhs, rhs) => {
+                if !(*lhs != *rhs) {
+                    $crate::__private::assert_failed($crate::Error::from_err($msg))
+                }
+            }
+        }
+    };
+    ($lhs:expr, $rhs:expr, $($arg:tt)+) => {
+        match (&$lhs, &$rhs) {
+            (lhs, rhs) => {
+                if !(*lhs != *rhs) {
+                    $crate::__private::assert_failed($crate::__private::format_err(
+                        $crate::__private::format_args!($($arg)+),
+                    ))
+                }
+            }
+        }
+    };
+}
+
+/// Asserts that the given substrings are found in distinct frames of an
+/// [Error](crate::Error), in order from the newest frame to the oldest. This
+/// is less brittle than comparing against the whole rendered error (which
+/// includes line numbers) while still catching ordering bugs. This works on
+/// `Error`s and on `Result<_, Error>`s, where an `Ok` fails the assertion.
+///
+/// Panics with a report of which needle failed and the actual frames.
+///
+/// ```
+/// use stacked_errors::{matches_stack, Error, Result, StackableErr};
+///
+/// let res: Result<()> = Err(Error::from_err("root cause"))
+///     .stack_err("mid layer")
+///     .stack_err("outer context");
+/// matches_stack!(res, ["outer context", "mid layer", "root cause"]);
+/// matches_stack!(res.unwrap_err(), ["outer", "root"]);
+/// ```
+#[macro_export]
+macro_rules! matches_stack {
+    ($err:expr, [$($needle:expr),* $(,)?]) => {
+        if let Err(report) = $crate::__private::matches_stack(&$err, &[$($needle),*]) {
+            $crate::__private::panic!("{}", report)
+        }
+    };
+}
+
+/// The same as [matches_stack] except that this evaluates to a `bool` instead
+/// of panicking
+///
+/// ```
+/// use stacked_errors::{matches_stack_bool, Error};
+///
+/// let e = Error::from_err("root cause").add_err("outer context");
+/// assert!(matches_stack_bool!(e, ["outer context", "root cause"]));
+/// assert!(!matches_stack_bool!(e, ["root cause", "outer context"]));
+/// ```
+#[macro_export]
+macro_rules! matches_stack_bool {
+    ($err:expr, [$($needle:expr),* $(,)?]) => {
+        $crate::__private::matches_stack(&$err, &[$($needle),*]).is_ok()
+    };
+}
+
+/// Applies [json_path_get](crate::json_path_get) and `?` to a dotted path, for
+/// when the path comes from configuration. Requires the "serde_json" feature.
+///
+/// ```
+/// use serde_json::json;
+/// use stacked_errors::{ensure, stacked_get_path, Result};
+///
+/// fn ex(path: &str) -> Result<()> {
+///     let value = json!({"State": {"Status": "running"}});
+///     ensure!(stacked_get_path!(value, path) == "running");
+///     Ok(())
+/// }
+///
+/// ex("State.Status").unwrap();
+/// assert!(ex("State.Other").is_err());
+/// ```
+#[cfg(feature = "serde_json")]
+#[macro_export]
+macro_rules! stacked_get_path {
+    ($value:expr, $path:expr) => {
+        $crate::json_path_get(&$value, $path)?
+    };
+}
+
+/// Applies [stacked_get] and then a typed conversion, with `?` applied to
+/// both. The type can be one of `u64`, `i64`, `f64`, `bool`, or `str`, or it
+/// can be `deserialize T` for any `T: serde::Deserialize`. A conversion
+/// failure names the type that was found. Requires the "serde_json" feature.
+///
+/// ```
+/// use serde_json::json;
+/// use stacked_errors::{stacked_get_as, Result};
+///
+/// fn ex(value: &serde_json::Value) -> Result<()> {
+///     let created = stacked_get_as!(value["Created"], u64);
+///     assert_eq!(created, 2023);
+///     let args: Vec<String> = stacked_get_as!(value["Args"], deserialize Vec<String>);
+///     assert_eq!(args, ["--uuid"]);
+///     let _ = stacked_get_as!(value["Id"], u64);
+///     Ok(())
+/// }
+///
+/// let value = json!({"Created": 2023, "Args": ["--uuid"], "Id": "2023"});
+/// let e = ex(&value).unwrap_err();
+/// assert_eq!(
+///     format!("{e}"),
+///     "\n    expected u64, found string \"2023\" at src/macros.rs 13:13"
+/// );
+/// ```
+#[cfg(feature = "serde_json")]
+#[macro_export]
+macro_rules! stacked_get_as {
+    (@split [$($get:tt)+] , str) => {
+        $crate::__private::json_as::<&str>($crate::stacked_get!($($get)+))?
+    };
+    (@split [$($get:tt)+] , deserialize $ty:ty) => {
+        $crate::__private::json_deserialize::<$ty>($crate::stacked_get!($($get)+))?
+    };
+    (@split [$($get:tt)+] , $ty:ty) => {
+        $crate::__private::json_as::<$ty>($crate::stacked_get!($($get)+))?
+    };
+    (@split [$($get:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::stacked_get_as!(@split [$($get)* $next] $($rest)*)
+    };
+    ($($t:tt)+) => {
+        $crate::stacked_get_as!(@split [] $($t)+)
+    };
+}
+
+/// Applies [stacked_get] and then deserializes the value into `T` without
+/// cloning, with `?` applied to both. On a deserialization failure the serde
+/// error is the root and a frame naming the indexing and `T` is stacked on top
+/// of it. Requires the "serde_json" feature.
+///
+/// ```
+/// use serde_json::json;
+/// use stacked_errors::{stacked_get_deserialize, Result};
+///
+/// #[derive(serde::Deserialize)]
+/// struct Limits {
+///     max: u32,
+/// }
+///
+/// fn ex(value: &serde_json::Value) -> Result<u32> {
+///     let limits = stacked_get_deserialize!(value["cfg"]["limits"], Limits);
+///     Ok(limits.max)
+/// }
+///
+/// assert_eq!(ex(&json!({"cfg": {"limits": {"max": 8}}})).unwrap(), 8);
+/// assert!(ex(&json!({"cfg": {"limits": {"max": "8"}}})).is_err());
+/// ```
+#[cfg(feature = "serde_json")]
+#[macro_export]
+macro_rules! stacked_get_deserialize {
+    (@split [$($get:tt)+] , $ty:ty) => {
+        $crate::__private::json_deserialize_at::<$ty>(
+            $crate::stacked_get!($($get)+),
+            $crate::__private::stringify!($($get)+),
+        )?
+    };
+    (@split [$($get:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::stacked_get_deserialize!(@split [$($get)* $next] $($rest)*)
+    };
+    ($($t:tt)+) => {
+        $crate::stacked_get_deserialize!(@split [] $($t)+)
+    };
+}
+
+/// Indexes into a [StackedGet](crate::StackedGet) value in a chain, returning
+/// an error with the path traversed so far and the kind of container indexing
+/// failed on. The head can be any expression such as `self.config` or
+/// `resp.json()`, and it is evaluated only once.
+#[cfg_attr(feature = "serde_json", doc = "```")]
+#[cfg_attr(not(feature = "serde_json"), doc = "```ignore")]
+/// use serde_json::Value;
+/// use stacked_errors::{ensure, stacked_get, Result, StackableErr};
+///
+/// let s = r#"{
+///     "Id": "id example",
+///     "Created": 2023,
 ///     "Args": [
 ///         "--entry-name",
 ///         "--uuid"
@@ -291,40 +1322,54 @@ macro_rules! ensure_ne {
 ///     Ok(())
 /// }
 ///
-/// assert!(ex1(s).is_err());
+/// let e = ex1(s).unwrap_err();
+/// let e = format!("{e}");
+/// assert!(e.contains(r#"stacked_get(value["State"]["nonexistent"])"#));
+/// assert!(e.contains(r#"indexing failed at ["State"] (json object)"#));
 /// ```
 #[macro_export]
 macro_rules! stacked_get {
-    ($value:ident [$inx0:expr] $([$inx1:expr])*) => {{
-        // this is unrolled once to avoid a let binding
-        // and allow multiple kinds of borrowing
+    // applies the rest of the indexes one at a time, keeping track of the indexes
+    // already applied for the error message
+    (@chain $head:ident, $tmp:ident, [$($prev:expr),*]) => {};
+    (@chain $head:ident, $tmp:ident, [$($prev:expr),*] [$inx:expr] $($rest:tt)*) => {
+        $tmp = $tmp.__stacked_get(&$inx, |kind| {
+            $crate::__private::format_get_failure("stacked_get", $head, kind, &[$(&$prev),*], &$inx)
+        })?;
+        $crate::stacked_get!(@chain $head, $tmp, [$($prev,)* $inx] $($rest)*);
+    };
+    // the head can be an arbitrary expression, but an `expr` matcher would also
+    // consume the indexing, so tokens are moved into the head until only
+    // `[...]` groups are left
+    (@split [$($head:tt)+] [$inx0:expr] $([$inx1:expr])*) => {{
+        // this is unrolled once to avoid a let binding of the head, which allows
+        // multiple kinds of borrowing and means that it is only evaluated once
+        use $crate::StackedGet as _;
+        let head = $crate::__private::stringify!($($head)+);
         #[allow(unused)]
-        let mut tmp = $crate::StackableErr::stack_err_with($value.get($inx0),
-            || {$crate::__private::format!(
-                "stacked_get({} ... [{:?}] ...) -> indexing failed",
-                $crate::__private::stringify!($value),
-                $inx0
-            )}
-        )?;
-        $(
-            tmp = $crate::StackableErr::stack_err_with(tmp.get($inx1),
-                || $crate::__private::format!(
-                    "stacked_get({} ... [{:?}] ...) -> indexing failed",
-                    $crate::__private::stringify!($value),
-                    $inx1
-                )
-            )?;
-        )*
+        let mut tmp = ($($head)+).__stacked_get(&$inx0, |kind| {
+            $crate::__private::format_get_failure("stacked_get", head, kind, &[], &$inx0)
+        })?;
+        $crate::stacked_get!(@chain head, tmp, [$inx0] $([$inx1])*);
         tmp
     }};
+    (@split [$($head:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::stacked_get!(@split [$($head)* $next] $($rest)*)
+    };
+    ($($t:tt)+) => {
+        $crate::stacked_get!(@split [] $($t)+)
+    };
 }
 
-/// Applies `get_mut` and `stack_err_with(...)?` in a chain, this is compatible
-/// with many things.
-///
-/// ```
+/// Mutably indexes into a [StackedGet](crate::StackedGet) value in a chain,
+/// returning an error with the path traversed so far and the kind of container
+/// indexing failed on. The head can be any expression such as `self.config` or
+/// `resp.json_mut()`, and it is evaluated only once.
+#[cfg_attr(feature = "serde_json", doc = "```")]
+#[cfg_attr(not(feature = "serde_json"), doc = "```ignore")]
 /// use serde_json::Value;
-/// use stacked_errors::{ensure, stacked_get, stacked_get_mut, Result, StackableErr};
+/// use stacked_errors::{ensure, stacked_get, stacked_get_mut};
+/// use stacked_errors::{Result, StackableErr};
 ///
 /// let s = r#"{
 ///     "Id": "id example",
@@ -371,28 +1416,273 @@ macro_rules! stacked_get {
 ///     Ok(())
 /// }
 ///
-/// assert!(ex1(s).is_err());
+/// let e = ex1(s).unwrap_err();
+/// let e = format!("{e}");
+/// assert!(e.contains(r#"stacked_get_mut(value["State"]["nonexistent"])"#));
+/// assert!(e.contains(r#"indexing failed at ["State"] (json object)"#));
 /// ```
 #[macro_export]
 macro_rules! stacked_get_mut {
-    ($value:ident [$inx0:expr] $([$inx1:expr])*) => {{
-        #[allow(unused)]
-        let mut tmp = $crate::StackableErr::stack_err_with($value.get_mut($inx0),
-            || $crate::__private::format!(
-                "stacked_get_mut({} ... [{:?}] ...) -> indexing failed",
-                $crate::__private::stringify!($value),
-                $inx0
+    // applies the rest of the indexes one at a time, keeping track of the indexes
+    // already applied for the error message
+    (@chain $head:ident, $tmp:ident, [$($prev:expr),*]) => {};
+    (@chain $head:ident, $tmp:ident, [$($prev:expr),*] [$inx:expr] $($rest:tt)*) => {
+        $tmp = $tmp.__stacked_get_mut(&$inx, |kind| {
+            $crate::__private::format_get_failure(
+                "stacked_get_mut", $head, kind, &[$(&$prev),*], &$inx
             )
-        )?;
-        $(
-            tmp = $crate::StackableErr::stack_err_with(tmp.get_mut($inx1),
-                || $crate::__private::format!(
-                    "stacked_get_mut({} ... [{:?}] ...) -> indexing failed",
-                    $crate::__private::stringify!($value),
-                    $inx1
-                )
-            )?;
-        )*
+        })?;
+        $crate::stacked_get_mut!(@chain $head, $tmp, [$($prev,)* $inx] $($rest)*);
+    };
+    // the head can be an arbitrary expression, but an `expr` matcher would also
+    // consume the indexing, so tokens are moved into the head until only
+    // `[...]` groups are left
+    (@split [$($head:tt)+] [$inx0:expr] $([$inx1:expr])*) => {{
+        use $crate::StackedGet as _;
+        let head = $crate::__private::stringify!($($head)+);
+        #[allow(unused)]
+        let mut tmp = ($($head)+).__stacked_get_mut(&$inx0, |kind| {
+            $crate::__private::format_get_failure("stacked_get_mut", head, kind, &[], &$inx0)
+        })?;
+        $crate::stacked_get_mut!(@chain head, tmp, [$inx0] $([$inx1])*);
         tmp
     }};
+    (@split [$($head:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::stacked_get_mut!(@split [$($head)* $next] $($rest)*)
+    };
+    ($($t:tt)+) => {
+        $crate::stacked_get_mut!(@split [] $($t)+)
+    };
+}
+
+/// Assigns to the value found by [stacked_get_mut], with `.into()` applied to
+/// the right hand side.
+#[cfg_attr(feature = "serde_json", doc = "```")]
+#[cfg_attr(not(feature = "serde_json"), doc = "```ignore")]
+/// use serde_json::{json, Value};
+/// use stacked_errors::{stacked_set, Result};
+///
+/// fn ex(value: &mut Value) -> Result<()> {
+///     stacked_set!(value["State"]["Status"] = "stopped");
+///     Ok(())
+/// }
+///
+/// let mut value = json!({"State": {"Status": "running"}});
+/// ex(&mut value).unwrap();
+/// assert_eq!(value, json!({"State": {"Status": "stopped"}}));
+///
+/// // the key must exist, see `stacked_set_insert` for creating it
+/// let mut value = json!({"State": {}});
+/// assert!(ex(&mut value).is_err());
+/// ```
+#[macro_export]
+macro_rules! stacked_set {
+    (@split [$($get:tt)+] = $val:expr) => {
+        *$crate::stacked_get_mut!($($get)+) = ($val).into()
+    };
+    (@split [$($get:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::stacked_set!(@split [$($get)* $next] $($rest)*)
+    };
+    ($($t:tt)+) => {
+        $crate::stacked_set!(@split [] $($t)+)
+    };
+}
+
+/// The same as [stacked_set] except that the final key is allowed to be
+/// absent, in which case it is inserted using `IndexMut`. Missing intermediate
+/// keys are still errors. Note that the `IndexMut` impl of
+/// `serde_json::Value` panics if the final container is not an object or
+/// `null`.
+#[cfg_attr(feature = "serde_json", doc = "```")]
+#[cfg_attr(not(feature = "serde_json"), doc = "```ignore")]
+/// use serde_json::{json, Value};
+/// use stacked_errors::{stacked_set_insert, Result};
+///
+/// fn ex(value: &mut Value) -> Result<()> {
+///     stacked_set_insert!(value["State"]["Status"] = "stopped");
+///     Ok(())
+/// }
+///
+/// let mut value = json!({"State": {}});
+/// ex(&mut value).unwrap();
+/// assert_eq!(value, json!({"State": {"Status": "stopped"}}));
+///
+/// let mut value = json!({});
+/// assert!(ex(&mut value).is_err());
+/// ```
+#[macro_export]
+macro_rules! stacked_set_insert {
+    // finds the container of the final key
+    (@target [$($head:tt)+] [$inx0:expr] $([$inx1:expr])*) => {
+        (*$crate::stacked_get_mut!($($head)+ [$inx0] $([$inx1])*))
+    };
+    (@target [$($head:tt)+]) => {
+        ($($head)+)
+    };
+    (@target [$($head:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::stacked_set_insert!(@target [$($head)* $next] $($rest)*)
+    };
+    (@split [$($get:tt)+] [$last:expr] = $val:expr) => {
+        $crate::stacked_set_insert!(@target [] $($get)+)[$last] = ($val).into()
+    };
+    (@split [$($get:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::stacked_set_insert!(@split [$($get)* $next] $($rest)*)
+    };
+    ($($t:tt)+) => {
+        $crate::stacked_set_insert!(@split [] $($t)+)
+    };
+}
+
+/// Used by the message arms of the `bail`, `format_err`, and `ensure*`
+/// macros, never for arms that take a source error. With the "locations-only"
+/// feature the message is still type checked, but it is never evaluated and
+/// only a `UnitError` with the location (or without, for the `_locationless`
+/// macros) is returned.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __message {
+    (@locationless $err:expr) => {
+        if $crate::__private::LOCATIONS_ONLY {
+            $crate::Error::from_err_locationless($crate::UnitError {})
+        } else {
+            $err
+        }
+    };
+    ($err:expr) => {
+        if $crate::__private::LOCATIONS_ONLY {
+            $crate::Error::new()
+        } else {
+            $err
+        }
+    };
+}
+
+/// Formats a message like `format!` and returns it as an `Arc<str>` shared
+/// through the global interner, for context messages that repeat often.
+/// Messages that are already interned do not allocate. Requires the "intern"
+/// feature.
+///
+/// ```
+/// use stacked_errors::{intern, Result, StackableErr};
+///
+/// fn connect(host: &str) -> Result<()> {
+///     Err(std::io::Error::other("refused")).stack_err_with(|| intern!("connecting to {host}"))
+/// }
+///
+/// let e = connect("db0").unwrap_err();
+/// assert!(format!("{e}").contains("connecting to db0"));
+/// ```
+#[cfg(feature = "intern")]
+#[macro_export]
+macro_rules! intern {
+    ($($arg:tt)+) => {
+        $crate::__private::intern_fmt($crate::__private::format_args!($($arg)+))
+    };
+}
+
+/// Used by `ensure!` and `stacked_assert!` for picking the operands that are
+/// included in the default message. `$mode` is the name of the macro.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __ensure_cmp {
+    // the comparison operators are listed explicitly, `$op:tt` would also match
+    // e.g. the `.` of `ensure!(cfg.enabled)`
+    ($mode:ident; $lhs:ident == $rhs:ident) => {
+        $crate::__ensure!($mode; $lhs == $rhs; $lhs, $rhs)
+    };
+    ($mode:ident; $lhs:ident == $rhs:literal) => {
+        $crate::__ensure!($mode; $lhs == $rhs; $lhs)
+    };
+    ($mode:ident; $lhs:literal == $rhs:ident) => {
+        $crate::__ensure!($mode; $lhs == $rhs; $rhs)
+    };
+    ($mode:ident; $lhs:ident != $rhs:ident) => {
+        $crate::__ensure!($mode; $lhs != $rhs; $lhs, $rhs)
+    };
+    ($mode:ident; $lhs:ident != $rhs:literal) => {
+        $crate::__ensure!($mode; $lhs != $rhs; $lhs)
+    };
+    ($mode:ident; $lhs:literal != $rhs:ident) => {
+        $crate::__ensure!($mode; $lhs != $rhs; $rhs)
+    };
+    ($mode:ident; $lhs:ident < $rhs:ident) => {
+        $crate::__ensure!($mode; $lhs < $rhs; $lhs, $rhs)
+    };
+    ($mode:ident; $lhs:ident < $rhs:literal) => {
+        $crate::__ensure!($mode; $lhs < $rhs; $lhs)
+    };
+    ($mode:ident; $lhs:literal < $rhs:ident) => {
+        $crate::__ensure!($mode; $lhs < $rhs; $rhs)
+    };
+    ($mode:ident; $lhs:ident <= $rhs:ident) => {
+        $crate::__ensure!($mode; $lhs <= $rhs; $lhs, $rhs)
+    };
+    ($mode:ident; $lhs:ident <= $rhs:literal) => {
+        $crate::__ensure!($mode; $lhs <= $rhs; $lhs)
+    };
+    ($mode:ident; $lhs:literal <= $rhs:ident) => {
+        $crate::__ensure!($mode; $lhs <= $rhs; $rhs)
+    };
+    ($mode:ident; $lhs:ident > $rhs:ident) => {
+        $crate::__ensure!($mode; $lhs > $rhs; $lhs, $rhs)
+    };
+    ($mode:ident; $lhs:ident > $rhs:literal) => {
+        $crate::__ensure!($mode; $lhs > $rhs; $lhs)
+    };
+    ($mode:ident; $lhs:literal > $rhs:ident) => {
+        $crate::__ensure!($mode; $lhs > $rhs; $rhs)
+    };
+    ($mode:ident; $lhs:ident >= $rhs:ident) => {
+        $crate::__ensure!($mode; $lhs >= $rhs; $lhs, $rhs)
+    };
+    ($mode:ident; $lhs:ident >= $rhs:literal) => {
+        $crate::__ensure!($mode; $lhs >= $rhs; $lhs)
+    };
+    ($mode:ident; $lhs:literal >= $rhs:ident) => {
+        $crate::__ensure!($mode; $lhs >= $rhs; $rhs)
+    };
+    ($mode:ident; $expr:expr) => {
+        $crate::__ensure!($mode; $expr;)
+    };
+}
+
+/// Used by `__ensure_cmp!`
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __ensure {
+    (ensure; $expr:expr; $($var:ident),*) => {
+        if !$expr {
+            return Err($crate::__message!($crate::Error::from_err(
+                $crate::__ensure!(@msg ensure; $expr; $($var),*)
+            )))
+        }
+    };
+    (stacked_assert; $expr:expr; $($var:ident),*) => {
+        if !$expr {
+            $crate::__private::assert_failed($crate::Error::from_err(
+                $crate::__ensure!(@msg stacked_assert; $expr; $($var),*)
+            ))
+        }
+    };
+    // expressions without operands get a static message
+    (@msg $mode:ident; $expr:expr;) => {
+        $crate::__private::concat!(
+            $crate::__private::stringify!($mode),
+            "(",
+            $crate::__private::stringify!($expr),
+            ") -> assertion failed"
+        )
+    };
+    (@msg $mode:ident; $expr:expr; $($var:ident),+) => {{
+        #[allow(unused_imports)]
+        use $crate::__private::{EnsureDebug as _, EnsureNoDebug as _};
+        $crate::__private::format_ensure(
+            $crate::__private::stringify!($mode),
+            $crate::__private::stringify!($expr),
+            &[$((
+                $crate::__private::stringify!($var),
+                (&&$crate::__private::EnsureOperand(&$var)).ensure_debug(),
+            )),+],
+        )
+    }};
 }
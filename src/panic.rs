@@ -0,0 +1,281 @@
+use alloc::{boxed::Box, format, string::String};
+use core::{any::Any, panic::Location};
+use std::{
+    panic::{catch_unwind, PanicHookInfo, UnwindSafe},
+    sync::Mutex,
+};
+
+use crate::{
+    error::caller, Error, PanicError, PanicLocation, PanicPayload, StackedErrorDowncast, UnitError,
+};
+
+/// Converts a panic payload into an `Error` with the message of the payload
+/// and a `PanicError` with location on top
+#[track_caller]
+pub(crate) fn from_payload(payload: Box<dyn Any + Send>) -> Error {
+    from_payload_at(payload, caller(), false)
+}
+
+/// Like `from_payload` with a location captured earlier, if `retain` then a
+/// non-string payload is kept in a `PanicPayload`
+fn from_payload_at(
+    payload: Box<dyn Any + Send>,
+    l: Option<&'static Location<'static>>,
+    retain: bool,
+) -> Error {
+    let mut e = match payload.downcast::<&'static str>() {
+        Ok(s) => Error::from_err_locationless(*s),
+        Err(payload) => match payload.downcast::<String>() {
+            Ok(s) => Error::from_err_locationless(*s),
+            Err(payload) if retain => {
+                Error::from_err_locationless(PanicPayload(Mutex::new(payload)))
+            }
+            Err(_) => Error::from_err_locationless("<unknown panic payload>"),
+        },
+    };
+    e.push_err_at(PanicError {}, l);
+    e
+}
+
+impl Error {
+    /// Converts the payload of a panic caught elsewhere (e.g. from
+    /// `JoinHandle::join` or `catch_unwind`) into an `Error` with the message
+    /// of the payload and a `PanicError` with the caller location on top. A
+    /// payload that is not a `&'static str` or `String` is kept in a
+    /// [PanicPayload] frame for later downcasting.
+    ///
+    /// ```
+    /// use stacked_errors::{Error, PanicPayload, StackedErrorDowncast};
+    ///
+    /// let payload = std::thread::spawn(|| panic!("oh no")).join().unwrap_err();
+    /// let e = Error::from_panic_payload(payload);
+    /// assert!(e.is_panic());
+    /// assert_eq!(
+    ///     *e.iter().next().unwrap().downcast_ref::<&str>().unwrap(),
+    ///     "oh no"
+    /// );
+    ///
+    /// let payload = std::thread::spawn(|| std::panic::panic_any(7u8))
+    ///     .join()
+    ///     .unwrap_err();
+    /// let mut e = Error::from_panic_payload(payload);
+    /// let x = e.iter_mut().next().unwrap();
+    /// let payload = x.downcast_mut::<PanicPayload>().unwrap();
+    /// assert_eq!(*payload.get_mut().downcast_mut::<u8>().unwrap(), 7);
+    /// ```
+    #[track_caller]
+    pub fn from_panic_payload(payload: Box<dyn Any + Send>) -> Self {
+        from_payload_at(payload, caller(), true)
+    }
+
+    /// Converts the information given to a panic hook into an `Error` with
+    /// the panic message at the root, a locationless `PanicError`, and a
+    /// [PanicLocation] on top if the panic has a location. This is for custom
+    /// panic hooks, see [install_panic_hook] for a ready made one.
+    ///
+    /// ```
+    /// use stacked_errors::Error;
+    ///
+    /// let prev = std::panic::take_hook();
+    /// std::panic::set_hook(Box::new(|info| {
+    ///     let e = Error::from_panic_info(info);
+    ///     assert!(e.is_panic());
+    ///     assert!(format!("{e}").contains("panicked at src/panic.rs"));
+    /// }));
+    /// let _ = std::panic::catch_unwind(|| panic!("oh no"));
+    /// std::panic::set_hook(prev);
+    /// ```
+    pub fn from_panic_info(info: &PanicHookInfo<'_>) -> Self {
+        let payload = info.payload();
+        let mut e = if let Some(s) = payload.downcast_ref::<&'static str>() {
+            Error::from_err_locationless(*s)
+        } else if let Some(s) = payload.downcast_ref::<String>() {
+            Error::from_err_locationless(s.clone())
+        } else {
+            Error::from_err_locationless("<unknown panic payload>")
+        };
+        e.push_err_locationless(PanicError {});
+        if let Some(l) = info.location() {
+            e.push_err_locationless(PanicLocation {
+                file: String::from(l.file()),
+                line: l.line(),
+                column: l.column(),
+            });
+        }
+        e
+    }
+}
+
+/// Installs a panic hook that prints panics as stacked reports (see
+/// [Error::from_panic_info]) to stderr, with the same styled `Debug` format
+/// as returned errors. The previous hook is replaced, use
+/// [install_panic_hook_with] to also call it or to send the reports elsewhere.
+///
+/// ```no_run
+/// stacked_errors::install_panic_hook();
+/// ```
+pub fn install_panic_hook() {
+    install_panic_hook_with(false, |e| std::eprintln!("{}", panic_report(e)))
+}
+
+/// Installs a panic hook that calls `f` with the stacked report of each panic
+/// (see [Error::from_panic_info]), and then the previous hook if `forward`
+///
+/// ```
+/// use std::sync::mpsc;
+///
+/// use stacked_errors::install_panic_hook_with;
+///
+/// let (tx, rx) = mpsc::channel();
+/// install_panic_hook_with(false, move |e| {
+///     let _ = tx.send(format!("{e}"));
+/// });
+/// let _ = std::panic::catch_unwind(|| panic!("oh no"));
+/// let _ = std::panic::take_hook();
+/// assert!(rx.recv().unwrap().contains("oh no"));
+/// ```
+pub fn install_panic_hook_with<F: Fn(&Error) + Send + Sync + 'static>(forward: bool, f: F) {
+    let prev = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        f(&Error::from_panic_info(info));
+        if forward {
+            prev(info);
+        }
+    }));
+}
+
+/// The styled report that `install_panic_hook` prints
+fn panic_report(e: &Error) -> String {
+    match std::thread::current().name() {
+        Some(name) => format!("thread '{name}' panicked:{e:?}"),
+        None => format!("thread panicked:{e:?}"),
+    }
+}
+
+/// Calls `f` with [std::panic::catch_unwind], converting a panic into an
+/// `Error` with the panic message and a `PanicError` with the location of the
+/// `catch_stacked` call. This allows panicking subtasks to be handled the same
+/// way as erroring ones.
+///
+/// Note that the panic hook will still be called as usual.
+///
+/// ```
+/// use stacked_errors::{catch_stacked, StackedErrorDowncast};
+///
+/// let res = catch_stacked(|| 42u8);
+/// assert_eq!(res.unwrap(), 42);
+///
+/// let e = catch_stacked(|| panic!("oh no")).unwrap_err();
+/// assert!(e.is_panic());
+/// let root = e.iter().next().unwrap();
+/// assert_eq!(*root.downcast_ref::<&str>().unwrap(), "oh no");
+/// ```
+#[track_caller]
+pub fn catch_stacked<T, F: FnOnce() -> T + UnwindSafe>(f: F) -> Result<T, Error> {
+    match catch_unwind(f) {
+        Ok(o) => Ok(o),
+        Err(payload) => Err(from_payload(payload)),
+    }
+}
+
+/// Like [catch_stacked] for closures that return a `Result<T, Error>`, the
+/// result is flattened so that an `Err` returned by `f` passes through
+/// unchanged and a panic becomes an `Err` like with `catch_stacked`
+///
+/// ```
+/// use stacked_errors::{bail, catch_stacked_result, Result};
+///
+/// fn work(x: u8) -> Result<u8> {
+///     if x == 0 {
+///         bail!("zero")
+///     }
+///     Ok(100 / (x - 1))
+/// }
+///
+/// assert_eq!(catch_stacked_result(|| work(2)).unwrap(), 100);
+/// let e = catch_stacked_result(|| work(0)).unwrap_err();
+/// assert!(!e.is_panic());
+/// let e = catch_stacked_result(|| work(1)).unwrap_err();
+/// assert!(e.is_panic());
+/// ```
+#[track_caller]
+pub fn catch_stacked_result<T, F: FnOnce() -> Result<T, Error> + UnwindSafe>(
+    f: F,
+) -> Result<T, Error> {
+    match catch_unwind(f) {
+        Ok(res) => res,
+        Err(payload) => Err(from_payload(payload)),
+    }
+}
+
+/// The async version of [catch_stacked], panics while polling `f` are
+/// converted into an `Error` with a `PanicError` with the location of the
+/// `catch_stacked_future` call. `f` is wrapped in `AssertUnwindSafe`, the
+/// same considerations as for `catch_unwind` apply to state shared with `f`.
+/// Requires the "futures" feature.
+///
+/// ```
+/// use stacked_errors::catch_stacked_future;
+///
+/// # let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+/// # rt.block_on(async {
+/// let e = catch_stacked_future(async { panic!("oh no") })
+///     .await
+///     .unwrap_err();
+/// assert!(e.is_panic());
+/// assert_eq!(catch_stacked_future(async { 42u8 }).await.unwrap(), 42);
+/// # });
+/// ```
+#[cfg(feature = "futures")]
+#[track_caller]
+pub fn catch_stacked_future<F: core::future::Future>(
+    f: F,
+) -> impl core::future::Future<Output = Result<F::Output, Error>> {
+    let l = caller();
+    async move {
+        match futures_util::FutureExt::catch_unwind(std::panic::AssertUnwindSafe(f)).await {
+            Ok(o) => Ok(o),
+            Err(payload) => Err(from_payload_at(payload, l, false)),
+        }
+    }
+}
+
+/// The styled report that `stacked_unwrap` and `stacked_expect` print before
+/// panicking
+fn unwrap_report(e: &Error) -> String {
+    format!("stacked_unwrap failed:{e:?}")
+}
+
+/// The panic message that `stacked_unwrap` and `stacked_expect` use, this
+/// includes only the root cause
+fn unwrap_summary(e: &Error) -> String {
+    // the bottom frame that is not a `UnitError`
+    match e.iter().find(|e| e.downcast_ref::<UnitError>().is_none()) {
+        Some(root) => format!("stacked_unwrap failed with root cause: {}", root.get_err()),
+        None => String::from("stacked_unwrap failed on an error without a message"),
+    }
+}
+
+/// Used by `stacked_unwrap` and `stacked_expect`
+#[track_caller]
+pub fn unwrap_failed(e: Error) -> ! {
+    std::eprintln!("{}", unwrap_report(&e));
+    panic!("{}", unwrap_summary(&e))
+}
+
+#[test]
+fn unwrap_failed_report() {
+    let e = Error::from_err_locationless("root").add();
+    let report = unwrap_report(&e);
+    assert!(report.starts_with("stacked_unwrap failed:\n"));
+    assert!(report.contains("root"));
+    assert!(report.contains("src/panic.rs"));
+    assert_eq!(
+        unwrap_summary(&e),
+        "stacked_unwrap failed with root cause: root"
+    );
+    assert_eq!(
+        unwrap_summary(&Error::new()),
+        "stacked_unwrap failed on an error without a message"
+    );
+}
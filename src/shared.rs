@@ -0,0 +1,77 @@
+use alloc::sync::Arc;
+use core::{
+    fmt::{self, Debug, Display},
+    ops::Deref,
+};
+
+use crate::Error;
+
+/// A cheaply cloneable handle to an [Error], for broadcasting one failure to
+/// many consumers (e.g. through a watch channel or a cache). Unlike
+/// `Arc<Error>`, this is itself a valid frame, so every consumer can stack
+/// its own context on top.
+///
+/// ```
+/// use stacked_errors::{Error, Result, SharedError, StackableErr};
+///
+/// let shared = SharedError::new(Error::from_err("init failed"));
+/// let res: Result<()> = Err(Error::from_err(shared.clone())).stack_err("while waiting for init");
+/// let s = format!("{}", res.unwrap_err());
+/// assert!(s.contains("init failed"));
+/// assert!(s.contains("while waiting for init"));
+///
+/// // the original can be recovered once the other handles are gone
+/// let e = shared.unwrap_or_clone();
+/// assert_eq!(e.iter().len(), 1);
+/// ```
+#[derive(Clone)]
+pub struct SharedError(Arc<Error>);
+
+impl SharedError {
+    /// Puts `e` behind a shared handle
+    pub fn new(e: Error) -> Self {
+        Self(Arc::new(e))
+    }
+
+    /// Returns the inner `Error` if this is the only handle to it, otherwise
+    /// returns `self` unchanged
+    pub fn try_unwrap(self) -> Result<Error, Self> {
+        Arc::try_unwrap(self.0).map_err(Self)
+    }
+
+    /// Returns the inner `Error` if this is the only handle to it. `Error`
+    /// cannot be deep cloned, so otherwise this returns a new `Error` with the
+    /// shared handle as its only frame, which displays the same.
+    pub fn unwrap_or_clone(self) -> Error {
+        match self.try_unwrap() {
+            Ok(e) => e,
+            Err(this) => Error::from_err_locationless(this),
+        }
+    }
+}
+
+impl From<Error> for SharedError {
+    fn from(e: Error) -> Self {
+        Self::new(e)
+    }
+}
+
+impl Deref for SharedError {
+    type Target = Error;
+
+    fn deref(&self) -> &Error {
+        &self.0
+    }
+}
+
+impl Debug for SharedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&*self.0, f)
+    }
+}
+
+impl Display for SharedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&*self.0, f)
+    }
+}
@@ -1,3 +1,5 @@
+use alloc::string::String;
+
 /// Used internally when an error needs to be pushed but only the location is
 /// important
 #[derive(thiserror::Error, Debug)]
@@ -14,3 +16,153 @@ pub struct TimeoutError {}
 #[derive(thiserror::Error, Debug)]
 #[error("ProbablyNotRootCauseError")]
 pub struct ProbablyNotRootCauseError {}
+
+/// Pushed by [Error::try_push_err](crate::Error::try_push_err) in place of an
+/// error that could not be stored without allocating
+#[derive(thiserror::Error, Debug)]
+#[error("AllocFailedError")]
+pub struct AllocFailedError {}
+
+/// Pushed with debug assertions above a frame that nests a whole `Error` if
+/// there is no hook set with [set_nesting_hook](crate::set_nesting_hook)
+#[derive(thiserror::Error, Debug)]
+#[error(
+    "NestedErrorWarning: the next frame contains a whole `Error`, pass the `Error` itself or use \
+     `chain_errors` or `add_sub_error` to merge the stacks"
+)]
+pub struct NestedErrorWarning {}
+
+/// Used to signal that an error originated from a panic
+#[derive(thiserror::Error, Debug)]
+#[error("PanicError")]
+pub struct PanicError {}
+
+/// The location of a panic, pushed by
+/// [Error::from_panic_info](crate::Error::from_panic_info). The location of a
+/// `PanicHookInfo` is not `'static`, so it is kept as an owned frame instead
+/// of as the location of a frame.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("panicked at {} {line}:{column}", crate::shorten_location(file))]
+pub struct PanicLocation {
+    pub file: String,
+    pub line: u32,
+    pub column: u32,
+}
+
+/// A panic payload that is not a string, retained by
+/// [Error::from_panic_payload](crate::Error::from_panic_payload) so that it can
+/// be downcast later. The payload is only `Send`, so it is behind a `Mutex` to
+/// make the frame `Sync`.
+#[cfg(feature = "std")]
+#[derive(thiserror::Error, Debug)]
+#[error("non-string panic payload")]
+pub struct PanicPayload(pub std::sync::Mutex<alloc::boxed::Box<dyn core::any::Any + Send>>);
+
+#[cfg(feature = "std")]
+impl PanicPayload {
+    /// Returns a mutable reference to the payload, which can be downcast with
+    /// `downcast_mut`
+    pub fn get_mut(&mut self) -> &mut (dyn core::any::Any + Send) {
+        &mut **self.0.get_mut().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Returns the payload, e.g. for `std::panic::resume_unwind`
+    pub fn into_inner(self) -> alloc::boxed::Box<dyn core::any::Any + Send> {
+        self.0.into_inner().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// The module path and an optional label, pushed by
+/// [stack_here](crate::stack_here). The location is stored by the frame like
+/// usual.
+#[derive(Debug, Clone, Copy)]
+pub struct ModuleContext {
+    pub module: &'static str,
+    pub label: Option<&'static str>,
+}
+
+impl core::fmt::Display for ModuleContext {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.label {
+            Some(label) => write!(f, "{label} in {}", self.module),
+            None => write!(f, "in {}", self.module),
+        }
+    }
+}
+
+/// The type name of the value that an operation was producing, pushed by
+/// [StackableErr::stack_typed](crate::StackableErr::stack_typed)
+#[derive(thiserror::Error, Debug, Clone, Copy)]
+#[error("while producing {type_name}")]
+pub struct TypeContext {
+    pub type_name: &'static str,
+}
+
+impl TypeContext {
+    /// Returns the context for `T`, using [core::any::type_name]
+    pub fn of<T: ?Sized>() -> Self {
+        Self {
+            type_name: core::any::type_name::<T>(),
+        }
+    }
+}
+
+/// The name and target of a `tracing` span, pushed by
+/// [StackableErr::stack_span](crate::StackableErr::stack_span)
+#[cfg(feature = "tracing")]
+#[derive(thiserror::Error, Debug, Clone, Copy)]
+#[error("in span {name} ({target})")]
+pub struct SpanContext {
+    pub name: &'static str,
+    pub target: &'static str,
+}
+
+#[cfg(feature = "tracing")]
+impl SpanContext {
+    /// Returns the metadata of `tracing::Span::current()`, or `None` if the
+    /// current span is disabled
+    pub fn current() -> Option<Self> {
+        tracing::Span::current().metadata().map(|m| Self {
+            name: m.name(),
+            target: m.target(),
+        })
+    }
+}
+
+/// The head of the error returned by
+/// [try_alternatives](crate::try_alternatives) when every alternative failed,
+/// the failures of the alternatives are below it
+#[derive(thiserror::Error, Debug, Clone, Copy)]
+#[error("all {count} alternatives failed")]
+pub struct MultipleErrors {
+    /// The number of alternatives that were tried
+    pub count: usize,
+}
+
+/// Replaces frames that were dropped because of
+/// [set_max_frames](crate::set_max_frames). This is always directly above the
+/// root frame.
+#[derive(Debug, Clone, Copy)]
+pub struct TruncatedFrames {
+    /// The number of frames that were dropped
+    pub dropped: usize,
+}
+
+impl core::fmt::Display for TruncatedFrames {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        // with thousands separators
+        let digits = alloc::format!("{}", self.dropped);
+        f.write_str("... ")?;
+        for (i, c) in digits.chars().enumerate() {
+            if (i != 0) && ((digits.len() - i) % 3 == 0) {
+                f.write_str(",")?;
+            }
+            f.write_fmt(format_args!("{c}"))?;
+        }
+        if self.dropped == 1 {
+            f.write_str(" frame dropped ...")
+        } else {
+            f.write_str(" frames dropped ...")
+        }
+    }
+}
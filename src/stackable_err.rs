@@ -1,6 +1,7 @@
-use core::{fmt::Display, mem};
+use alloc::{borrow::Cow, string::String, vec::Vec};
+use core::{any::Any, fmt::Display, mem};
 
-use crate::{Error, StackableErrorTrait};
+use crate::{Error, ProbablyNotRootCauseError, TypeContext};
 
 /// Conversion to and addition to the stack of a
 /// [stackable_error::Error](crate::Error).
@@ -15,6 +16,32 @@ pub trait StackableErr {
     /// Only converts to `Self::Output` and pushes it on the error stack
     fn stack_locationless(self) -> Self::Output;
 
+    /// Alternate for [StackableErr::stack_locationless] that is a pure
+    /// conversion to `Self::Output`, no location information is recorded.
+    ///
+    /// ```
+    /// use stacked_errors::{Result, StackableErr};
+    ///
+    /// fn parse(s: &str) -> Result<u64> {
+    ///     s.parse::<u64>().into_stacked()
+    /// }
+    ///
+    /// let e = parse("invalid").unwrap_err();
+    /// assert_eq!(format!("{e}"), "\n    invalid digit found in string");
+    ///
+    /// // compared to `stack` which pushes the location
+    /// let e = "invalid".parse::<u64>().stack().unwrap_err();
+    /// assert_eq!(
+    ///     format!("{e}"),
+    ///     "\n    invalid digit found in string at src/stackable_err.rs 15:34"
+    /// );
+    ///
+    /// // `Error`s pass through unchanged
+    /// let e = parse("invalid").into_stacked().unwrap_err();
+    /// assert_eq!(e.iter().len(), 1);
+    /// ```
+    fn into_stacked(self) -> Self::Output;
+
     /// Pushes the result of `f` and location information to the error stack
     fn stack_err<E: Display + Send + Sync + 'static>(self, e: E) -> Self::Output;
 
@@ -33,6 +60,93 @@ pub trait StackableErr {
         f: F,
     ) -> Self::Output;
 
+    /// Pushes the name and target of the current `tracing` span (see
+    /// [SpanContext](crate::SpanContext)) and location information to the
+    /// error stack. Only location information is pushed if the current span
+    /// is disabled.
+    #[cfg(feature = "tracing")]
+    fn stack_span(self) -> Self::Output;
+
+    /// Pushes a [ProbablyNotRootCauseError](crate::ProbablyNotRootCauseError)
+    /// and location information to the error stack, for errors that are known
+    /// to be downstream effects of some other error so that
+    /// [Error::is_probably_not_root_cause](crate::Error::is_probably_not_root_cause)
+    /// is true for them.
+    ///
+    /// ```
+    /// use stacked_errors::{Error, Result, StackableErr};
+    ///
+    /// fn recv() -> Result<()> {
+    ///     // the sender failed elsewhere and the channel closed
+    ///     Err(Error::from_err_locationless("channel closed"))
+    /// }
+    ///
+    /// let e = recv().stack_secondary().unwrap_err();
+    /// assert!(e.is_probably_not_root_cause());
+    ///
+    /// let e = recv().stack_err_secondary("receiving results").unwrap_err();
+    /// assert!(e.is_probably_not_root_cause());
+    /// assert_eq!(
+    ///     format!("{e}"),
+    ///     "\n    receiving results at src/stackable_err.rs 15:16\n    \
+    ///      ProbablyNotRootCauseError\n    channel closed"
+    /// );
+    /// ```
+    fn stack_secondary(self) -> Self::Output;
+
+    /// Pushes a locationless
+    /// [ProbablyNotRootCauseError](crate::ProbablyNotRootCauseError) and then
+    /// `e` with location information to the error stack, see
+    /// [StackableErr::stack_secondary]
+    fn stack_err_secondary<E: Display + Send + Sync + 'static>(self, e: E) -> Self::Output;
+
+    /// Pushes a [TypeContext](crate::TypeContext) with the type name of the
+    /// `Ok` or `Some` value and location information to the error stack, for
+    /// generic code where the same helper handles many types. `Error` uses
+    /// `()`.
+    ///
+    /// ```
+    /// use stacked_errors::{Result, StackableErr};
+    ///
+    /// fn parse<T: core::str::FromStr>(s: &str) -> Result<T>
+    /// where
+    ///     T::Err: core::fmt::Display + Send + Sync + 'static,
+    /// {
+    ///     s.parse::<T>().stack_typed()
+    /// }
+    ///
+    /// let e = parse::<u8>("256").unwrap_err();
+    /// assert_eq!(
+    ///     format!("{}", e.display_no_locations()),
+    ///     "\n    while producing u8\n    number too large to fit in target type"
+    /// );
+    /// ```
+    fn stack_typed(self) -> Self::Output;
+
+    /// Like [StackableErr::stack_err] with the message as a `Cow`, for hot
+    /// loops where the message is usually static. `Cow::Borrowed` is stored
+    /// inline like a `&'static str` so it does not allocate, and only the
+    /// `Cow::Owned` case pays for formatting.
+    ///
+    /// ```
+    /// use std::borrow::Cow;
+    ///
+    /// use stacked_errors::{Error, Result, StackableErr};
+    ///
+    /// fn connect(attempt: u32) -> Result<()> {
+    ///     let res: Result<()> = Err(Error::from_err("connection refused"));
+    ///     res.stack_err_cow(if attempt < 3 {
+    ///         Cow::Borrowed("retrying")
+    ///     } else {
+    ///         Cow::Owned(format!("giving up after attempt {attempt}"))
+    ///     })
+    /// }
+    ///
+    /// assert!(format!("{}", connect(1).unwrap_err()).contains("retrying"));
+    /// assert!(format!("{}", connect(3).unwrap_err()).contains("attempt 3"));
+    /// ```
+    fn stack_err_cow(self, msg: Cow<'static, str>) -> Self::Output;
+
     /// Alternate for [StackableErr::stack_err] which can be used for easier
     /// translation to and from the `eyre` crate
     fn wrap_err<D: Display + Send + Sync + 'static>(self, msg: D) -> Self::Output;
@@ -106,43 +220,93 @@ pub trait StackableErr {
     }
 }*/
 
+/// With the "locations-only" feature, context messages are dropped in favor of
+/// `UnitError`s so that only the locations remain
+#[cfg(not(feature = "locations-only"))]
+#[inline(always)]
+fn message<E: Display + Send + Sync + 'static>(e: E) -> E {
+    e
+}
+
+#[cfg(feature = "locations-only")]
+#[inline(always)]
+fn message<E>(_e: E) -> crate::UnitError {
+    crate::UnitError {}
+}
+
+/// Like `message` but `f` is not called with "locations-only"
+#[cfg(not(feature = "locations-only"))]
+#[inline(always)]
+fn message_with<E: Display + Send + Sync + 'static, F: FnOnce() -> E>(f: F) -> E {
+    f()
+}
+
+#[cfg(feature = "locations-only")]
+#[inline(always)]
+fn message_with<E, F: FnOnce() -> E>(_f: F) -> crate::UnitError {
+    crate::UnitError {}
+}
+
+// The helpers below are only called on the error path, they are outlined so
+// that only the `Ok` check is inlined into callers. The `track_caller` location
+// is passed to them as an implicit argument.
+
+#[cold]
+#[inline(never)]
 #[track_caller]
-fn stack<E: Display + Send + Sync + 'static>(mut err: E) -> Error {
-    let tmp: &mut dyn StackableErrorTrait = &mut err;
-    if let Some(tmp) = tmp._as_any_mut().downcast_mut::<Error>() {
+pub fn stack<E: Display + Send + Sync + 'static>(mut err: E) -> Error {
+    if let Some(tmp) = (&mut err as &mut dyn Any).downcast_mut::<Error>() {
         tmp.push();
-        // TODO does the allocation here optimize away or can we do something about
-        // this?
-        mem::take(tmp)
+        // `Error::empty` does not allocate unlike the `Default` impl
+        mem::replace(tmp, Error::empty())
     } else {
         Error::from_err(err)
     }
 }
 
+#[cold]
+#[inline(never)]
 fn stack_locationless<E: Display + Send + Sync + 'static>(mut err: E) -> Error {
-    let tmp: &mut dyn StackableErrorTrait = &mut err;
-    if let Some(tmp) = tmp._as_any_mut().downcast_mut::<Error>() {
-        mem::take(tmp)
+    if let Some(tmp) = (&mut err as &mut dyn Any).downcast_mut::<Error>() {
+        mem::replace(tmp, Error::empty())
     } else {
         Error::from_err_locationless(err)
     }
 }
 
+#[cold]
+#[inline(never)]
 #[track_caller]
-fn stack_err<E: Display + Send + Sync + 'static, E1: Display + Send + Sync + 'static>(
+pub fn stack_err<E: Display + Send + Sync + 'static, E1: Display + Send + Sync + 'static>(
     mut err: E,
     e: E1,
 ) -> Error {
-    let tmp: &mut dyn StackableErrorTrait = &mut err;
-    if let Some(tmp) = tmp._as_any_mut().downcast_mut::<Error>() {
+    if let Some(tmp) = (&mut err as &mut dyn Any).downcast_mut::<Error>() {
         tmp.push_err(e);
-        mem::take(tmp)
+        mem::replace(tmp, Error::empty())
     } else {
         // the location should be attached to the later part
         Error::from_err_locationless(err).add_err(e)
     }
 }
 
+/// `f` is only called on the error path, so it is called in here
+#[cold]
+#[inline(never)]
+#[track_caller]
+fn stack_err_with<
+    E: Display + Send + Sync + 'static,
+    E1: Display + Send + Sync + 'static,
+    F: FnOnce() -> E1,
+>(
+    err: E,
+    f: F,
+) -> Error {
+    stack_err(err, f())
+}
+
+#[cold]
+#[inline(never)]
 #[track_caller]
 fn stack_err_locationless<
     E: Display + Send + Sync + 'static,
@@ -151,15 +315,33 @@ fn stack_err_locationless<
     mut err: E,
     e: E1,
 ) -> Error {
-    let tmp: &mut dyn StackableErrorTrait = &mut err;
-    if let Some(tmp) = tmp._as_any_mut().downcast_mut::<Error>() {
+    if let Some(tmp) = (&mut err as &mut dyn Any).downcast_mut::<Error>() {
         tmp.push_err_locationless(e);
-        mem::take(tmp)
+        mem::replace(tmp, Error::empty())
     } else {
         Error::from_err_locationless(err).add_err_locationless(e)
     }
 }
 
+#[cfg(feature = "tracing")]
+#[cold]
+#[inline(never)]
+#[track_caller]
+fn stack_span<E: Display + Send + Sync + 'static>(err: E) -> Error {
+    match crate::SpanContext::current() {
+        Some(span) => stack_err(err, span),
+        None => stack(err),
+    }
+}
+
+/// Like `stack_err_with` for when there is no original error
+#[cold]
+#[inline(never)]
+#[track_caller]
+fn from_err_with<E1: Display + Send + Sync + 'static, F: FnOnce() -> E1>(f: F) -> Error {
+    Error::from_err(f())
+}
+
 impl<T, E: Display + Send + Sync + 'static> StackableErr for core::result::Result<T, E> {
     type Output = core::result::Result<T, Error>;
 
@@ -178,11 +360,15 @@ impl<T, E: Display + Send + Sync + 'static> StackableErr for core::result::Resul
         }
     }
 
+    fn into_stacked(self) -> Self::Output {
+        self.stack_locationless()
+    }
+
     #[track_caller]
     fn stack_err<E1: Display + Send + Sync + 'static>(self, e: E1) -> Self::Output {
         match self {
             Ok(o) => Ok(o),
-            Err(err) => Err(stack_err(err, e)),
+            Err(err) => Err(stack_err(err, message(e))),
         }
     }
 
@@ -193,14 +379,14 @@ impl<T, E: Display + Send + Sync + 'static> StackableErr for core::result::Resul
     ) -> Self::Output {
         match self {
             Ok(o) => Ok(o),
-            Err(err) => Err(stack_err(err, f())),
+            Err(err) => Err(stack_err_with(err, || message_with(f))),
         }
     }
 
     fn stack_err_locationless<E1: Display + Send + Sync + 'static>(self, e: E1) -> Self::Output {
         match self {
             Ok(o) => Ok(o),
-            Err(err) => Err(stack_err_locationless(err, e)),
+            Err(err) => Err(stack_err_locationless(err, message(e))),
         }
     }
 
@@ -210,10 +396,44 @@ impl<T, E: Display + Send + Sync + 'static> StackableErr for core::result::Resul
     ) -> Self::Output {
         match self {
             Ok(o) => Ok(o),
-            Err(err) => Err(stack_err_locationless(err, f())),
+            Err(err) => Err(stack_err_locationless(err, message_with(f))),
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    #[track_caller]
+    fn stack_span(self) -> Self::Output {
+        match self {
+            Ok(o) => Ok(o),
+            Err(err) => Err(stack_span(err)),
         }
     }
 
+    #[track_caller]
+    fn stack_secondary(self) -> Self::Output {
+        self.stack_err(ProbablyNotRootCauseError {})
+    }
+
+    #[track_caller]
+    fn stack_err_secondary<E1: Display + Send + Sync + 'static>(self, e: E1) -> Self::Output {
+        match self {
+            Ok(o) => Ok(o),
+            Err(err) => {
+                Err(stack_err_locationless(err, ProbablyNotRootCauseError {}).add_err(message(e)))
+            }
+        }
+    }
+
+    #[track_caller]
+    fn stack_typed(self) -> Self::Output {
+        self.stack_err(TypeContext::of::<T>())
+    }
+
+    #[track_caller]
+    fn stack_err_cow(self, msg: Cow<'static, str>) -> Self::Output {
+        self.stack_err(msg)
+    }
+
     #[track_caller]
     fn wrap_err<D: Display + Send + Sync + 'static>(self, msg: D) -> Self::Output {
         self.stack_err(msg)
@@ -259,11 +479,15 @@ impl<T> StackableErr for Option<T> {
         }
     }
 
+    fn into_stacked(self) -> Self::Output {
+        self.stack_locationless()
+    }
+
     #[track_caller]
     fn stack_err<E1: Display + Send + Sync + 'static>(self, e: E1) -> Self::Output {
         match self {
             Some(o) => Ok(o),
-            None => Err(Error::from_err(e)),
+            None => Err(Error::from_err(message(e))),
         }
     }
 
@@ -274,14 +498,14 @@ impl<T> StackableErr for Option<T> {
     ) -> Self::Output {
         match self {
             Some(o) => Ok(o),
-            None => Err(Error::from_err(f())),
+            None => Err(from_err_with(|| message_with(f))),
         }
     }
 
     fn stack_err_locationless<E1: Display + Send + Sync + 'static>(self, e: E1) -> Self::Output {
         match self {
             Some(o) => Ok(o),
-            None => Err(Error::from_err_locationless(e)),
+            None => Err(Error::from_err_locationless(message(e))),
         }
     }
 
@@ -291,10 +515,47 @@ impl<T> StackableErr for Option<T> {
     ) -> Self::Output {
         match self {
             Some(o) => Ok(o),
-            None => Err(Error::from_err_locationless(f())),
+            None => Err(Error::from_err_locationless(message_with(f))),
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    #[track_caller]
+    fn stack_span(self) -> Self::Output {
+        match self {
+            Some(o) => Ok(o),
+            None => Err(match crate::SpanContext::current() {
+                Some(span) => Error::from_err(span),
+                None => Error::new(),
+            }),
         }
     }
 
+    #[track_caller]
+    fn stack_secondary(self) -> Self::Output {
+        self.stack_err(ProbablyNotRootCauseError {})
+    }
+
+    #[track_caller]
+    fn stack_err_secondary<E1: Display + Send + Sync + 'static>(self, e: E1) -> Self::Output {
+        match self {
+            Some(o) => Ok(o),
+            None => {
+                Err(Error::from_err_locationless(ProbablyNotRootCauseError {}).add_err(message(e)))
+            }
+        }
+    }
+
+    #[track_caller]
+    fn stack_typed(self) -> Self::Output {
+        self.stack_err(TypeContext::of::<T>())
+    }
+
+    #[track_caller]
+    fn stack_err_cow(self, msg: Cow<'static, str>) -> Self::Output {
+        self.stack_err(msg)
+    }
+
     #[track_caller]
     fn wrap_err<D: Display + Send + Sync + 'static>(self, msg: D) -> Self::Output {
         self.stack_err(msg)
@@ -334,9 +595,13 @@ impl StackableErr for Error {
         Err(self)
     }
 
+    fn into_stacked(self) -> Self::Output {
+        self.stack_locationless()
+    }
+
     #[track_caller]
     fn stack_err<E1: Display + Send + Sync + 'static>(self, e: E1) -> Self::Output {
-        Err(self.add_err(e))
+        Err(self.add_err(message(e)))
     }
 
     #[track_caller]
@@ -344,18 +609,49 @@ impl StackableErr for Error {
         self,
         f: F,
     ) -> Self::Output {
-        Err(self.add_err(f()))
+        Err(self.add_err(message_with(f)))
     }
 
     fn stack_err_locationless<E1: Display + Send + Sync + 'static>(self, e: E1) -> Self::Output {
-        Err(self.add_err_locationless(e))
+        Err(self.add_err_locationless(message(e)))
     }
 
     fn stack_err_with_locationless<E1: Display + Send + Sync + 'static, F: FnOnce() -> E1>(
         self,
         f: F,
     ) -> Self::Output {
-        Err(self.add_err_locationless(f()))
+        Err(self.add_err_locationless(message_with(f)))
+    }
+
+    #[cfg(feature = "tracing")]
+    #[track_caller]
+    fn stack_span(self) -> Self::Output {
+        Err(match crate::SpanContext::current() {
+            Some(span) => self.add_err(span),
+            None => self.add(),
+        })
+    }
+
+    #[track_caller]
+    fn stack_secondary(self) -> Self::Output {
+        self.stack_err(ProbablyNotRootCauseError {})
+    }
+
+    #[track_caller]
+    fn stack_err_secondary<E1: Display + Send + Sync + 'static>(self, e: E1) -> Self::Output {
+        Err(self
+            .add_err_locationless(ProbablyNotRootCauseError {})
+            .add_err(message(e)))
+    }
+
+    #[track_caller]
+    fn stack_typed(self) -> Self::Output {
+        self.stack_err(TypeContext::of::<()>())
+    }
+
+    #[track_caller]
+    fn stack_err_cow(self, msg: Cow<'static, str>) -> Self::Output {
+        self.stack_err(msg)
     }
 
     #[track_caller]
@@ -384,3 +680,279 @@ impl StackableErr for Error {
         self.stack_err_with(msg)
     }
 }
+
+/// Transformation of the original error of a `Result` before it enters the
+/// stack. Unlike [StackableErr::stack_err] which adds a frame on top, this is
+/// for normalizing third party errors into your own (e.g. stripping verbose
+/// fields) so that they become the root frame.
+pub trait StackMapErr<T, E> {
+    /// Transforms the error with `f` and then stacks the result with location
+    /// information as in [StackableErr::stack]. If `f` returns an `Error`, the
+    /// location is pushed onto it.
+    ///
+    /// ```
+    /// use stacked_errors::{StackMapErr, StackedErrorDowncast};
+    ///
+    /// let e = "257"
+    ///     .parse::<u8>()
+    ///     .stack_map_err(|e| format!("bad byte ({e})"))
+    ///     .unwrap_err();
+    /// assert_eq!(e.iter().len(), 1);
+    /// assert_eq!(
+    ///     format!("{e}"),
+    ///     "\n    bad byte (number too large to fit in target type) at src/stackable_err.rs 9:6"
+    /// );
+    /// ```
+    fn stack_map_err<E2: Display + Send + Sync + 'static, F: FnOnce(E) -> E2>(
+        self,
+        f: F,
+    ) -> core::result::Result<T, Error>;
+}
+
+impl<T, E> StackMapErr<T, E> for core::result::Result<T, E> {
+    #[track_caller]
+    fn stack_map_err<E2: Display + Send + Sync + 'static, F: FnOnce(E) -> E2>(
+        self,
+        f: F,
+    ) -> core::result::Result<T, Error> {
+        match self {
+            Ok(o) => Ok(o),
+            Err(err) => Err(stack(f(err))),
+        }
+    }
+}
+
+/// Conversion of an `Option` into a `Result` with a stacked error, named after
+/// [Option::ok_or] and [Option::ok_or_else]. These are the same as
+/// [StackableErr::stack_err] and [StackableErr::stack_err_with] on an
+/// `Option`.
+pub trait OkOrStack<T> {
+    /// Returns `Ok` with the value if there is one, otherwise returns an
+    /// error with `e` and location information.
+    ///
+    /// ```
+    /// use stacked_errors::OkOrStack;
+    ///
+    /// assert_eq!(Some(1).ok_or_stack("missing value").unwrap(), 1);
+    ///
+    /// let e = None::<u64>.ok_or_stack("missing value").unwrap_err();
+    /// assert_eq!(
+    ///     format!("{e}"),
+    ///     "\n    missing value at src/stackable_err.rs 9:21"
+    /// );
+    /// ```
+    fn ok_or_stack<E: Display + Send + Sync + 'static>(
+        self,
+        e: E,
+    ) -> core::result::Result<T, Error>;
+
+    /// Like [OkOrStack::ok_or_stack], except that the error is only created by
+    /// calling `f` if there is no value.
+    ///
+    /// ```
+    /// use stacked_errors::OkOrStack;
+    ///
+    /// let key = "port";
+    /// assert_eq!(
+    ///     Some(8080)
+    ///         .ok_or_stack_with(|| format!("missing {key}"))
+    ///         .unwrap(),
+    ///     8080
+    /// );
+    ///
+    /// let e = None::<u64>
+    ///     .ok_or_stack_with(|| format!("missing {key}"))
+    ///     .unwrap_err();
+    /// assert_eq!(
+    ///     format!("{e}"),
+    ///     "\n    missing port at src/stackable_err.rs 16:6"
+    /// );
+    /// ```
+    fn ok_or_stack_with<E: Display + Send + Sync + 'static, F: FnOnce() -> E>(
+        self,
+        f: F,
+    ) -> core::result::Result<T, Error>;
+}
+
+impl<T> OkOrStack<T> for Option<T> {
+    #[track_caller]
+    fn ok_or_stack<E: Display + Send + Sync + 'static>(
+        self,
+        e: E,
+    ) -> core::result::Result<T, Error> {
+        self.stack_err(e)
+    }
+
+    #[track_caller]
+    fn ok_or_stack_with<E: Display + Send + Sync + 'static, F: FnOnce() -> E>(
+        self,
+        f: F,
+    ) -> core::result::Result<T, Error> {
+        self.stack_err_with(f)
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl<T> Sealed for core::result::Result<T, crate::Error> {}
+}
+
+/// Stacking for a `Result<T, Error>` that pushes onto the existing `Error`
+/// directly. The [StackableErr] methods on `Result<T, E>` have to discover
+/// at runtime whether `E` is already an `Error` through a `dyn` downcast,
+/// because specialization is not available. These are implemented only for
+/// `Result<T, Error>` and skip that, while producing the same frames.
+///
+/// ```
+/// use stacked_errors::{Error, Result, StackableErr, StackableErrSame};
+///
+/// fn inner() -> Result<()> {
+///     Err(Error::from_err_locationless("root"))
+/// }
+///
+/// let e = inner().stack_same_err("outer").unwrap_err();
+/// assert_eq!(
+///     format!("{e}"),
+///     "\n    outer at src/stackable_err.rs 11:17\n    root"
+/// );
+/// ```
+pub trait StackableErrSame<T>: sealed::Sealed {
+    /// Same as [StackableErr::stack]
+    fn stack_same(self) -> core::result::Result<T, Error>;
+
+    /// Same as [StackableErr::stack_err]
+    fn stack_same_err<E: Display + Send + Sync + 'static>(
+        self,
+        e: E,
+    ) -> core::result::Result<T, Error>;
+
+    /// Same as [StackableErr::stack_err_with]
+    fn stack_same_err_with<E: Display + Send + Sync + 'static, F: FnOnce() -> E>(
+        self,
+        f: F,
+    ) -> core::result::Result<T, Error>;
+}
+
+#[cold]
+#[inline(never)]
+#[track_caller]
+fn add_err_with<E: Display + Send + Sync + 'static, F: FnOnce() -> E>(err: Error, f: F) -> Error {
+    err.add_err(f())
+}
+
+impl<T> StackableErrSame<T> for core::result::Result<T, Error> {
+    #[track_caller]
+    fn stack_same(self) -> core::result::Result<T, Error> {
+        match self {
+            Ok(o) => Ok(o),
+            Err(err) => Err(add_err_with(err, || crate::UnitError {})),
+        }
+    }
+
+    #[track_caller]
+    fn stack_same_err<E: Display + Send + Sync + 'static>(
+        self,
+        e: E,
+    ) -> core::result::Result<T, Error> {
+        match self {
+            Ok(o) => Ok(o),
+            Err(err) => Err(add_err_with(err, || message(e))),
+        }
+    }
+
+    #[track_caller]
+    fn stack_same_err_with<E: Display + Send + Sync + 'static, F: FnOnce() -> E>(
+        self,
+        f: F,
+    ) -> core::result::Result<T, Error> {
+        match self {
+            Ok(o) => Ok(o),
+            Err(err) => Err(add_err_with(err, || message_with(f))),
+        }
+    }
+}
+
+/// Conversion of a bare message into an [Error] with location information,
+/// for when there is no `Result` or `Option` to call [StackableErr] methods
+/// on.
+///
+/// ```
+/// use stacked_errors::{IntoError, Result};
+///
+/// fn check(x: u64) -> Result<()> {
+///     if x > 9 {
+///         return Err(format!("{x} is too large").into_error())
+///     }
+///     Ok(())
+/// }
+///
+/// let e = check(10).unwrap_err();
+/// assert_eq!(
+///     format!("{e}"),
+///     "\n    10 is too large at src/stackable_err.rs 9:48"
+/// );
+/// assert_eq!(e.iter().len(), "msg".into_error().iter().len());
+/// ```
+pub trait IntoError {
+    /// Converts `self` into an `Error` with the `track_caller` location
+    fn into_error(self) -> Error;
+}
+
+impl IntoError for &'static str {
+    #[track_caller]
+    fn into_error(self) -> Error {
+        Error::from_err(self)
+    }
+}
+
+impl IntoError for String {
+    #[track_caller]
+    fn into_error(self) -> Error {
+        Error::from_err(self)
+    }
+}
+
+/// The `Cow` is stored as is, like [StackableErr::stack_err_cow], so that the
+/// `Cow::Borrowed` case does not allocate a `String`
+impl IntoError for Cow<'static, str> {
+    #[track_caller]
+    fn into_error(self) -> Error {
+        Error::from_err(self)
+    }
+}
+
+/// Something that errors can be collected into, used by the `=> acc` form of
+/// [stack_try!](crate::stack_try).
+///
+/// ```
+/// use stacked_errors::{stack_try, Error};
+///
+/// // keeps every error as a chain in a single `Error`
+/// let mut errs: Option<Error> = None;
+/// for s in ["invalid", "1", "also invalid"] {
+///     stack_try!(s.parse::<u64>() => errs, continue);
+/// }
+/// assert_eq!(errs.unwrap().iter().len(), 2);
+/// ```
+pub trait ErrorAccumulator {
+    /// Adds `err` to the accumulator
+    fn accumulate(&mut self, err: Error);
+}
+
+impl ErrorAccumulator for Vec<Error> {
+    fn accumulate(&mut self, err: Error) {
+        self.push(err);
+    }
+}
+
+/// The first error becomes the `Some`, later errors are chained on top of it
+/// with [Error::chain_errors]
+impl ErrorAccumulator for Option<Error> {
+    fn accumulate(&mut self, err: Error) {
+        *self = Some(match self.take() {
+            Some(e) => e.chain_errors(err),
+            None => err,
+        });
+    }
+}
@@ -0,0 +1,415 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+use stacked_errors::{
+    Error, ProbablyNotRootCauseError, StackedErrorDowncast, TimeoutError, UnitError,
+};
+
+// counts allocations per thread so that the tests can run in parallel
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    // makes allocations on this thread fail
+    static FAIL_ALLOCATIONS: Cell<bool> = const { Cell::new(false) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|a| a.set(a.get() + 1));
+        if FAIL_ALLOCATIONS.with(|f| f.get()) {
+            return std::ptr::null_mut()
+        }
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.with(|a| a.set(a.get() + 1));
+        if FAIL_ALLOCATIONS.with(|f| f.get()) {
+            return std::ptr::null_mut()
+        }
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// Returns the result of `f` and the number of allocations it performed
+fn count_allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.with(|a| a.get());
+    let res = f();
+    let after = ALLOCATIONS.with(|a| a.get());
+    (res, after - before)
+}
+
+#[test]
+fn markers_are_not_boxed() {
+    // the only allocation is the `ThinVec` itself, the zero sized markers are
+    // stored inline
+    let (e, n) = count_allocations(Error::new);
+    assert_eq!(n, 1);
+    e.iter()
+        .next()
+        .unwrap()
+        .downcast_ref::<UnitError>()
+        .unwrap();
+
+    let (e, n) = count_allocations(Error::timeout);
+    assert_eq!(n, 1);
+    e.iter()
+        .next()
+        .unwrap()
+        .downcast_ref::<TimeoutError>()
+        .unwrap();
+    assert!(e.is_timeout());
+
+    let (e, n) = count_allocations(Error::probably_not_root_cause);
+    assert_eq!(n, 1);
+    e.iter()
+        .next()
+        .unwrap()
+        .downcast_ref::<ProbablyNotRootCauseError>()
+        .unwrap();
+    assert!(e.is_probably_not_root_cause());
+}
+
+#[test]
+fn static_str_is_not_boxed() {
+    let (e, n) = count_allocations(|| Error::static_str("static message"));
+    assert_eq!(n, 1);
+    let item = e.iter().next().unwrap();
+    assert_eq!(*item.downcast_ref::<&str>().unwrap(), "static message");
+    assert!(item.get_location().is_some());
+    assert_eq!(format!("{}", item.get_err()), "static message");
+}
+
+#[test]
+fn stack_on_error_does_not_allocate() {
+    use stacked_errors::StackableErr;
+
+    // the same errors, one is pushed to directly and one through `stack`
+    let build = || Error::from_err_locationless("root").add().add();
+    let (mut e0, e1) = (build(), build());
+    let (_, direct) = count_allocations(|| e0.push());
+    let (res, n) = count_allocations(|| Err::<(), Error>(e1).stack());
+    assert_eq!(n, direct);
+    let e1 = res.unwrap_err();
+    assert_eq!(e1.iter().len(), 4);
+    let l = e1.iter().last().unwrap().get_location().unwrap();
+    assert_eq!(l.file(), "tests/alloc.rs");
+    assert_eq!(l.line(), 101);
+
+    let (mut e0, e1) = (build(), build());
+    let (_, direct) = count_allocations(|| e0.push_err("static"));
+    let (res, n) = count_allocations(|| Err::<(), Error>(e1).stack_err("static"));
+    assert_eq!(n, direct);
+    let e1 = res.unwrap_err();
+    let item = e1.iter().last().unwrap();
+    assert_eq!(format!("{}", item.get_err()), "static");
+    assert_eq!(item.get_location().unwrap().line(), 111);
+
+    // pure conversions perform no allocations at all
+    let e = build();
+    let (res, n) = count_allocations(|| Err::<(), Error>(e).into_stacked());
+    assert_eq!(res.unwrap_err().iter().len(), 3);
+    assert_eq!(n, 0);
+}
+
+#[test]
+fn static_str_frames_are_not_boxed() {
+    use stacked_errors::{bail, ensure, format_err, Result, StackableErr};
+
+    // the only allocation is the `ThinVec`
+    fn bail_literal() -> Result<()> {
+        bail!("literal")
+    }
+    let (res, n) = count_allocations(bail_literal);
+    assert_eq!(n, 1);
+    let e = res.unwrap_err();
+    assert_eq!(
+        *e.iter().next().unwrap().downcast_ref::<&str>().unwrap(),
+        "literal"
+    );
+
+    fn ensure_literal(x: bool) -> Result<()> {
+        ensure!(x);
+        Ok(())
+    }
+    let (res, n) = count_allocations(|| ensure_literal(false));
+    assert_eq!(n, 1);
+    assert_eq!(
+        *res.unwrap_err()
+            .iter()
+            .next()
+            .unwrap()
+            .downcast_ref::<&str>()
+            .unwrap(),
+        "ensure(x) -> assertion failed"
+    );
+
+    let (e, n) = count_allocations(|| format_err!("literal"));
+    assert_eq!(n, 1);
+    assert!(e.iter().next().unwrap().downcast_ref::<&str>().is_some());
+
+    // pushing a `&'static str` onto an error with spare capacity allocates nothing
+    let e = Error::from_err_locationless("root").add().add();
+    let (res, n) = count_allocations(|| Err::<(), Error>(e).stack_err("more context"));
+    assert_eq!(n, 0);
+    let e = res.unwrap_err();
+    let item = e.iter().last().unwrap();
+    assert_eq!(*item.downcast_ref::<&str>().unwrap(), "more context");
+    assert_eq!(format!("{}", item.get_err()), "more context");
+}
+
+#[test]
+fn stacking_markers_in_a_loop_does_not_box() {
+    use stacked_errors::{Result, StackableErr};
+
+    // the allocations of pushing `&'static str`s, which are stored inline,
+    // are only from the growth of the `ThinVec`
+    let mut e = Error::from_err_locationless("root");
+    let (_, direct) = count_allocations(|| {
+        for _ in 0..100 {
+            e.push_err("static");
+        }
+    });
+    assert!(direct < 10);
+
+    let mut res: Result<()> = Err(Error::from_err_locationless("root"));
+    let (res, n) = count_allocations(|| {
+        for _ in 0..100 {
+            res = res.stack();
+        }
+        res
+    });
+    assert_eq!(n, direct);
+    let e = res.unwrap_err();
+    assert_eq!(e.iter().len(), 101);
+    for item in e.iter().skip(1) {
+        item.downcast_ref::<UnitError>().unwrap();
+        assert_eq!(item.get_location().unwrap().file(), "tests/alloc.rs");
+    }
+    // the formatter still detects the unit frames and puts their locations on
+    // their own lines
+    let s = format!("{e}");
+    assert_eq!(s.matches("at tests/alloc.rs").count(), 100);
+    assert!(!s.contains("UnitError"));
+
+    let mut res: Result<()> = Err(Error::from_err_locationless("root"));
+    let (res, n) = count_allocations(|| {
+        for _ in 0..50 {
+            res = res.stack_secondary().stack_err(TimeoutError {});
+        }
+        res
+    });
+    assert_eq!(n, direct);
+    let e = res.unwrap_err();
+    assert!(e.is_timeout());
+    assert!(e.is_probably_not_root_cause());
+    assert_eq!(e.iter().len(), 101);
+}
+
+#[test]
+fn small_payloads_are_not_boxed() {
+    use core::fmt;
+
+    struct Words<const N: usize>([usize; N]);
+    impl<const N: usize> fmt::Display for Words<N> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{:?}", self.0)
+        }
+    }
+
+    // payloads up to 4 words only need the `ThinVec`
+    let s = String::from("owned message");
+    let (e, n) = count_allocations(|| Error::from_err(s));
+    assert_eq!(n, 1);
+    assert_eq!(
+        e.iter().next().unwrap().downcast_ref::<String>().unwrap(),
+        "owned message"
+    );
+    let (e, n) = count_allocations(|| Error::from_err(Words([1; 4])));
+    assert_eq!(n, 1);
+    assert_eq!(
+        format!("{}", e.iter().next().unwrap().get_err()),
+        "[1, 1, 1, 1]"
+    );
+
+    // larger payloads need a separate allocation
+    let (e, n) = count_allocations(|| Error::from_err(Words([1; 5])));
+    assert_eq!(n, 2);
+    assert_eq!(
+        e.iter()
+            .next()
+            .unwrap()
+            .downcast_ref::<Words<5>>()
+            .unwrap()
+            .0,
+        [1; 5]
+    );
+}
+
+#[test]
+fn single_frame_errors_need_one_allocation() {
+    use std::mem;
+
+    use stacked_errors::{bail, Result, StackableErr};
+
+    // `Error` is a single pointer to the `ThinVec` header, which the one
+    // frame and its inline payload share an allocation with
+    assert_eq!(mem::size_of::<Error>(), mem::size_of::<usize>());
+    assert_eq!(mem::size_of::<Result<()>>(), mem::size_of::<usize>());
+
+    fn inner(x: u64) -> Result<u64> {
+        if x == 0 {
+            bail!(std::io::Error::from(std::io::ErrorKind::NotFound))
+        }
+        Ok(x)
+    }
+    fn outer() -> Result<u64> {
+        let x = inner(0)?;
+        Ok(x)
+    }
+    let (res, n) = count_allocations(outer);
+    assert_eq!(n, 1);
+    let e = res.unwrap_err();
+    assert_eq!(e.iter().len(), 1);
+    assert!(e
+        .iter()
+        .next()
+        .unwrap()
+        .downcast_ref::<std::io::Error>()
+        .is_some());
+
+    // only the first extra frame needs to grow the `ThinVec`
+    let (res, n) = count_allocations(|| Err::<(), Error>(e).stack());
+    assert_eq!(n, 1);
+    assert_eq!(res.unwrap_err().iter().len(), 2);
+}
+
+#[test]
+fn try_push_with_failing_allocator() {
+    use core::fmt;
+
+    use stacked_errors::AllocFailedError;
+
+    struct Large([u64; 8]);
+    impl fmt::Display for Large {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{:?}", self.0)
+        }
+    }
+
+    // reserve while allocation works
+    let mut e = Error::with_capacity(4);
+    e.push_err("root");
+
+    // every allocation fails from here, anything that allocates would abort
+    FAIL_ALLOCATIONS.with(|f| f.set(true));
+    let (res, n) = count_allocations(|| {
+        let a = e.try_push();
+        let b = e.try_push_err(Large([7; 8]));
+        let c = e.try_push_err(String::new());
+        let d = e.try_push_err("no more capacity");
+        (a, b, c, d)
+    });
+    FAIL_ALLOCATIONS.with(|f| f.set(false));
+    assert_eq!(n, 0);
+    assert_eq!(res, (true, false, true, false));
+
+    assert_eq!(e.iter().len(), 4);
+    let mut iter = e.iter();
+    assert_eq!(
+        *iter.next().unwrap().downcast_ref::<&str>().unwrap(),
+        "root"
+    );
+    iter.next().unwrap().downcast_ref::<UnitError>().unwrap();
+    let failed = iter.next().unwrap();
+    failed.downcast_ref::<AllocFailedError>().unwrap();
+    assert_eq!(failed.get_location().unwrap().file(), "tests/alloc.rs");
+    iter.next().unwrap().downcast_ref::<String>().unwrap();
+    assert!(format!("{e}").contains("AllocFailedError at tests/alloc.rs"));
+
+    // there is space again after reserving
+    e.reserve(1);
+    assert!(e.try_push_err("more"));
+}
+
+#[test]
+fn cow_context() {
+    use std::borrow::Cow;
+
+    use stacked_errors::{Result, StackableErr};
+
+    // one allocation for the stack, borrowed context is stored inline
+    let (res, n) = count_allocations(|| {
+        let res: Result<()> = Err(Error::with_capacity(2).add_err("root"));
+        res.stack_err_cow(Cow::Borrowed("retrying"))
+    });
+    assert_eq!(n, 1);
+    let e = res.unwrap_err();
+    let msg = e
+        .iter()
+        .last()
+        .unwrap()
+        .downcast_ref::<Cow<'static, str>>()
+        .unwrap();
+    assert!(matches!(msg, Cow::Borrowed("retrying")));
+
+    // owned context only adds its own buffer
+    let (res, n) = count_allocations(|| {
+        let res: Result<()> = Err(Error::with_capacity(2).add_err("root"));
+        res.stack_err_cow(Cow::Owned(String::from("attempt 3")))
+    });
+    assert_eq!(n, 2);
+    let e = res.unwrap_err();
+    let msg = e
+        .iter()
+        .last()
+        .unwrap()
+        .downcast_ref::<Cow<'static, str>>()
+        .unwrap();
+    assert!(matches!(msg, Cow::Owned(s) if s == "attempt 3"));
+
+    // the same for the other receivers
+    let e = None::<()>.stack_err_cow(Cow::Borrowed("none")).unwrap_err();
+    assert!(e.contains::<Cow<'static, str>>());
+    let res: std::result::Result<(), &str> = Err("root");
+    let e = res.stack_err_cow(Cow::Borrowed("ctx")).unwrap_err();
+    assert_eq!(
+        format!("{}", e.display_no_locations()),
+        "\n    ctx\n    root"
+    );
+}
+
+#[test]
+fn dedup_does_not_allocate() {
+    let mut e = Error::with_capacity(2);
+    let retry = |e: &mut Error| e.push_err_dedup("retrying");
+    retry(&mut e);
+    let (_, n) = count_allocations(|| {
+        for _ in 0..10 {
+            retry(&mut e);
+        }
+    });
+    assert_eq!(n, 0);
+    assert_eq!(e.iter().len(), 1);
+    assert_eq!(e.iter().last().unwrap().occurrences(), 11);
+
+    let (_, n) = count_allocations(|| {
+        for _ in 0..10 {
+            e.push_dedup();
+        }
+    });
+    // the first `push_dedup` pushes a frame into the reserved capacity
+    assert_eq!(n, 0);
+    assert_eq!(e.iter().len(), 2);
+    assert_eq!(e.iter().last().unwrap().occurrences(), 10);
+}
@@ -40,3 +40,43 @@ fn ex(s: &str, error: bool) -> Result<String> {
         Ok(s.to_owned())
     }
 }
+
+#[test]
+fn display_limited() {
+    let e = Error::from_err_locationless("0")
+        .add_err_locationless("1")
+        .add_err_locationless("2")
+        .add_err_locationless("3")
+        .add_err_locationless("4");
+    assert_eq!(
+        format!("{}", e.display_limited(2)),
+        r#"
+    4
+    3
+    … (3 more frames)"#
+    );
+    assert_eq!(format!("{}", e.display_limited(5)), format!("{e}"));
+    assert_eq!(format!("{}", e.display_limited(6)), format!("{e}"));
+    assert_eq!(e.iter().len(), 5);
+}
+
+#[test]
+fn root_cause_style() {
+    let e = Error::from_err_locationless("root")
+        .add()
+        .add_err_locationless("context0")
+        .add_err_locationless("context1");
+    let debug = format!("{e:?}");
+    // the escape codes directly before each message
+    let style_of = |msg: &str| {
+        let end = debug.find(msg).unwrap();
+        let start = debug[..end].rfind("    ").unwrap() + 4;
+        debug[start..end].to_owned()
+    };
+    assert!(!style_of("root").is_empty());
+    assert_ne!(style_of("root"), style_of("context0"));
+    assert_eq!(style_of("context0"), style_of("context1"));
+    // bold
+    assert!(style_of("root").ends_with(";1m"));
+    assert!(!style_of("context0").ends_with(";1m"));
+}
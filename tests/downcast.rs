@@ -0,0 +1,154 @@
+//! Downcasting across the categories of payloads, the inline and heap storage
+//! of `SmallBox` and the unsafe in `Error::try_into_single` are covered here
+//! so that `cargo miri test --test downcast` can check them
+
+use std::{
+    fmt::{self, Display},
+    num::ParseIntError,
+    sync::Arc,
+};
+
+use stacked_errors::{Error, StackedErrorDowncast, TimeoutError, UnitError};
+
+/// Counts drops through the strong count of the `Arc`
+#[derive(Debug)]
+struct Tracked<const N: usize> {
+    payload: [u64; N],
+    _rc: Arc<()>,
+}
+
+impl<const N: usize> Tracked<N> {
+    fn new(rc: &Arc<()>) -> Self {
+        Self {
+            payload: [7; N],
+            _rc: Arc::clone(rc),
+        }
+    }
+}
+
+impl<const N: usize> Display for Tracked<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "tracked {}", self.payload.iter().sum::<u64>())
+    }
+}
+
+fn parse_int_error() -> ParseIntError {
+    "x".parse::<u8>().unwrap_err()
+}
+
+#[test]
+fn downcast_ref() {
+    let e = Error::from_err(UnitError {})
+        .add_err(TimeoutError {})
+        .add_err("str")
+        .add_err(String::from("string"))
+        .add_err(42u64)
+        .add_err(parse_int_error())
+        .add_err(std::io::Error::other("io"));
+    let items: Vec<_> = e.iter().collect();
+    assert!(items[0].downcast_ref::<UnitError>().is_some());
+    assert!(items[1].downcast_ref::<TimeoutError>().is_some());
+    assert_eq!(*items[2].downcast_ref::<&str>().unwrap(), "str");
+    assert_eq!(items[3].downcast_ref::<String>().unwrap(), "string");
+    assert_eq!(*items[4].downcast_ref::<u64>().unwrap(), 42);
+    assert_eq!(
+        items[5].downcast_ref::<ParseIntError>(),
+        Some(&parse_int_error())
+    );
+    assert_eq!(
+        items[6]
+            .downcast_ref::<std::io::Error>()
+            .unwrap()
+            .to_string(),
+        "io"
+    );
+    // wrong types
+    assert!(items[0].downcast_ref::<TimeoutError>().is_none());
+    assert!(items[2].downcast_ref::<String>().is_none());
+    assert!(items[3].downcast_ref::<&str>().is_none());
+    assert!(items[4].downcast_ref::<u32>().is_none());
+    assert!(items[6].downcast_ref::<ParseIntError>().is_none());
+    // formatting goes through the same trait object
+    let messages: Vec<String> = e.messages().map(|(e, _)| e.to_string()).collect();
+    assert_eq!(messages[0], "TimeoutError");
+    assert_eq!(messages[2], "string");
+    assert_eq!(messages[5], "io");
+}
+
+#[test]
+fn downcast_mut() {
+    let rc = Arc::new(());
+    let mut e = Error::from_err(String::from("root"))
+        .add_err(1u8)
+        .add_err(Tracked::<1>::new(&rc))
+        .add_err(Tracked::<16>::new(&rc));
+    let mut items = e.iter_mut();
+    items
+        .next()
+        .unwrap()
+        .downcast_mut::<String>()
+        .unwrap()
+        .push_str(" cause");
+    *items.next().unwrap().downcast_mut::<u8>().unwrap() += 1;
+    // inline
+    items
+        .next()
+        .unwrap()
+        .downcast_mut::<Tracked<1>>()
+        .unwrap()
+        .payload[0] = 1;
+    // on the heap
+    let item = items.next().unwrap();
+    assert!(item.downcast_mut::<Tracked<1>>().is_none());
+    item.downcast_mut::<Tracked<16>>().unwrap().payload = [1; 16];
+    assert_eq!(
+        format!("{}", e.display_no_locations()),
+        "\n    tracked 16\n    tracked 1\n    2\n    root cause"
+    );
+    assert_eq!(Arc::strong_count(&rc), 3);
+    drop(e);
+    assert_eq!(Arc::strong_count(&rc), 1);
+}
+
+#[test]
+fn try_into_single() {
+    let e = Error::from_err(UnitError {});
+    assert!(e.try_into_single::<UnitError>().is_ok());
+    let e = Error::from_err("str");
+    assert_eq!(e.try_into_single::<&str>().unwrap(), "str");
+    let e = Error::from_err(String::from("string"));
+    assert_eq!(e.try_into_single::<String>().unwrap(), "string");
+    let e = Error::from_err(parse_int_error());
+    assert_eq!(
+        e.try_into_single::<ParseIntError>().unwrap(),
+        parse_int_error()
+    );
+    let e = Error::from_err(std::io::Error::other("io"));
+    assert_eq!(
+        e.try_into_single::<std::io::Error>().unwrap().to_string(),
+        "io"
+    );
+
+    // the payload is moved out exactly once, inline and on the heap
+    let rc = Arc::new(());
+    let e = Error::from_err(Tracked::<2>::new(&rc));
+    let e = e.try_into_single::<String>().unwrap_err();
+    let tracked = e.try_into_single::<Tracked<2>>().unwrap();
+    assert_eq!(tracked.payload, [7; 2]);
+    assert_eq!(Arc::strong_count(&rc), 2);
+    drop(tracked);
+    assert_eq!(Arc::strong_count(&rc), 1);
+    let e = Error::from_err(Tracked::<16>::new(&rc));
+    let tracked = e.try_into_single::<Tracked<16>>().unwrap();
+    assert_eq!(tracked.payload, [7; 16]);
+    assert_eq!(Arc::strong_count(&rc), 2);
+    drop(tracked);
+    assert_eq!(Arc::strong_count(&rc), 1);
+
+    // not consumed when there are more frames
+    let e = Error::from_err(Tracked::<16>::new(&rc)).add();
+    let e = e.try_into_single::<Tracked<16>>().unwrap_err();
+    assert_eq!(Arc::strong_count(&rc), 2);
+    drop(e);
+    assert_eq!(Arc::strong_count(&rc), 1);
+}
@@ -111,3 +111,318 @@ fn ensure_test() {
     hello at tests/ensure.rs 104:9"#
     );
 }
+
+#[test]
+fn ensure_eq_diff() {
+    #[derive(Debug, PartialEq)]
+    struct Large {
+        a: u64,
+        b: &'static str,
+        c: Vec<u8>,
+        d: bool,
+        e: Option<i32>,
+        f: &'static str,
+    }
+
+    let lhs = Large {
+        a: 1,
+        b: "hello",
+        c: vec![1, 2, 3],
+        d: true,
+        e: None,
+        f: "a long enough field",
+    };
+    let rhs = Large {
+        a: 1,
+        b: "world",
+        c: vec![1, 2, 3],
+        d: true,
+        e: None,
+        f: "a long enough field",
+    };
+    let fail = || -> Result<u8> {
+        ensure_eq!(lhs, rhs);
+        Ok(0)
+    };
+    let msg = format!("{}", fail().unwrap_err());
+    // only the changed field and its context are shown
+    assert!(msg.contains(" -    b: \"hello\",\n +    b: \"world\",\n"));
+    assert!(!msg.contains("a long enough field"));
+    assert_eq!(
+        msg,
+        r#"
+    ensure_eq(
+ diff (-lhs +rhs):
+  Large {
+      a: 1,
+ -    b: "hello",
+ +    b: "world",
+      c: [
+          1,
+ …
+) -> equality assertion failed
+  at tests/ensure.rs 144:9"#
+    );
+
+    // short values still show both
+    let fail = || -> Result<u8> {
+        ensure_eq!(vec![1, 2], vec![1, 3]);
+        Ok(0)
+    };
+    assert_eq!(
+        format!("{}", fail().unwrap_err()),
+        r#"
+    ensure_eq(
+ lhs: [1, 2]
+ rhs: [1, 3]
+) -> equality assertion failed
+  at tests/ensure.rs 169:9"#
+    );
+}
+
+#[test]
+fn ensure_eq_diff_multiline_str() {
+    let lhs: String = (0..10).map(|i| format!("line {i}\n")).collect();
+    let rhs = lhs.replace("line 5", "line five");
+    let fail = || -> Result<u8> {
+        ensure_eq!(lhs, rhs);
+        Ok(0)
+    };
+    assert_eq!(
+        format!("{}", fail().unwrap_err()),
+        r#"
+    ensure_eq(
+ diff (-lhs +rhs):
+ …
+  line 3\n
+  line 4\n
+ -line 5\n
+ +line five\n
+  line 6\n
+  line 7\n
+ …
+) -> equality assertion failed
+  at tests/ensure.rs 188:9"#
+    );
+}
+
+#[test]
+fn ensure_locationless() {
+    use stacked_errors::{
+        ensure_eq_locationless, ensure_locationless, ensure_ne_locationless, StackedErrorDowncast,
+    };
+
+    fn ex(arm: u8) -> Result<()> {
+        let x = 1;
+        match arm {
+            0 => ensure_locationless!(x == 2),
+            1 => ensure_locationless!(x == 2, "custom"),
+            2 => ensure_eq_locationless!(x, 2),
+            3 => ensure_eq_locationless!(x, 2, "custom"),
+            4 => ensure_ne_locationless!(x, 1),
+            5 => ensure_ne_locationless!(x, 1, "custom"),
+            _ => {
+                ensure_locationless!(x == 1);
+                ensure_eq_locationless!(x, 1);
+                ensure_ne_locationless!(x, 2);
+            }
+        }
+        Ok(())
+    }
+
+    let messages = [
+        "ensure(x == 2) -> assertion failed",
+        "custom",
+        "ensure_eq(\n lhs: 1\n rhs: 2\n) -> equality assertion failed",
+        "custom",
+        "ensure_ne(\n lhs: 1\n rhs: 1\n) -> inequality assertion failed",
+        "custom",
+    ];
+    for (arm, message) in messages.into_iter().enumerate() {
+        let e = ex(arm as u8).unwrap_err();
+        assert_eq!(e.iter().len(), 1);
+        let frame = e.iter().next().unwrap();
+        assert!(frame.get_location().is_none());
+        assert_eq!(format!("{}", frame.get_err()), message);
+    }
+    ex(6).unwrap();
+}
+
+#[test]
+fn ensure_operand_truncation() {
+    use stacked_errors::{max_operand_bytes, DEFAULT_MAX_OPERAND_BYTES};
+
+    assert_eq!(max_operand_bytes(), DEFAULT_MAX_OPERAND_BYTES);
+
+    let lhs = "a".repeat(1 << 20);
+    let rhs = "b".repeat(1 << 20);
+    let fail = || -> Result<u8> {
+        ensure_eq!(lhs, rhs);
+        Ok(0)
+    };
+    let e = format!("{}", fail().unwrap_err());
+    assert!(e.len() < 3 * DEFAULT_MAX_OPERAND_BYTES);
+    // the cap is per operand, the leading quote is kept
+    let elided = (1 << 20) + 2 - DEFAULT_MAX_OPERAND_BYTES;
+    assert_eq!(
+        e.matches(&format!("... ({elided} bytes elided)")).count(),
+        2
+    );
+
+    let fail = || -> Result<u8> {
+        ensure_ne!(lhs, lhs);
+        Ok(0)
+    };
+    let e = format!("{}", fail().unwrap_err());
+    assert!(e.len() < 3 * DEFAULT_MAX_OPERAND_BYTES);
+    assert!(e.contains("bytes elided)"));
+
+    // multi-byte characters are not split, "é" is 2 bytes and the cap lands in
+    // the middle of one after the leading quote
+    let lhs = "é".repeat(1 << 19);
+    let fail = || -> Result<u8> {
+        ensure_eq!(lhs, "");
+        Ok(0)
+    };
+    let e = format!("{}", fail().unwrap_err());
+    let kept = DEFAULT_MAX_OPERAND_BYTES - 1;
+    let elided = (1 << 20) + 2 - kept;
+    assert!(e.contains(&format!(
+        " lhs: \"{}... ({elided} bytes elided)\n",
+        "é".repeat((kept - 1) / 2)
+    )));
+    assert!(e.contains(" rhs: \"\"\n"));
+}
+
+#[test]
+fn ensure_captures() {
+    use stacked_errors::{ensure, set_max_operand_bytes, StackedErrorDowncast};
+
+    fn msg(res: Result<()>) -> String {
+        res.unwrap_err()
+            .iter()
+            .next()
+            .unwrap()
+            .downcast_ref::<String>()
+            .unwrap()
+            .clone()
+    }
+
+    // the operands are not moved
+    fn strings(a: String, b: String) -> Result<()> {
+        ensure!(a == b);
+        ensure!(a.len() < 4);
+        drop((a, b));
+        Ok(())
+    }
+    strings("x".to_owned(), "x".to_owned()).unwrap();
+    assert_eq!(
+        msg(strings("x".to_owned(), "y".to_owned())),
+        r#"ensure(a == b) -> assertion failed (a = "x", b = "y")"#
+    );
+    let e = strings("long".to_owned(), "long".to_owned()).unwrap_err();
+    assert_eq!(
+        *e.iter().next().unwrap().downcast_ref::<&str>().unwrap(),
+        "ensure(a.len() < 4) -> assertion failed"
+    );
+
+    fn bools(a: bool, b: bool) -> Result<()> {
+        ensure!(a && b);
+        ensure!(a || b);
+        Ok(())
+    }
+    bools(true, true).unwrap();
+    // only comparisons capture their operands
+    let e = bools(true, false).unwrap_err();
+    assert_eq!(
+        *e.iter().next().unwrap().downcast_ref::<&str>().unwrap(),
+        "ensure(a && b) -> assertion failed"
+    );
+
+    // field accesses are not mistaken for a binary operation
+    struct Config {
+        enabled: bool,
+        limit: u32,
+    }
+    fn fields(cfg: Config, c: u32) -> Result<()> {
+        ensure!(cfg.enabled);
+        ensure!(cfg.limit < c);
+        Ok(())
+    }
+    fields(
+        Config {
+            enabled: true,
+            limit: 1,
+        },
+        2,
+    )
+    .unwrap();
+    for (cfg, msg) in [
+        (
+            Config {
+                enabled: false,
+                limit: 1,
+            },
+            "ensure(cfg.enabled) -> assertion failed",
+        ),
+        (
+            Config {
+                enabled: true,
+                limit: 3,
+            },
+            "ensure(cfg.limit < c) -> assertion failed",
+        ),
+    ] {
+        let e = fields(cfg, 2).unwrap_err();
+        assert_eq!(
+            *e.iter().next().unwrap().downcast_ref::<&str>().unwrap(),
+            msg
+        );
+    }
+
+    fn literal_first(x: i32) -> Result<()> {
+        ensure!(-1 < x);
+        Ok(())
+    }
+    literal_first(0).unwrap();
+    assert_eq!(
+        msg(literal_first(-5)),
+        "ensure(-1 < x) -> assertion failed (x = -5)"
+    );
+
+    // operands without `Debug` are skipped
+    #[derive(PartialEq)]
+    struct NoDebug(u8);
+    fn no_debug(a: NoDebug, b: u8) -> Result<()> {
+        ensure!(a == a);
+        let b = NoDebug(b);
+        ensure!(a == b);
+        Ok(())
+    }
+    assert_eq!(
+        msg(no_debug(NoDebug(1), 2)),
+        "ensure(a == b) -> assertion failed"
+    );
+    fn half_debug(a: NoDebug, b: u8) -> Result<()> {
+        ensure!(a.0 == b);
+        ensure!(b > 10);
+        Ok(())
+    }
+    assert_eq!(
+        msg(half_debug(NoDebug(1), 1)),
+        "ensure(b > 10) -> assertion failed (b = 1)"
+    );
+
+    // operands are truncated like those of `ensure_eq`
+    fn long(a: &[u8], b: &[u8]) -> Result<()> {
+        ensure!(a == b);
+        Ok(())
+    }
+    set_max_operand_bytes(4);
+    let s = msg(long(&[0; 100], &[1]));
+    set_max_operand_bytes(stacked_errors::DEFAULT_MAX_OPERAND_BYTES);
+    assert_eq!(
+        s,
+        "ensure(a == b) -> assertion failed (a = [0, ... (296 bytes elided), b = [1])"
+    );
+}
@@ -0,0 +1,3 @@
+// included with an absolute path, so that the location is absolute like the
+// locations of dependencies built from source outside of the registry
+stacked_errors::Error::from_err("root")
@@ -0,0 +1,6 @@
+use stacked_errors::{Error, Result, StackableErr};
+
+pub fn fail() -> Result<()> {
+    let res: Result<()> = Err(Error::from_err("connection reset"));
+    res.stack_err("connecting")
+}
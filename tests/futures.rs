@@ -0,0 +1,39 @@
+#![cfg(feature = "futures")]
+
+use stacked_errors::{catch_stacked_future, PanicError, StackedErrorDowncast};
+
+#[test]
+fn catch_future_panic() {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    rt.block_on(async {
+        assert_eq!(catch_stacked_future(async { 42u8 }).await.unwrap(), 42);
+
+        // the location is of the `catch_stacked_future` call, not of the poll
+        let line = line!() + 1;
+        let fut = catch_stacked_future(async {
+            tokio::task::yield_now().await;
+            panic!("test")
+        });
+        let e = fut.await.unwrap_err();
+        assert!(e.is_panic());
+        let mut iter = e.iter();
+        assert_eq!(
+            *iter.next().unwrap().downcast_ref::<&str>().unwrap(),
+            "test"
+        );
+        let x = iter.next().unwrap();
+        x.downcast_ref::<PanicError>().unwrap();
+        let l = x.get_location().unwrap();
+        assert_eq!(l.file(), "tests/futures.rs");
+        assert_eq!(l.line(), line);
+
+        let x = 5u64;
+        let e = catch_stacked_future(async move { panic!("test {x}") })
+            .await
+            .unwrap_err();
+        let x = e.iter().next().unwrap();
+        assert_eq!(*x.downcast_ref::<String>().unwrap(), "test 5");
+    });
+}
@@ -0,0 +1,48 @@
+//! The interner is global, so this is kept in its own test binary with a
+//! single test
+#![cfg(feature = "intern")]
+
+use std::sync::Arc;
+
+use stacked_errors::{
+    intern, interner_stats, set_interner_max_entries, Error, StackedErrorDowncast,
+};
+
+fn payload(e: &Error) -> &Arc<str> {
+    e.iter().last().unwrap().downcast_ref::<Arc<str>>().unwrap()
+}
+
+#[test]
+fn interner() {
+    let before = interner_stats();
+    let errs: Vec<Error> = (0..3)
+        .map(|_| Error::new().add_err_interned(&format!("retrying {}", "host")))
+        .collect();
+    assert!(Arc::ptr_eq(payload(&errs[0]), payload(&errs[1])));
+    assert!(Arc::ptr_eq(payload(&errs[0]), payload(&errs[2])));
+    let x = 5;
+    let e = Error::new().add_err(intern!("retrying {}", "host"));
+    assert!(Arc::ptr_eq(payload(&errs[0]), payload(&e)));
+    let e = Error::new().add_err(intern!("other {x}"));
+    assert!(!Arc::ptr_eq(payload(&errs[0]), payload(&e)));
+    assert!(format!("{e}").contains("other 5 at "));
+    let stats = interner_stats();
+    assert_eq!(stats.misses - before.misses, 2);
+    assert_eq!(stats.hits - before.hits, 3);
+
+    // eviction keeps the table bounded, entries still used by errors are kept
+    // over unused ones
+    set_interner_max_entries(4);
+    for i in 0..100 {
+        let _ = intern(&i.to_string());
+        assert!(interner_stats().entries <= 4);
+    }
+    let stats = interner_stats();
+    assert!(stats.evictions > 0);
+    assert_eq!(stats.max_entries, 4);
+    drop(e);
+    let kept = intern("retrying host");
+    assert!(Arc::ptr_eq(payload(&errs[0]), &kept));
+    // evicted messages stay valid
+    assert_eq!(&*payload(&errs[0]).clone(), "retrying host");
+}
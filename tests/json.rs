@@ -0,0 +1,167 @@
+#![cfg(feature = "serde_json")]
+
+use serde_json::json;
+use stacked_errors::{json_path_get, stacked_get_path, Result};
+
+#[test]
+fn json_path() {
+    let value = json!({
+        "State": {
+            "Status": "running",
+            "Inner": {"Deep": true}
+        },
+        "Args": ["--entry-name", {"Uuid": 7}],
+        "dotted.key": {"x": 1},
+        "back\\slash": 2
+    });
+    assert_eq!(json_path_get(&value, "").unwrap(), &value);
+    assert_eq!(json_path_get(&value, "State.Status").unwrap(), "running");
+    assert_eq!(json_path_get(&value, "State.Inner.Deep").unwrap(), true);
+    assert_eq!(json_path_get(&value, "Args.0").unwrap(), "--entry-name");
+    assert_eq!(json_path_get(&value, "Args.1.Uuid").unwrap(), 7);
+    assert_eq!(json_path_get(&value, "dotted\\.key.x").unwrap(), 1);
+    assert_eq!(json_path_get(&value, "back\\\\slash").unwrap(), 2);
+
+    let e = json_path_get(&value, "State.Missing.Deep").unwrap_err();
+    assert!(format!("{e}").contains(
+        "json_path_get(\"State.Missing.Deep\") -> indexing failed at segment \"Missing\""
+    ));
+    let e = json_path_get(&value, "Args.x").unwrap_err();
+    assert!(format!("{e}").contains("indexing failed at segment \"x\""));
+    let e = json_path_get(&value, "Args.5").unwrap_err();
+    assert!(format!("{e}").contains("indexing failed at segment \"5\""));
+    // without escaping the dot is a separator
+    assert!(json_path_get(&value, "dotted.key.x").is_err());
+
+    // only canonical indexes are accepted
+    for path in [
+        "Args.+1", "Args.01", "Args.00", "Args.-0", "Args. 1", "Args.1 ", "Args.",
+    ] {
+        let e = json_path_get(&value, path).unwrap_err();
+        assert!(
+            format!("{e}").contains("indexing failed at segment"),
+            "{path}"
+        );
+    }
+    assert_eq!(json_path_get(&value, "Args.0").unwrap(), "--entry-name");
+
+    // a trailing backslash is literal
+    let value = json!({"a\\": 1, "a": 2, "b": {"c\\": 3}});
+    assert_eq!(json_path_get(&value, "a\\").unwrap(), 1);
+    assert_eq!(json_path_get(&value, "b.c\\").unwrap(), 3);
+    assert_eq!(json_path_get(&value, "a\\\\").unwrap(), 1);
+}
+
+#[test]
+fn json_path_macro() {
+    fn ex(path: &str) -> Result<u64> {
+        let value = json!({"State": {"Retries": [3, 4]}});
+        let x = stacked_get_path!(value, path).as_u64().unwrap();
+        Ok(x)
+    }
+
+    assert_eq!(ex("State.Retries.1").unwrap(), 4);
+    let e = ex("State.Other").unwrap_err();
+    assert!(format!("{e}").contains("tests/json.rs"));
+}
+
+#[test]
+fn json_get_as() {
+    use serde_json::Value;
+    use stacked_errors::{stacked_get_as, StackedErrorDowncast};
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct State {
+        status: String,
+        running: bool,
+    }
+
+    let value = json!({
+        "Created": 2023,
+        "Offset": -4,
+        "Ratio": 0.5,
+        "Id": "id example",
+        "State": {"status": "running", "running": true}
+    });
+
+    fn ok(value: &Value) -> Result<()> {
+        assert_eq!(stacked_get_as!(value["Created"], u64), 2023);
+        assert_eq!(stacked_get_as!(value["Offset"], i64), -4);
+        assert_eq!(stacked_get_as!(value["Ratio"], f64), 0.5);
+        assert_eq!(stacked_get_as!(value["Id"], str), "id example");
+        assert!(stacked_get_as!(value["State"]["running"], bool));
+        assert_eq!(stacked_get_as!(value["State"], deserialize State), State {
+            status: "running".to_owned(),
+            running: true
+        });
+        Ok(())
+    }
+    ok(&value).unwrap();
+
+    fn wrong_type(value: &Value) -> Result<u64> {
+        Ok(stacked_get_as!(value["Id"], u64))
+    }
+    let e = wrong_type(&value).unwrap_err();
+    assert_eq!(e.iter().len(), 1);
+    let frame = e.iter().next().unwrap();
+    assert_eq!(
+        format!("{}", frame.get_err()),
+        "expected u64, found string \"id example\""
+    );
+    assert_eq!(frame.get_location().unwrap().file(), "tests/json.rs");
+
+    fn wrong_deserialize(value: &Value) -> Result<State> {
+        Ok(stacked_get_as!(value["Created"], deserialize State))
+    }
+    let e = wrong_deserialize(&value).unwrap_err();
+    assert!(format!("{e}").contains("found number 2023: invalid type"));
+
+    fn missing(value: &Value) -> Result<bool> {
+        Ok(stacked_get_as!(value["State"]["missing"], bool))
+    }
+    let e = missing(&value).unwrap_err();
+    let frame = e.iter().next().unwrap();
+    assert!(format!("{}", frame.get_err()).contains("indexing failed at [\"State\"]"));
+    assert_eq!(frame.get_location().unwrap().file(), "tests/json.rs");
+}
+
+#[test]
+fn json_get_deserialize() {
+    use serde_json::Value;
+    use stacked_errors::{stacked_get_deserialize, StackedErrorDowncast};
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Limits {
+        max: u32,
+        names: Vec<String>,
+    }
+
+    fn ex(value: &Value) -> Result<Limits> {
+        Ok(stacked_get_deserialize!(value["cfg"]["limits"], Limits))
+    }
+
+    let value = json!({"cfg": {"limits": {"max": 8, "names": ["a"]}}});
+    assert_eq!(ex(&value).unwrap(), Limits {
+        max: 8,
+        names: vec!["a".to_owned()]
+    });
+
+    let e = ex(&json!({"cfg": {}})).unwrap_err();
+    assert_eq!(e.iter().len(), 1);
+    assert!(format!("{e}").contains("indexing failed at [\"cfg\"]"));
+
+    let e = ex(&json!({"cfg": {"limits": {"max": "8", "names": []}}})).unwrap_err();
+    assert_eq!(e.iter().len(), 2);
+    let root = e.iter().next().unwrap();
+    assert!(root.downcast_ref::<serde_json::Error>().is_some());
+    assert!(root.get_location().is_none());
+    assert_eq!(
+        format!("{}", root.get_err()),
+        "invalid type: string \"8\", expected u32"
+    );
+    let top = e.iter().nth(1).unwrap();
+    assert_eq!(top.get_location().unwrap().file(), "tests/json.rs");
+    assert!(format!("{}", top.get_err())
+        .starts_with("stacked_get_deserialize(value[\"cfg\"][\"limits\"]) -> deserializing into "));
+    assert!(format!("{}", top.get_err()).ends_with("Limits failed"));
+}
@@ -0,0 +1,29 @@
+//! The location wrap width is global, so this is kept in its own test binary
+//! with a single test
+
+use stacked_errors::{location_wrap_width, set_location_wrap_width, Error};
+
+#[test]
+fn location_wrap_width_responds() {
+    let e = Error::from_err("a message that is long enough to matter, but not too long");
+    // the default is 80 for `Display`
+    assert_eq!(location_wrap_width(), None);
+    assert!(format!("{e}").contains("\n  at tests/location_wrap_width.rs"));
+
+    set_location_wrap_width(Some(200));
+    assert_eq!(location_wrap_width(), Some(200));
+    assert!(format!("{e}").contains("too long at tests/location_wrap_width.rs"));
+    assert!(format!("{e:?}").contains(" at "));
+    assert!(!format!("{e:?}").contains("\n  at "));
+
+    set_location_wrap_width(Some(20));
+    let e = Error::from_err("short message");
+    assert!(format!("{e}").contains("\n  at tests/location_wrap_width.rs"));
+    assert!(format!("{e:?}").contains("\n  at "));
+
+    // wrapping options take precedence
+    assert!(!format!("{}", e.display_wrapped(200)).contains("\n  at "));
+
+    set_location_wrap_width(None);
+    assert!(format!("{e}").contains("short message at tests/location_wrap_width.rs"));
+}
@@ -0,0 +1,119 @@
+//! Only run with `cargo test --features locations-only --test locations_only`,
+//! the other tests check messages
+#![cfg(feature = "locations-only")]
+
+use stacked_errors::{
+    bail, bail_locationless, break_err, ensure, ensure_eq, ensure_eq_locationless,
+    ensure_locationless, format_err, format_err_locationless, Error, Result, StackableErr,
+    StackedErrorDowncast, UnitError,
+};
+
+fn is_unit(e: &Error) -> bool {
+    e.iter().all(|e| e.downcast_ref::<UnitError>().is_some())
+}
+
+#[test]
+fn locations_only() {
+    fn f(x: u64) -> Result<()> {
+        ensure!(x < 10, "x was too large");
+        ensure_eq!(x % 2, 0);
+        if x == 4 {
+            bail!("four: {x}")
+        }
+        Ok(())
+    }
+    for x in [3, 4, 10] {
+        let e = f(x)
+            .stack_err("in f")
+            .stack_err_with(|| format!("x = {x}"))
+            .unwrap_err();
+        assert_eq!(e.iter().len(), 3);
+        assert!(is_unit(&e));
+        assert!(e.iter().all(|e| e.get_location().is_some()));
+        // the chain of locations is intact
+        assert!(e
+            .iter()
+            .all(|e| e.get_location().unwrap().file() == file!()));
+        assert!(!format!("{e}").contains("in f"));
+    }
+
+    let e = None::<()>.stack_err("none").unwrap_err();
+    assert!(is_unit(&e));
+    assert!(e.iter().next().unwrap().get_location().is_some());
+
+    // the closure is never called
+    let e = Error::from_err("root")
+        .stack_err_with(|| -> &str { unreachable!() })
+        .unwrap_err();
+    assert_eq!(e.iter().len(), 2);
+
+    // root errors are kept
+    let e = "a".parse::<u8>().stack_err("parsing").unwrap_err();
+    assert_eq!(e.iter().len(), 2);
+    assert!(!is_unit(&e));
+    assert!(e
+        .iter()
+        .nth(1)
+        .unwrap()
+        .downcast_ref::<UnitError>()
+        .is_some());
+    fn g() -> Result<u8> {
+        bail!("a".parse::<u8>().unwrap_err(); "parsing {}", 1)
+    }
+    let e = g().unwrap_err();
+    assert!(!format!("{e}").contains("parsing"));
+    assert!(format!("{e}").contains("invalid digit"));
+
+    // source errors passed alone are not messages
+    fn source() -> Result<()> {
+        bail!(Error::from_err("root").add_err("mid"))
+    }
+    let e = source().unwrap_err();
+    assert_eq!(e.iter().len(), 3);
+    assert!(format!("{e}").contains("root"));
+    assert!(format!("{e}").contains("mid"));
+    let e = format_err!(Error::from_err("root").add_err("mid"));
+    assert_eq!(e.iter().len(), 3);
+    assert!(format!("{e}").contains("mid"));
+    let e = format_err!("{}", 1);
+    assert!(is_unit(&e));
+    assert!(e.iter().next().unwrap().get_location().is_some());
+
+    // the message arms of the locationless macros have no location either
+    fn locationless(x: u64) -> Result<()> {
+        ensure_locationless!(x < 10);
+        ensure_eq_locationless!(x % 2, 0, "x was odd");
+        if x == 4 {
+            bail_locationless!("four: {x}")
+        }
+        Ok(())
+    }
+    for x in [3, 4, 10] {
+        let e = locationless(x).unwrap_err();
+        assert_eq!(e.iter().len(), 1);
+        assert!(is_unit(&e));
+        assert!(e.iter().next().unwrap().get_location().is_none());
+    }
+    let e = format_err_locationless!("message");
+    assert!(is_unit(&e));
+    assert!(e.iter().next().unwrap().get_location().is_none());
+
+    fn brk(source: bool) -> Result<()> {
+        let res = 'parse: {
+            if source {
+                break_err!('parse, "a".parse::<u8>().unwrap_err(); "parsing {}", 1)
+            }
+            for x in 0..2 {
+                if x == 1 {
+                    break_err!('parse, "x = {}", x)
+                }
+            }
+            Ok(())
+        };
+        res
+    }
+    assert!(is_unit(&brk(false).unwrap_err()));
+    let e = brk(true).unwrap_err();
+    assert!(!format!("{e}").contains("parsing"));
+    assert!(format!("{e}").contains("invalid digit"));
+}
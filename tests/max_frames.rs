@@ -0,0 +1,97 @@
+//! This is separate from the other tests because the maximum is global
+
+use stacked_errors::{set_max_frames, Error, StackedErrorDowncast, TimeoutError, TruncatedFrames};
+
+#[test]
+fn max_frames() {
+    set_max_frames(10);
+
+    let mut e = Error::from_err("root");
+    for i in 0..1000u64 {
+        e.push_err(i);
+        assert!(e.iter().len() <= 10);
+    }
+    assert_eq!(e.iter().len(), 10);
+    // the root frame survives
+    assert_eq!(
+        *e.iter().next().unwrap().downcast_ref::<&str>().unwrap(),
+        "root"
+    );
+    // there is exactly one marker, directly above the root
+    let markers: Vec<_> = e
+        .iter()
+        .filter_map(|e| e.downcast_ref::<TruncatedFrames>())
+        .collect();
+    assert_eq!(markers.len(), 1);
+    assert_eq!(markers[0].dropped, 992);
+    assert!(e
+        .iter()
+        .nth(1)
+        .unwrap()
+        .downcast_ref::<TruncatedFrames>()
+        .is_some());
+    // the most recent frames are kept in order
+    let recent: Vec<u64> = e
+        .iter()
+        .skip(2)
+        .map(|e| *e.downcast_ref::<u64>().unwrap())
+        .collect();
+    assert_eq!(recent, (992..1000).collect::<Vec<_>>());
+    let s = format!("{e}");
+    assert!(s.contains("\n    ... 992 frames dropped ...\n    root at "));
+
+    // chaining is also capped
+    let mut other = Error::timeout();
+    for _ in 0..20 {
+        other.push();
+    }
+    assert_eq!(other.iter().len(), 10);
+    let e = e.chain_errors(other);
+    assert_eq!(e.iter().len(), 10);
+    assert_eq!(
+        e.iter()
+            .nth(1)
+            .unwrap()
+            .downcast_ref::<TruncatedFrames>()
+            .unwrap()
+            .dropped,
+        1013
+    );
+    // the 8 recent frames of `e`, and the root (the timeout) and marker
+    // (counting 12 frames) of `other` were dropped
+    assert!(!e.is_timeout());
+    assert!(!e.iter().any(|e| e.downcast_ref::<TimeoutError>().is_some()));
+
+    assert_eq!(
+        format!("{}", TruncatedFrames { dropped: 9431 }),
+        "... 9,431 frames dropped ..."
+    );
+    assert_eq!(
+        format!("{}", TruncatedFrames { dropped: 1 }),
+        "... 1 frame dropped ..."
+    );
+    assert_eq!(
+        format!("{}", TruncatedFrames { dropped: 1234567 }),
+        "... 1,234,567 frames dropped ..."
+    );
+
+    // the help is not counted as a frame and is never dropped
+    let mut e = Error::from_err("root").with_help("help");
+    for i in 0..20u64 {
+        e.push_err(i);
+    }
+    assert_eq!(e.iter().len(), 10);
+    assert_eq!(e.help(), Some("help"));
+    assert_eq!(
+        *e.iter().next().unwrap().downcast_ref::<&str>().unwrap(),
+        "root"
+    );
+    assert!(format!("{e}").ends_with("\n  help: help"));
+
+    set_max_frames(usize::MAX);
+    let mut e = Error::from_err("root");
+    for _ in 0..100 {
+        e.push();
+    }
+    assert_eq!(e.iter().len(), 101);
+}
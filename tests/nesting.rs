@@ -0,0 +1,95 @@
+//! Strict nesting and the nesting hook are global, so this is kept in its own
+//! test binary with a single test
+
+use std::{
+    panic::catch_unwind,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use stacked_errors::{
+    set_nesting_hook, set_strict_nesting, strict_nesting, Error, ErrorItem, NestedErrorWarning,
+    StackableErr, StackedErrorDowncast,
+};
+
+static NESTED: AtomicUsize = AtomicUsize::new(0);
+
+fn hook(_: &ErrorItem) {
+    NESTED.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Returns if creating `e` reported nesting
+fn warned(f: impl FnOnce() -> Error) -> bool {
+    let before = NESTED.load(Ordering::Relaxed);
+    let e = f();
+    // the error itself is not changed when there is a hook
+    assert!(e
+        .iter()
+        .all(|e| e.downcast_ref::<NestedErrorWarning>().is_none()));
+    NESTED.load(Ordering::Relaxed) != before
+}
+
+#[test]
+fn nesting() {
+    set_nesting_hook(Some(hook));
+    let inner = || Error::from_err("root").add_err("context");
+
+    // not nested
+    assert!(!warned(|| Error::from_err(inner())));
+    assert!(!warned(|| Error::from_err("message")));
+    assert!(!warned(
+        || Error::from_err("root").add_err(String::from("ok"))
+    ));
+    // rendered reports are ordinary messages
+    assert!(!warned(|| Error::from_err(format!("{}", inner()))));
+    assert!(!warned(
+        || Error::from_err("cause").add_err(inner().to_string())
+    ));
+    assert!(!warned(|| Error::from_err("\n    indented")));
+    assert!(!warned(|| Error::from_err("cause").add_sub_error(inner())));
+
+    let len = |e: Error| e.iter().len();
+    assert_eq!(len(Error::from_err(Arc::new(inner()))), 1);
+    assert_eq!(len(Error::from_err("cause").add_err(Box::new(inner()))), 2);
+    for f in [
+        Box::new(|| Error::from_err(Arc::new(inner()))) as Box<dyn FnOnce() -> Error>,
+        Box::new(|| Error::from_err(Box::new(inner()))),
+        Box::new(|| {
+            Err::<(), _>("cause")
+                .stack_err(Arc::new(inner()))
+                .unwrap_err()
+        }),
+    ] {
+        // the check has no cost without debug assertions
+        assert_eq!(warned(f), cfg!(debug_assertions));
+    }
+
+    assert!(!strict_nesting());
+    set_strict_nesting(true);
+    let res = catch_unwind(|| Error::from_err(Arc::new(inner())));
+    assert_eq!(res.is_err(), cfg!(debug_assertions));
+    assert!(catch_unwind(|| Error::from_err(inner())).is_ok());
+    set_strict_nesting(false);
+    assert!(catch_unwind(|| Error::from_err(Arc::new(inner()))).is_ok());
+
+    // without a hook a warning frame is pushed
+    set_nesting_hook(None);
+    let before = NESTED.load(Ordering::Relaxed);
+    let e = Error::from_err("cause").add_err(Arc::new(inner()));
+    assert_eq!(NESTED.load(Ordering::Relaxed), before);
+    let top = e.iter().last().unwrap();
+    assert_eq!(
+        top.downcast_ref::<NestedErrorWarning>().is_some(),
+        cfg!(debug_assertions)
+    );
+    assert_eq!(e.iter().len(), if cfg!(debug_assertions) { 3 } else { 2 });
+    if cfg!(debug_assertions) {
+        assert!(format!("{e}").contains("`add_sub_error`"));
+    }
+    assert_eq!(
+        Error::from_err("cause").add_sub_error(inner()).iter().len(),
+        4
+    );
+}
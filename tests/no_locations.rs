@@ -0,0 +1,38 @@
+//! Only run with `cargo test --features no-locations --test no_locations`,
+//! the other tests check locations
+#![cfg(feature = "no-locations")]
+
+use stacked_errors::{bail, ensure, Error, IntoError, Result, StackableErr, StackedErrorDowncast};
+
+#[test]
+fn no_locations() {
+    let e = Error::from_err("root").add().add_err("context");
+    assert_eq!(e.iter().len(), 3);
+    assert!(e.iter().all(|e| e.get_location().is_none()));
+    // the location-only frame is not rendered at all
+    assert_eq!(format!("{e}"), "\n    context\n    root");
+
+    fn f(x: u64) -> Result<()> {
+        ensure!(x < 10);
+        if x == 5 {
+            bail!("five")
+        }
+        Ok(())
+    }
+    let e = f(5).stack_err("with x = 5").unwrap_err();
+    assert!(e.iter().all(|e| e.get_location().is_none()));
+    let e = f(10).stack().unwrap_err();
+    assert!(e.iter().all(|e| e.get_location().is_none()));
+    let e = None::<()>.stack_err("none").unwrap_err();
+    assert!(e.iter().all(|e| e.get_location().is_none()));
+    let e = "message".into_error();
+    assert!(e.iter().all(|e| e.get_location().is_none()));
+    assert!(!format!("{e}").contains(" at "));
+
+    let mut e = Error::empty();
+    for _ in 0..3 {
+        e.push_err_dedup("retrying");
+    }
+    assert_eq!(e.iter().len(), 1);
+    assert_eq!(e.iter().next().unwrap().occurrences(), 3);
+}
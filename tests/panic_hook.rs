@@ -0,0 +1,53 @@
+//! This is separate from the other tests because the panic hook is global
+#![cfg(feature = "std")]
+
+use std::{panic::catch_unwind, sync::mpsc};
+
+use stacked_errors::{install_panic_hook_with, PanicLocation, RootKind, StackedErrorDowncast};
+
+#[test]
+fn panic_hook() {
+    let (tx, rx) = mpsc::channel();
+    install_panic_hook_with(false, move |e| {
+        let _ = tx.send((
+            format!("{}", e.display_no_locations()),
+            e.iter()
+                .last()
+                .unwrap()
+                .downcast_ref::<PanicLocation>()
+                .cloned(),
+            e.is_panic(),
+            e.kind_at_root(),
+        ));
+    });
+    let line = line!() + 1;
+    let res0 = catch_unwind(|| panic!("oh no"));
+    let x = 3;
+    let res1 = catch_unwind(|| panic!("x was {x}"));
+    let res2 = catch_unwind(|| std::panic::panic_any(42u8));
+    // restore the default hook before anything can fail
+    let _ = std::panic::take_hook();
+    assert!(res0.is_err() && res1.is_err() && res2.is_err());
+
+    let (s, l, is_panic, kind) = rx.recv().unwrap();
+    assert_eq!(
+        s,
+        format!("\n    panicked at tests/panic_hook.rs {line}:32\n    PanicError\n    oh no")
+    );
+    let l = l.unwrap();
+    assert_eq!((l.file.as_str(), l.line), ("tests/panic_hook.rs", line));
+    assert!(is_panic);
+    assert_eq!(kind, RootKind::Panic);
+
+    // formatted messages and other payloads
+    assert!(rx
+        .recv()
+        .unwrap()
+        .0
+        .ends_with("\n    PanicError\n    x was 3"));
+    assert!(rx
+        .recv()
+        .unwrap()
+        .0
+        .ends_with("\n    PanicError\n    <unknown panic payload>"));
+}
@@ -0,0 +1,35 @@
+#![cfg(feature = "std")]
+
+#[path = "fixtures/snippet.rs"]
+mod snippet;
+
+use stacked_errors::Error;
+
+#[test]
+fn display_with_snippets() {
+    let e = snippet::fail().unwrap_err();
+    assert_eq!(
+        format!("{}", e.display_with_snippets()),
+        "
+    connecting at tests/fixtures/snippet.rs 5:9
+    5 |     res.stack_err(\"connecting\")
+      |         ^
+    connection reset at tests/fixtures/snippet.rs 4:31
+    4 |     let res: Result<()> = Err(Error::from_err(\"connection reset\"));
+      |                               ^"
+    );
+    // the normal `Display` is unchanged
+    assert_eq!(
+        format!("{e}"),
+        "
+    connecting at tests/fixtures/snippet.rs 5:9
+    connection reset at tests/fixtures/snippet.rs 4:31"
+    );
+
+    // locationless frames and unreadable files are skipped silently
+    let e = Error::from_err_locationless("root").add_err_locationless("top");
+    assert_eq!(
+        format!("{}", e.display_with_snippets()),
+        "\n    top\n    root"
+    );
+}
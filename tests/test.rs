@@ -1,7 +1,9 @@
 use core::mem;
 
 use stacked_errors::{
-    bail, Error, Result, StackableErr, StackedError, StackedErrorDowncast, UnitError,
+    anyhow, bail, bail_from, bail_probably_not_root_cause, bail_timeout, define_tag_error, eyre,
+    format_err, format_err_locationless, stack_try, Error, PanicError, ProbablyNotRootCauseError,
+    Result, StackableErr, StackedError, StackedErrorDowncast, TimeoutError, UnitError,
 };
 
 #[allow(unused)]
@@ -84,6 +86,74 @@ fn debug_and_display() {
     assert_eq!(format!("{x}"), "\n    hello");
 }
 
+#[test]
+fn slice_access() {
+    let mut e = Error::from_err("a").add_err("b").add_err("c");
+    assert_eq!(e.as_slice().len(), 3);
+    assert_eq!(*e.as_slice()[0].downcast_ref::<&str>().unwrap(), "a");
+    assert_eq!(*e.as_slice()[1].downcast_ref::<&str>().unwrap(), "b");
+    assert_eq!(
+        *e.as_slice().last().unwrap().downcast_ref::<&str>().unwrap(),
+        "c"
+    );
+    let (lower, upper) = e.as_ref().split_at(1);
+    assert_eq!(lower.len(), 1);
+    assert_eq!(upper.len(), 2);
+
+    *e.as_mut_slice()[0].downcast_mut::<&str>().unwrap() = "d";
+    e.as_mut_slice().swap(0, 2);
+    assert_eq!(*e.as_slice()[2].downcast_ref::<&str>().unwrap(), "d");
+    assert_eq!(*e.as_slice()[0].downcast_ref::<&str>().unwrap(), "c");
+}
+
+#[test]
+fn test_stack_try() {
+    let mut errs: Vec<Error> = vec![];
+    let mut sum = 0u64;
+    for s in ["1", "invalid", "3", "invalid"] {
+        sum += stack_try!(s.parse::<u64>() => errs, continue);
+    }
+    assert_eq!(sum, 4);
+    assert_eq!(errs.len(), 2);
+    for e in &errs {
+        assert_eq!(e.iter().len(), 1);
+        let x = e.iter().next().unwrap();
+        x.downcast_ref::<core::num::ParseIntError>().unwrap();
+        assert_eq!(x.get_location().unwrap().file(), "tests/test.rs");
+    }
+
+    let mut last = 0;
+    for x in [Some(1), Some(2), None, Some(4)] {
+        last = stack_try!(x, break);
+    }
+    assert_eq!(last, 2);
+
+    let mut errs: Vec<Error> = vec![];
+    let mut f =
+        |x: core::result::Result<u64, Error>| -> u64 { stack_try!(x => errs, return 0) + 1 };
+    assert_eq!(f(Ok(1)), 2);
+    assert_eq!(f(Err(Error::from_err("test"))), 0);
+    assert_eq!(errs.len(), 1);
+    // merged instead of nested
+    assert_eq!(errs[0].iter().len(), 2);
+
+    // accumulating into an `Option<Error>` behind a reference
+    fn parse_all(strs: &[&str], errs: &mut Option<Error>) -> u64 {
+        let mut sum = 0;
+        for s in strs {
+            sum += stack_try!(s.parse::<u64>() => *errs, continue);
+        }
+        sum
+    }
+    let mut errs = None;
+    assert_eq!(parse_all(&["1", "x", "2", "y"], &mut errs), 3);
+    let e = errs.unwrap();
+    assert_eq!(e.iter().len(), 2);
+    assert!(e
+        .iter()
+        .all(|x| x.downcast_ref::<core::num::ParseIntError>().is_some()));
+}
+
 #[test]
 fn test_bail() {
     let f = || -> Result<()> { bail!("test") };
@@ -106,6 +176,267 @@ fn test_bail() {
     let tmp = f().unwrap_err();
     let x = tmp.iter().next().unwrap();
     assert_eq!(*x.downcast_ref::<String>().unwrap(), "test 5");
+
+    let f = || -> Result<()> {
+        let e = ron::from_str::<bool>("invalid").unwrap_err();
+        bail!(e; "test")
+    };
+    let tmp = f().unwrap_err();
+    assert_eq!(tmp.iter().len(), 2);
+    let mut iter = tmp.iter();
+    let x = iter.next().unwrap();
+    x.downcast_ref::<ron::error::SpannedError>().unwrap();
+    assert!(x.get_location().is_none());
+    let x = iter.next().unwrap();
+    assert_eq!(*x.downcast_ref::<&str>().unwrap(), "test");
+    assert!(x.get_location().is_some());
+
+    let f = || -> Result<()> {
+        let x = 5u64;
+        bail!(Error::from_err_locationless("root"); "test {}", x)
+    };
+    let tmp = f().unwrap_err();
+    assert_eq!(tmp.iter().len(), 2);
+    let mut iter = tmp.iter();
+    let x = iter.next().unwrap();
+    assert_eq!(*x.downcast_ref::<&str>().unwrap(), "root");
+    assert!(x.get_location().is_none());
+    let x = iter.next().unwrap();
+    assert_eq!(*x.downcast_ref::<String>().unwrap(), "test 5");
+    assert!(x.get_location().is_some());
+}
+
+#[test]
+fn test_bail_from() {
+    let f = |e: Error| -> Result<()> { bail_from!(e) };
+    let tmp = f(Error::from_err("root")).unwrap_err();
+    assert_eq!(tmp.iter().len(), 2);
+    let mut iter = tmp.iter();
+    assert_eq!(
+        *iter.next().unwrap().downcast_ref::<&str>().unwrap(),
+        "root"
+    );
+    let x = iter.next().unwrap();
+    x.downcast_ref::<UnitError>().unwrap();
+    assert!(x.get_location().is_some());
+
+    let f = |e: Error| -> Result<()> {
+        let x = 5u64;
+        bail_from!(e, "test {}", x)
+    };
+    let tmp = f(Error::from_err("root")).unwrap_err();
+    assert_eq!(tmp.iter().len(), 2);
+    let mut iter = tmp.iter();
+    assert_eq!(
+        *iter.next().unwrap().downcast_ref::<&str>().unwrap(),
+        "root"
+    );
+    let x = iter.next().unwrap();
+    assert_eq!(*x.downcast_ref::<String>().unwrap(), "test 5");
+    assert!(x.get_location().is_some());
+
+    // not a `stacked_errors::Error`
+    let f = || -> Result<()> {
+        match ron::from_str::<bool>("invalid") {
+            Ok(_) => Ok(()),
+            Err(e) => bail_from!(e, "test"),
+        }
+    };
+    let tmp = f().unwrap_err();
+    assert_eq!(tmp.iter().len(), 2);
+    let mut iter = tmp.iter();
+    let x = iter.next().unwrap();
+    x.downcast_ref::<ron::error::SpannedError>().unwrap();
+    assert!(x.get_location().is_none());
+    let x = iter.next().unwrap();
+    assert_eq!(*x.downcast_ref::<&str>().unwrap(), "test");
+    assert!(x.get_location().is_some());
+
+    let f = || -> Result<()> { bail_from!("root") };
+    let tmp = f().unwrap_err();
+    assert_eq!(tmp.iter().len(), 1);
+    let x = tmp.iter().next().unwrap();
+    assert_eq!(*x.downcast_ref::<&str>().unwrap(), "root");
+    assert!(x.get_location().is_some());
+}
+
+#[test]
+fn test_format_err() {
+    let tmp: Error = format_err!("test");
+    let x = tmp.iter().next().unwrap();
+    assert!(x.get_location().is_some());
+    assert_eq!(*x.downcast_ref::<&str>().unwrap(), "test");
+
+    let x = 5u64;
+    let tmp: Error = format_err!("test {x}");
+    let x = tmp.iter().next().unwrap();
+    assert!(x.get_location().is_some());
+    assert_eq!(*x.downcast_ref::<String>().unwrap(), "test 5");
+
+    let x = 5u64;
+    let tmp: Error = format_err!("test {}", x);
+    let x = tmp.iter().next().unwrap();
+    assert!(x.get_location().is_some());
+    assert_eq!(*x.downcast_ref::<String>().unwrap(), "test 5");
+
+    let tmp: Error = format_err!(UnitError {});
+    let x = tmp.iter().next().unwrap();
+    assert!(x.get_location().is_some());
+    x.downcast_ref::<UnitError>().unwrap();
+
+    let tmp: Error = format_err_locationless!("test");
+    let x = tmp.iter().next().unwrap();
+    assert!(x.get_location().is_none());
+    assert_eq!(*x.downcast_ref::<&str>().unwrap(), "test");
+
+    let x = 5u64;
+    let tmp: Error = format_err_locationless!("test {}", x);
+    let x = tmp.iter().next().unwrap();
+    assert!(x.get_location().is_none());
+    assert_eq!(*x.downcast_ref::<String>().unwrap(), "test 5");
+
+    let tmp: Error = format_err_locationless!(UnitError {});
+    let x = tmp.iter().next().unwrap();
+    assert!(x.get_location().is_none());
+    x.downcast_ref::<UnitError>().unwrap();
+}
+
+#[test]
+fn test_eyre_and_anyhow() {
+    let x = 5u64;
+
+    let e: Error = eyre!("test");
+    assert_eq!(
+        *e.iter().next().unwrap().downcast_ref::<&str>().unwrap(),
+        "test"
+    );
+    let e: Error = eyre!(UnitError {});
+    e.iter()
+        .next()
+        .unwrap()
+        .downcast_ref::<UnitError>()
+        .unwrap();
+    let e: Error = eyre!("test {}", x);
+    assert_eq!(
+        *e.iter().next().unwrap().downcast_ref::<String>().unwrap(),
+        "test 5"
+    );
+
+    let e: Error = anyhow!("test");
+    assert_eq!(
+        *e.iter().next().unwrap().downcast_ref::<&str>().unwrap(),
+        "test"
+    );
+    let e: Error = anyhow!(UnitError {});
+    e.iter()
+        .next()
+        .unwrap()
+        .downcast_ref::<UnitError>()
+        .unwrap();
+    let e: Error = anyhow!("test {}", x);
+    assert_eq!(
+        *e.iter().next().unwrap().downcast_ref::<String>().unwrap(),
+        "test 5"
+    );
+
+    let f = || -> Result<()> { Err(eyre!("test")) };
+    let tmp = f().unwrap_err();
+    let x = tmp.iter().next().unwrap();
+    assert!(x.get_location().is_some());
+    assert_eq!(*x.downcast_ref::<&str>().unwrap(), "test");
+
+    let f = || -> Result<()> {
+        let x = 5u64;
+        Err(anyhow!("test {x}"))
+    };
+    let tmp = f().unwrap_err();
+    let x = tmp.iter().next().unwrap();
+    assert!(x.get_location().is_some());
+    assert_eq!(*x.downcast_ref::<String>().unwrap(), "test 5");
+}
+
+#[test]
+fn test_bail_special() {
+    let f = || -> Result<()> { bail_timeout!() };
+    let tmp = f().unwrap_err();
+    assert!(tmp.is_timeout());
+    assert_eq!(tmp.iter().len(), 1);
+    let x = tmp.iter().next().unwrap();
+    x.downcast_ref::<TimeoutError>().unwrap();
+    assert!(x.get_location().is_some());
+
+    let f = || -> Result<()> {
+        let name = "server";
+        bail_timeout!("waiting for {}", name)
+    };
+    let tmp = f().unwrap_err();
+    assert!(tmp.is_timeout());
+    assert_eq!(tmp.iter().len(), 2);
+    let mut iter = tmp.iter();
+    let x = iter.next().unwrap();
+    x.downcast_ref::<TimeoutError>().unwrap();
+    assert!(x.get_location().is_none());
+    let x = iter.next().unwrap();
+    assert_eq!(*x.downcast_ref::<String>().unwrap(), "waiting for server");
+    assert!(x.get_location().is_some());
+
+    let f = || -> Result<()> { bail_probably_not_root_cause!() };
+    let tmp = f().unwrap_err();
+    assert!(tmp.is_probably_not_root_cause());
+    assert_eq!(tmp.iter().len(), 1);
+    let x = tmp.iter().next().unwrap();
+    x.downcast_ref::<ProbablyNotRootCauseError>().unwrap();
+    assert!(x.get_location().is_some());
+
+    let f = || -> Result<()> { bail_probably_not_root_cause!("test") };
+    let tmp = f().unwrap_err();
+    assert!(tmp.is_probably_not_root_cause());
+    assert_eq!(tmp.iter().len(), 2);
+    let mut iter = tmp.iter();
+    let x = iter.next().unwrap();
+    x.downcast_ref::<ProbablyNotRootCauseError>().unwrap();
+    assert!(x.get_location().is_none());
+    let x = iter.next().unwrap();
+    assert_eq!(*x.downcast_ref::<&str>().unwrap(), "test");
+    assert!(x.get_location().is_some());
+}
+
+define_tag_error! {
+    /// test docs
+    pub struct RateLimitedError;
+    pub trait RateLimited { fn rate_limited; fn is_rate_limited; }
+}
+
+define_tag_error! {
+    struct RetryAfterError(u64);
+    trait RetryAfter { fn retry_after; fn is_retry_after; }
+}
+
+#[test]
+fn test_define_tag_error() {
+    let e = Error::rate_limited();
+    assert!(e.is_rate_limited());
+    assert!(!e.is_retry_after());
+    let x = e.iter().next().unwrap();
+    x.downcast_ref::<RateLimitedError>().unwrap();
+    assert_eq!(x.get_location().unwrap().file(), "tests/test.rs");
+    assert_eq!(format!("{RateLimitedError}"), "RateLimitedError");
+    assert_eq!(format!("{:?}", RateLimitedError), "RateLimitedError");
+    let _: RateLimitedError = Default::default();
+
+    let e = Error::from_err("hello")
+        .chain_errors(Error::retry_after(5))
+        .add_err("world");
+    assert!(!e.is_rate_limited());
+    assert!(e.is_retry_after());
+    let x = e.iter().nth(1).unwrap();
+    assert_eq!(x.downcast_ref::<RetryAfterError>().unwrap().0, 5);
+    assert_eq!(format!("{}", RetryAfterError(5)), "RetryAfterError(5)");
+    assert_eq!(format!("{:?}", RetryAfterError(5)), "RetryAfterError(5)");
+
+    let e = Error::from_err_locationless(RateLimitedError).add_err(RetryAfterError(1));
+    assert!(e.is_rate_limited());
+    assert!(e.is_retry_after());
 }
 
 #[test]
@@ -120,4 +451,1973 @@ fn test_special() {
         .add_err("world");
     assert_eq!(e.iter().len(), 3);
     assert!(e.is_timeout());
+    assert!(!e.is_panic());
+    let e = Error::from_err("hello")
+        .add_err(PanicError {})
+        .add_err("world");
+    assert!(e.is_panic());
+    assert!(!e.is_timeout());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_catch_stacked() {
+    use stacked_errors::catch_stacked;
+
+    assert_eq!(catch_stacked(|| 42u8).unwrap(), 42);
+    let res: Result<Result<()>> = catch_stacked(|| Err(Error::from_err("not a panic")));
+    assert!(res.unwrap().is_err());
+
+    let e = catch_stacked(|| panic!("test")).unwrap_err();
+    assert!(e.is_panic());
+    assert_eq!(e.iter().len(), 2);
+    let mut iter = e.iter();
+    let x = iter.next().unwrap();
+    assert_eq!(*x.downcast_ref::<&str>().unwrap(), "test");
+    assert!(x.get_location().is_none());
+    let x = iter.next().unwrap();
+    x.downcast_ref::<PanicError>().unwrap();
+    assert_eq!(x.get_location().unwrap().file(), "tests/test.rs");
+
+    let x = 5u64;
+    let e = catch_stacked(|| panic!("test {x}")).unwrap_err();
+    let x = e.iter().next().unwrap();
+    assert_eq!(*x.downcast_ref::<String>().unwrap(), "test 5");
+
+    let e = catch_stacked(|| std::panic::panic_any(5u64)).unwrap_err();
+    let x = e.iter().next().unwrap();
+    assert_eq!(
+        *x.downcast_ref::<&str>().unwrap(),
+        "<unknown panic payload>"
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_stacked_unwrap() {
+    use stacked_errors::{stacked_expect, stacked_unwrap};
+
+    assert_eq!(stacked_unwrap!(Some(1)), 1);
+    assert_eq!(stacked_unwrap!(Ok::<u8, Error>(1)), 1);
+    assert_eq!(stacked_unwrap!("1".parse::<u8>()), 1);
+    assert_eq!(stacked_expect!(Some(1), "msg"), 1);
+}
+
+#[cfg(feature = "std")]
+#[test]
+#[should_panic(expected = "stacked_unwrap failed with root cause: invalid digit found in string")]
+fn test_stacked_unwrap_foreign() {
+    stacked_errors::stacked_unwrap!("invalid".parse::<u8>());
+}
+
+#[cfg(feature = "std")]
+#[test]
+#[should_panic(expected = "stacked_unwrap failed with root cause: root")]
+fn test_stacked_unwrap_error() {
+    stacked_errors::stacked_unwrap!(Err::<(), _>(Error::from_err("root")).stack_err("context"));
+}
+
+#[cfg(feature = "std")]
+#[test]
+#[should_panic(expected = "stacked_unwrap failed with root cause: msg")]
+fn test_stacked_expect_option() {
+    stacked_errors::stacked_expect!(None::<u8>, "msg");
+}
+
+#[test]
+fn test_help() {
+    use stacked_errors::RootKind;
+
+    let e = Error::from_err_locationless("root").add_err_locationless("context");
+    assert_eq!(e.help(), None);
+    let e = e.with_help("first").with_help("try again later");
+    assert_eq!(e.help(), Some("try again later"));
+    // the help is not a frame
+    assert_eq!(e.iter().len(), 2);
+    assert_eq!(e.as_slice().len(), 2);
+    assert_eq!(e.messages().count(), 2);
+    let e = e.add_err_locationless("outer");
+    assert_eq!(
+        format!("{e}"),
+        "\n    outer\n    context\n    root\n  help: try again later"
+    );
+    let debug = format!("{e:?}");
+    assert!(debug.find("root").unwrap() < debug.find("help: try again later").unwrap());
+    assert_eq!(
+        format!("{}", e.display_limited(1)),
+        "\n    outer\n    … (2 more frames)\n  help: try again later"
+    );
+
+    let e = None::<()>.stack().unwrap_err().with_help("help");
+    assert!(e.is_unit_only());
+    assert_eq!(e.kind_at_root(), RootKind::Unit);
+    let e = Error::timeout().with_help("help");
+    assert_eq!(e.kind_at_root(), RootKind::Timeout);
+    let e = Error::from_err(String::from("single")).with_help("help");
+    assert_eq!(e.try_into_single::<String>().unwrap(), "single");
+    let mut e = Error::from_err_locationless("root").with_help("help");
+    assert!(e.replace_err(0, "replaced"));
+    assert!(!e.replace_err(1, "out of bounds"));
+    assert_eq!(format!("{e}"), "\n    replaced\n  help: help");
+
+    // chaining keeps the help of the error with the root cause
+    let e = Error::from_err_locationless("root")
+        .with_help("root help")
+        .chain_errors(Error::from_err_locationless("other").with_help("other help"));
+    assert_eq!(e.help(), Some("root help"));
+    assert_eq!(e.iter().len(), 2);
+    let e = Error::from_err_locationless("root")
+        .chain_errors(Error::from_err_locationless("other").with_help("other help"));
+    assert_eq!(e.help(), Some("other help"));
+    assert_eq!(e.iter().len(), 2);
+    let e = Error::from_err_locationless("context")
+        .with_help("context help")
+        .chain_errors_under(Error::from_err_locationless("root").with_help("root help"));
+    assert_eq!(e.help(), Some("context help"));
+    assert_eq!(e.iter().len(), 2);
+}
+
+#[test]
+fn test_stacked_assert() {
+    use stacked_errors::{stacked_assert, stacked_assert_eq, stacked_assert_ne};
+
+    stacked_assert!(true);
+    stacked_assert!(true, "msg");
+    stacked_assert!(true, "msg {}", 1);
+    stacked_assert_eq!(1, 1);
+    stacked_assert_eq!(1, 1, "msg");
+    stacked_assert_eq!(1, 1, "msg {}", 1);
+    stacked_assert_ne!(1, 2);
+    stacked_assert_ne!(1, 2, "msg");
+    stacked_assert_ne!(1, 2, "msg {}", 1);
+}
+
+#[test]
+#[should_panic(expected = "stacked_assert(1 > 2) -> assertion failed at tests/test.rs")]
+fn test_stacked_assert_failure() {
+    stacked_errors::stacked_assert!(1 > 2);
+}
+
+#[test]
+#[should_panic(expected = "stacked_assert(x < 3) -> assertion failed (x = 5) at tests/test.rs")]
+fn test_stacked_assert_operands_failure() {
+    let x = 5;
+    stacked_errors::stacked_assert!(x < 3);
+}
+
+#[test]
+#[should_panic(expected = "x was 5 at tests/test.rs")]
+fn test_stacked_assert_inline_args_failure() {
+    let x = 5;
+    stacked_errors::stacked_assert!(x < 3, "x was {x}");
+}
+
+#[test]
+#[should_panic(expected = "x was 3 at tests/test.rs")]
+fn test_stacked_assert_eq_inline_args_failure() {
+    let x = 3;
+    stacked_errors::stacked_assert_eq!(x, 4, "x was {x}");
+}
+
+#[test]
+#[should_panic(expected = "\n lhs: \"a\"\n rhs: \"b\"\n) -> equality assertion failed")]
+fn test_stacked_assert_eq_failure() {
+    stacked_errors::stacked_assert_eq!("a", "b");
+}
+
+#[test]
+#[should_panic(expected = "x was 3 at tests/test.rs")]
+fn test_stacked_assert_eq_format_failure() {
+    let x = 3;
+    stacked_errors::stacked_assert_eq!(x, 4, "x was {}", x);
+}
+
+#[test]
+#[should_panic(expected = "\n lhs: 8\n rhs: 8\n) -> inequality assertion failed")]
+fn test_stacked_assert_ne_failure() {
+    stacked_errors::stacked_assert_ne!(8, 8);
+}
+
+#[test]
+fn test_chain_errors_order() {
+    let lower = || Error::from_err_locationless("lower root").add_err_locationless("lower top");
+    let upper = || Error::from_err_locationless("upper root").add_err_locationless("upper top");
+    assert_eq!(
+        format!("{}", lower().chain_errors(upper())),
+        "\n    upper top\n    upper root\n    lower top\n    lower root"
+    );
+    assert_eq!(
+        format!("{}", upper().chain_errors_under(lower())),
+        "\n    upper top\n    upper root\n    lower top\n    lower root"
+    );
+    assert_eq!(
+        format!("{}", lower().chain_errors_under(upper())),
+        "\n    lower top\n    lower root\n    upper top\n    upper root"
+    );
+}
+
+#[test]
+fn test_matches_stack() {
+    use stacked_errors::{matches_stack, matches_stack_bool};
+
+    let e = Error::from_err("root cause")
+        .add_err("mid layer")
+        .add_err("outer context");
+    matches_stack!(e, ["outer context", "mid layer", "root cause"]);
+    matches_stack!(&e, ["outer", "cause"]);
+    matches_stack!(e, []);
+    let res: Result<()> = Err(e);
+    matches_stack!(res, ["mid layer"]);
+    // the same frame cannot match twice
+    assert!(!matches_stack_bool!(res, ["layer", "mid"]));
+    assert!(!matches_stack_bool!(Ok::<(), Error>(()), []));
+}
+
+#[test]
+#[should_panic(
+    expected = "matches_stack failed, needle \"outer context\" was not found in order\n \
+                needles:\n    \"root cause\"\n  - \"outer context\"\n frames (newest first):\n  \
+                0: outer context\n  1: root cause"
+)]
+fn test_matches_stack_wrong_order() {
+    let e = Error::from_err("root cause").add_err("outer context");
+    stacked_errors::matches_stack!(e, ["root cause", "outer context"]);
+}
+
+#[test]
+#[should_panic(
+    expected = "needle \"missing\" was not found in order\n needles:\n    \"outer context\"\n  - \
+                \"missing\"\n  ? \"root cause\"\n"
+)]
+fn test_matches_stack_missing() {
+    let e = Error::from_err("root cause").add_err("outer context");
+    stacked_errors::matches_stack!(e, ["outer context", "missing", "root cause"]);
+}
+
+#[test]
+fn test_display_github_annotations() {
+    let line = line!() + 1;
+    let e = Error::from_err("root cause: bad\ninput").add_err_locationless("context");
+    assert_eq!(
+        format!("{}", e.display_github_annotations()),
+        format!(
+            "::error::context\n::error file=tests/test.rs,line={line},col=13::root cause: \
+             bad%0Ainput"
+        )
+    );
+    let e = e.with_help("100% fixable");
+    assert!(
+        format!("{}", e.display_github_annotations()).ends_with("\n::notice::help: 100%25 fixable")
+    );
+}
+
+mod net {
+    use stacked_errors::{stack_here, Error, Result};
+
+    pub fn fetch(fail: bool) -> Result<u8> {
+        let res: Result<u8> = if fail {
+            Err(Error::from_err_locationless("root cause"))
+        } else {
+            Ok(1)
+        };
+        let x = stack_here!(res)?;
+        let x = stack_here!(Some(x), "unwrapping")?;
+        Ok(x)
+    }
+}
+
+#[test]
+fn test_stack_here() {
+    use stacked_errors::{stack_here, ModuleContext};
+
+    assert_eq!(net::fetch(false).unwrap(), 1);
+    let e = net::fetch(true).unwrap_err();
+    let frame = e.iter().last().unwrap();
+    let context = frame.downcast_ref::<ModuleContext>().unwrap();
+    assert_eq!(context.module, "test::net");
+    assert_eq!(context.label, None);
+    assert!(frame.get_location().is_some());
+    assert!(format!("{e}").starts_with("\n    in test::net at tests/test.rs"));
+
+    let e = Error::from_err(stack_here!());
+    assert!(format!("{e}").starts_with("\n    in test at tests/test.rs"));
+}
+
+#[test]
+fn test_replace_err() {
+    let mut e = Error::from_err("root").add_err("middle").add_err("top");
+    let location = e.as_slice()[1].get_location();
+    assert!(location.is_some());
+    assert!(e.replace_err(1, String::from("replaced")));
+    assert_eq!(e.as_slice()[1].get_location(), location);
+    assert_eq!(format!("{}", e.as_slice()[1].get_err()), "replaced");
+    stacked_errors::matches_stack!(e, ["top", "replaced", "root"]);
+    assert!(!e.replace_err(3, "oob"));
+    assert_eq!(e.iter().len(), 3);
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+fn test_stacked_get_expr_heads() {
+    use core::cell::Cell;
+
+    use serde_json::{json, Value};
+    use stacked_errors::{stacked_get, stacked_get_mut};
+
+    struct Resp {
+        config: Value,
+        evaluations: Cell<usize>,
+    }
+
+    impl Resp {
+        fn json(&self) -> &Value {
+            self.evaluations.set(self.evaluations.get() + 1);
+            &self.config
+        }
+
+        fn json_mut(&mut self) -> &mut Value {
+            self.evaluations.set(self.evaluations.get() + 1);
+            &mut self.config
+        }
+
+        fn max_retries(&self) -> Result<u64> {
+            stacked_get!(self.config["retry"]["max"]).as_u64().stack()
+        }
+    }
+
+    fn ex(resp: &mut Resp) -> Result<()> {
+        assert_eq!(resp.max_retries()?, 3);
+        assert_eq!(stacked_get!(resp.json()["data"][1]), 2);
+        assert_eq!(stacked_get!((resp.json())["data"][0]), 1);
+        *stacked_get_mut!(resp.config["retry"]["max"]) = 4.into();
+        *stacked_get_mut!(resp.json_mut()["data"][0]) = 0.into();
+        assert_eq!(resp.config, json!({"retry": {"max": 4}, "data": [0, 2]}));
+        Ok(())
+    }
+
+    let mut resp = Resp {
+        config: json!({"retry": {"max": 3}, "data": [1, 2]}),
+        evaluations: Cell::new(0),
+    };
+    ex(&mut resp).unwrap();
+    // the head is evaluated exactly once per use
+    assert_eq!(resp.evaluations.get(), 3);
+
+    fn missing(resp: &Resp) -> Result<()> {
+        let _ = stacked_get!(resp.json()["data"][5]);
+        Ok(())
+    }
+    let e = missing(&resp).unwrap_err();
+    assert!(format!("{e}")
+        .contains("stacked_get(resp.json()[\"data\"][5]) -> indexing failed at [\"data\"]"));
+    assert_eq!(resp.evaluations.get(), 4);
+}
+
+#[test]
+fn test_stack_map_err() {
+    use stacked_errors::StackMapErr;
+
+    #[derive(Debug)]
+    struct VerboseError {
+        code: u16,
+        #[allow(dead_code)]
+        headers: Vec<String>,
+    }
+
+    impl core::fmt::Display for VerboseError {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            write!(f, "{self:?}")
+        }
+    }
+
+    let res: core::result::Result<(), VerboseError> = Err(VerboseError {
+        code: 404,
+        headers: vec!["x".repeat(100)],
+    });
+    let e = res
+        .stack_map_err(|e| format!("request failed with {}", e.code))
+        .stack_err("fetching")
+        .unwrap_err();
+    assert_eq!(e.iter().len(), 2);
+    let root = e.iter().next().unwrap();
+    assert_eq!(
+        root.downcast_ref::<String>().unwrap(),
+        "request failed with 404"
+    );
+    assert!(root.get_location().is_some());
+
+    // mapping into an `Error` pushes a location onto it
+    let res: core::result::Result<(), &str> = Err("inner");
+    let e = res.stack_map_err(Error::from_err_locationless).unwrap_err();
+    assert_eq!(e.iter().len(), 2);
+    assert_eq!(format!("{}", e.iter().next().unwrap().get_err()), "inner");
+
+    assert_eq!(Ok::<u8, ()>(1).stack_map_err(|_| "unreachable").unwrap(), 1);
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+fn test_stacked_get_path_in_message() {
+    use serde_json::{json, Value};
+    use stacked_errors::{stacked_get, stacked_get_mut};
+
+    let value = json!({"State": {"Inner": {"Items": [{"Deep": 1}]}}});
+
+    fn get(value: &Value) -> Result<&Value> {
+        Ok(stacked_get!(value["State"]["Inner"]["Items"][0]["Missing"]))
+    }
+    let e = get(&value).unwrap_err();
+    assert_eq!(e.iter().len(), 1);
+    assert_eq!(
+        format!("{}", e.iter().next().unwrap().get_err()),
+        "stacked_get(value[\"State\"][\"Inner\"][\"Items\"][0][\"Missing\"]) -> indexing failed \
+         at [\"State\"][\"Inner\"][\"Items\"][0] (json object)"
+    );
+
+    fn get_root(value: &Value) -> Result<&Value> {
+        Ok(stacked_get!(value["Missing"]["Inner"]))
+    }
+    let e = get_root(&value).unwrap_err();
+    assert_eq!(
+        format!("{}", e.iter().next().unwrap().get_err()),
+        "stacked_get(value[\"Missing\"]) -> indexing failed at the root (json object)"
+    );
+
+    fn get_mut(mut value: Value) -> Result<()> {
+        *stacked_get_mut!(value["State"]["Inner"]["Other"]["Deep"]) = 2.into();
+        Ok(())
+    }
+    let e = get_mut(value).unwrap_err();
+    assert_eq!(
+        format!("{}", e.iter().next().unwrap().get_err()),
+        "stacked_get_mut(value[\"State\"][\"Inner\"][\"Other\"]) -> indexing failed at \
+         [\"State\"][\"Inner\"] (json object)"
+    );
+}
+
+#[cfg(feature = "thread-names")]
+#[test]
+fn test_thread_names() {
+    use std::thread;
+
+    let e = thread::Builder::new()
+        .name("worker-3".to_owned())
+        .spawn(|| Error::from_err("worker failed").add_err("in worker"))
+        .unwrap()
+        .join()
+        .unwrap();
+    let e = e.chain_errors(Error::from_err_locationless("joined"));
+    let root = e.iter().next().unwrap();
+    assert_eq!(root.meta().thread().unwrap().name(), Some("worker-3"));
+    // only the origin is recorded
+    assert!(e.iter().nth(1).unwrap().meta().thread().is_none());
+    assert!(e.iter().nth(2).unwrap().meta().thread().is_none());
+
+    let s = format!("{}", e.display_with_threads());
+    assert!(s.contains("\n    [thread: worker-3] worker failed at tests/test.rs"));
+    assert!(s.contains("\n    in worker at"));
+    assert!(!format!("{e}").contains("[thread"));
+
+    // unnamed threads use the id
+    let (e, id) = thread::spawn(|| (Error::from_err("x"), thread::current().id()))
+        .join()
+        .unwrap();
+    assert!(format!("{}", e.display_with_threads()).contains(&format!("[thread: {id:?}] x")));
+}
+
+#[test]
+fn test_strip_frames_in() {
+    fn wrapper(e: Error) -> Error {
+        // a wrapper that is missing `#[track_caller]` and records its own location
+        e.add()
+    }
+
+    let e = Error::from_err_locationless("root")
+        .add_err("context")
+        .add();
+    let mut e = wrapper(e);
+    e.push();
+    assert_eq!(e.iter().len(), 5);
+    e.strip_frames_in("tests/other");
+    assert_eq!(e.iter().len(), 5);
+    e.strip_frames_in("tests/");
+    // only the messages are left
+    assert_eq!(e.iter().len(), 2);
+    assert!(e.iter().all(|e| e.downcast_ref::<UnitError>().is_none()));
+    assert!(format!("{e}").starts_with("\n    context at tests/test.rs"));
+}
+
+#[test]
+fn test_try_into_single() {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    // large enough to not be stored inline
+    #[derive(Debug)]
+    struct Large {
+        drops: Arc<AtomicUsize>,
+        data: [u64; 8],
+    }
+
+    impl core::fmt::Display for Large {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            write!(f, "{:?}", self.data)
+        }
+    }
+
+    impl Drop for Large {
+        fn drop(&mut self) {
+            self.drops.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let e = Error::from_err_locationless(String::from("single"));
+    assert_eq!(e.try_into_single::<String>().unwrap(), "single");
+
+    let drops = Arc::new(AtomicUsize::new(0));
+    let e = Error::from_err(Large {
+        drops: drops.clone(),
+        data: [7; 8],
+    });
+    let large = e.try_into_single::<Large>().unwrap();
+    assert_eq!(large.data, [7; 8]);
+    assert_eq!(drops.load(Ordering::SeqCst), 0);
+    drop(large);
+    assert_eq!(drops.load(Ordering::SeqCst), 1);
+
+    // wrong type
+    let e = Error::from_err("str");
+    let e = e.try_into_single::<String>().unwrap_err();
+    assert_eq!(
+        *e.iter().next().unwrap().downcast_ref::<&str>().unwrap(),
+        "str"
+    );
+
+    // multiple frames
+    let e = Error::from_err(String::from("root")).add_err("context");
+    let e = e.try_into_single::<String>().unwrap_err();
+    assert_eq!(e.iter().len(), 2);
+    assert!(Error::empty().try_into_single::<String>().is_err());
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+fn test_stacked_set() {
+    use serde_json::{json, Value};
+    use stacked_errors::{stacked_set, stacked_set_insert};
+
+    fn overwrite(value: &mut Value) -> Result<()> {
+        stacked_set!(value["State"]["Status"] = "stopped");
+        stacked_set!(value["Args"][1] = String::from("--other"));
+        stacked_set!(value["Count"] = 2);
+        Ok(())
+    }
+
+    fn create(value: &mut Value) -> Result<()> {
+        stacked_set_insert!(value["State"]["Inner"]["Leaf"] = true);
+        stacked_set_insert!(value["Top"] = 1);
+        Ok(())
+    }
+
+    let mut value = json!({
+        "State": {"Status": "running", "Inner": {}},
+        "Args": ["--entry-name", "--uuid"],
+        "Count": 1
+    });
+    overwrite(&mut value).unwrap();
+    create(&mut value).unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "State": {"Status": "stopped", "Inner": {"Leaf": true}},
+            "Args": ["--entry-name", "--other"],
+            "Count": 2,
+            "Top": 1
+        })
+    );
+
+    // `stacked_set` requires the leaf to exist
+    let mut value = json!({"State": {}, "Args": [], "Count": 0});
+    let e = overwrite(&mut value).unwrap_err();
+    assert!(format!("{e}").contains(
+        "stacked_get_mut(value[\"State\"][\"Status\"]) -> indexing failed at [\"State\"]"
+    ));
+
+    // missing intermediate object
+    let mut value = json!({"State": {}});
+    let e = create(&mut value).unwrap_err();
+    assert!(format!("{e}").contains(
+        "stacked_get_mut(value[\"State\"][\"Inner\"]) -> indexing failed at [\"State\"]"
+    ));
+    assert_eq!(value, json!({"State": {}}));
+}
+
+#[test]
+fn test_messages() {
+    let e = Error::from_err("root")
+        .add()
+        .add_err("middle")
+        .add()
+        .add_err_locationless("top");
+    assert_eq!(e.iter().len(), 5);
+    let messages: Vec<_> = e
+        .messages()
+        .map(|(e, l)| (e.to_string(), l.map(|l| l.file())))
+        .collect();
+    assert_eq!(messages, [
+        ("root".to_owned(), Some("tests/test.rs")),
+        ("middle".to_owned(), Some("tests/test.rs")),
+        ("top".to_owned(), None),
+    ]);
+
+    // only location frames
+    let e = Error::new().add();
+    assert_eq!(e.messages().count(), 0);
+}
+
+#[test]
+fn test_stack_dbg() {
+    use std::cell::Cell;
+
+    use stacked_errors::stack_dbg;
+
+    struct Counted<'a>(&'a Cell<u32>);
+
+    impl core::fmt::Debug for Counted<'_> {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            self.0.set(self.0.get() + 1);
+            f.write_str("Counted")
+        }
+    }
+
+    struct Config {
+        retries: u8,
+    }
+
+    let count = Cell::new(0);
+    let counted = Counted(&count);
+    let config = Config { retries: 3 };
+
+    // `Debug` is never invoked on the `Ok` path
+    let res: Result<u8> = Ok(1);
+    assert_eq!(stack_dbg!(res, counted, config.retries).unwrap(), 1);
+    assert_eq!(stack_dbg!(Some(2), counted).unwrap(), 2);
+    assert_eq!(count.get(), 0);
+
+    let e = stack_dbg!(None::<()>, counted, config.retries, config.retries + 1).unwrap_err();
+    assert_eq!(count.get(), 1);
+    assert_eq!(e.iter().len(), 1);
+    let frame = e.iter().last().unwrap();
+    assert_eq!(
+        format!("{}", frame.get_err()),
+        "counted = Counted, config.retries = 3, config.retries + 1 = 4"
+    );
+    assert_eq!(frame.get_location().unwrap().file(), "tests/test.rs");
+
+    let e = stack_dbg!(Error::from_err_locationless("root"); config.retries,);
+    assert_eq!(e.iter().len(), 2);
+    assert_eq!(
+        format!("{}", e.iter().last().unwrap().get_err()),
+        "config.retries = 3"
+    );
+}
+
+#[test]
+fn test_shorten_location_with() {
+    use stacked_errors::{shorten_location, shorten_location_with};
+
+    let patterns = &["/target/", "/vendor/*/", "/git/checkouts/*-*/"];
+    // the first pattern that matches is used
+    assert_eq!(
+        shorten_location_with("/a/vendor/x/target/debug/build.rs", patterns),
+        "debug/build.rs"
+    );
+    assert_eq!(
+        shorten_location_with("/a/vendor/serde-1.0.0/src/de.rs", patterns),
+        "src/de.rs"
+    );
+    // the wildcard does not cross path separators
+    assert_eq!(
+        shorten_location_with("/a/vendor/x/src/de.rs", &["/vendor/*/de.rs"]),
+        "/a/vendor/x/src/de.rs"
+    );
+    assert_eq!(
+        shorten_location_with("/c/git/checkouts/repo-1a2b/abc/src/lib.rs", patterns),
+        "abc/src/lib.rs"
+    );
+    assert_eq!(
+        shorten_location_with("/home/git/checkouts/src/lib.rs", patterns),
+        "/home/git/checkouts/src/lib.rs"
+    );
+    // a later occurrence can match when an earlier one doesn't
+    assert_eq!(
+        shorten_location_with("/src/a/x/src/b/lib.rs", &["/src/*/lib"]),
+        ".rs"
+    );
+    assert_eq!(shorten_location_with("/a/x/y.rs", &["/x*"]), "/y.rs");
+
+    // no match
+    assert_eq!(shorten_location_with("src/lib.rs", patterns), "src/lib.rs");
+    assert_eq!(shorten_location_with("src/lib.rs", &[]), "src/lib.rs");
+
+    #[cfg(not(windows))]
+    assert_eq!(
+        shorten_location(
+            "/home/admin/.cargo/registry/src/index.crates.io-6f17d22bba15001f/\
+             super_orchestrator-0.5.1/src/misc.rs"
+        ),
+        "super_orchestrator-0.5.1/src/misc.rs"
+    );
+}
+
+#[test]
+fn test_frame_meta() {
+    let e = Error::from_err("root")
+        .add_err_locationless("context")
+        .add();
+    let frames: Vec<_> = e.iter().collect();
+    let location = frames[0].meta().location().unwrap();
+    assert_eq!(location.file(), "tests/test.rs");
+    assert_eq!(Some(location), frames[0].get_location());
+    assert!(frames[1].meta().location().is_none());
+    assert_eq!(
+        frames[2].meta().location().unwrap().line(),
+        location.line() + 2
+    );
+
+    let meta: stacked_errors::FrameMeta = frames[0].meta().clone();
+    assert_eq!(meta.location(), Some(location));
+    assert!(stacked_errors::FrameMeta::default().location().is_none());
+    assert_eq!(stacked_errors::FrameMeta::default().occurrences(), 1);
+}
+
+#[test]
+fn test_stack_secondary() {
+    use stacked_errors::ProbablyNotRootCauseError;
+
+    let res: core::result::Result<(), &str> = Err("channel closed");
+    let e = res.stack_secondary().unwrap_err();
+    assert!(e.is_probably_not_root_cause());
+    assert_eq!(e.iter().len(), 2);
+    let top = e.iter().last().unwrap();
+    assert!(top.downcast_ref::<ProbablyNotRootCauseError>().is_some());
+    assert_eq!(top.get_location().unwrap().file(), "tests/test.rs");
+
+    let e = None::<()>.stack_err_secondary("waiting").unwrap_err();
+    assert!(e.is_probably_not_root_cause());
+    let frames: Vec<_> = e.iter().collect();
+    assert_eq!(frames.len(), 2);
+    assert!(frames[0]
+        .downcast_ref::<ProbablyNotRootCauseError>()
+        .is_some());
+    assert!(frames[0].get_location().is_none());
+    assert_eq!(format!("{}", frames[1].get_err()), "waiting");
+    assert!(frames[1].get_location().is_some());
+
+    let e = Error::from_err("root")
+        .stack_err_secondary("context")
+        .unwrap_err();
+    assert!(e.is_probably_not_root_cause());
+    assert_eq!(e.iter().len(), 3);
+    assert!(!Error::from_err("root").is_probably_not_root_cause());
+
+    assert_eq!(Ok::<u8, Error>(1).stack_secondary().unwrap(), 1);
+    assert_eq!(Some(2).stack_err_secondary("unused").unwrap(), 2);
+}
+
+#[test]
+fn test_display_with_separator() {
+    let e = Error::from_err("root")
+        .add()
+        .add_err_locationless("a message that is long enough that it would normally be split")
+        .with_help("retry");
+    let s = format!("{}", e.display_with_separator("\n"));
+    let lines: Vec<&str> = s.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert_eq!(
+        lines[0],
+        "a message that is long enough that it would normally be split"
+    );
+    assert!(lines[1].starts_with("at tests/test.rs "));
+    assert!(lines[2].starts_with("root at tests/test.rs "));
+    assert_eq!(lines[3], "help: retry");
+    // no commas or indentation are added between frames
+    assert!(!s.contains(','));
+    assert!(!s.contains("  "));
+
+    let s = format!("{}", e.display_with_separator(" | "));
+    assert!(!s.contains('\n'));
+    assert_eq!(s.matches(" | ").count(), 3);
+
+    let e = Error::from_err_locationless("root")
+        .add_err_locationless("middle")
+        .add_err_locationless("top");
+    assert_eq!(
+        format!("{}", e.display_with_separator(";")),
+        "top;middle;root"
+    );
+    assert_eq!(
+        format!(
+            "{}",
+            Error::empty()
+                .with_help("only help")
+                .display_with_separator(";")
+        ),
+        "help: only help"
+    );
+}
+
+#[test]
+fn test_is_unit_only() {
+    let e = None::<()>.stack().stack().stack().unwrap_err();
+    assert_eq!(e.iter().len(), 3);
+    assert!(e.is_unit_only());
+    assert!(Error::empty().is_unit_only());
+    assert!(Error::new().is_unit_only());
+
+    let e = None::<()>.stack_err("missing").stack().unwrap_err();
+    assert!(!e.is_unit_only());
+    let e = Error::new().add_err("message").add();
+    assert!(!e.is_unit_only());
+    assert!(!Error::timeout().add().is_unit_only());
+}
+
+#[test]
+fn test_debug_storage_order() {
+    let e = Error::from_err("root")
+        .add_err("middle")
+        .add()
+        .add_err_locationless("top");
+    // build the expected output from the frames in the order they were pushed
+    let mut expected = String::new();
+    for item in e.iter() {
+        let is_unit = item.downcast_ref::<UnitError>().is_some();
+        expected.push('\n');
+        if !is_unit {
+            expected.push_str(&format!("    {}", item.get_err()));
+        }
+        if let Some(l) = item.get_location() {
+            expected.push_str(if is_unit { "  at " } else { " at " });
+            expected.push_str(&format!("{} {}:{}", l.file(), l.line(), l.column()));
+        }
+    }
+    let s = format!("{:?}", e.debug_storage_order());
+    assert_eq!(s, expected);
+    assert!(!s.contains('\u{1b}'));
+    assert!(s.find("root").unwrap() < s.find("top").unwrap());
+
+    // the normal impls are still reversed
+    let s = format!("{e}");
+    assert!(s.find("top").unwrap() < s.find("root").unwrap());
+}
+
+#[test]
+fn test_shared_error() {
+    use stacked_errors::SharedError;
+
+    let shared = SharedError::new(Error::from_err("init failed").add_err("in setup"));
+    // fan out to three consumers that each add their own context
+    let handles: Vec<_> = ["a", "b", "c"]
+        .into_iter()
+        .map(|name| {
+            let shared = shared.clone();
+            std::thread::spawn(move || {
+                Err::<(), Error>(Error::from_err(shared))
+                    .stack_err(format!("consumer {name} waiting for init"))
+                    .unwrap_err()
+            })
+        })
+        .collect();
+    for (name, handle) in ["a", "b", "c"].into_iter().zip(handles) {
+        let e = handle.join().unwrap();
+        assert_eq!(e.iter().len(), 2);
+        let s = format!("{e}");
+        assert!(s.contains("init failed"));
+        assert!(s.contains("in setup"));
+        assert!(s.contains(&format!("consumer {name} waiting for init")));
+        let shared = e
+            .iter()
+            .next()
+            .unwrap()
+            .downcast_ref::<SharedError>()
+            .unwrap();
+        assert_eq!(shared.iter().len(), 2);
+    }
+
+    // all the other handles have been dropped
+    let e = shared.unwrap_or_clone();
+    assert_eq!(e.iter().len(), 2);
+
+    let shared = SharedError::from(Error::from_err("root"));
+    let other = shared.clone();
+    assert_eq!(format!("{shared}"), format!("{}", *other));
+    let shared = shared.try_unwrap().unwrap_err();
+    let e = shared.unwrap_or_clone();
+    assert_eq!(e.iter().len(), 1);
+    assert!(e
+        .iter()
+        .next()
+        .unwrap()
+        .downcast_ref::<SharedError>()
+        .is_some());
+    drop(e);
+    assert!(other.try_unwrap().is_ok());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_io_error_round_trip() {
+    use std::io;
+
+    fn read() -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::PermissionDenied, "denied"))
+    }
+    fn load() -> io::Result<()> {
+        read()
+            .stack_err("while reading")
+            .stack_err("while loading")?;
+        Ok(())
+    }
+
+    let e = load().unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::PermissionDenied);
+    let s = e.to_string();
+    assert!(s.contains("denied"));
+    assert!(s.contains("while loading"));
+    let e = Error::from_io_error(e);
+    assert_eq!(e.iter().len(), 3);
+    let inner = e
+        .iter()
+        .next()
+        .unwrap()
+        .downcast_ref::<io::Error>()
+        .unwrap();
+    assert_eq!(inner.kind(), io::ErrorKind::PermissionDenied);
+
+    // no `io::Error` frames
+    let e = Error::from_err("plain").into_io_error();
+    assert_eq!(e.kind(), io::ErrorKind::Other);
+    // foreign `io::Error`s become a frame
+    let e = Error::from_io_error(io::Error::from(io::ErrorKind::TimedOut));
+    assert_eq!(e.iter().len(), 1);
+    assert!(e.iter().next().unwrap().get_location().is_some());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_cached_tags() {
+    // compares the cached answers with a fresh scan
+    #[track_caller]
+    fn check(e: &Error) {
+        assert_eq!(
+            e.is_timeout(),
+            e.iter().any(|i| i.downcast_ref::<TimeoutError>().is_some())
+        );
+        assert_eq!(
+            e.is_probably_not_root_cause(),
+            e.iter()
+                .any(|i| i.downcast_ref::<ProbablyNotRootCauseError>().is_some())
+        );
+        assert_eq!(
+            e.is_panic(),
+            e.iter().any(|i| i.downcast_ref::<PanicError>().is_some())
+        );
+        assert_eq!(e.is_timeout(), e.contains::<TimeoutError>());
+    }
+
+    check(&Error::empty());
+    check(&Error::new());
+    let mut e = Error::timeout();
+    check(&e);
+    assert!(e.is_timeout());
+    e.push();
+    e.push_err("context");
+    e.push_err_locationless(ProbablyNotRootCauseError {});
+    check(&e);
+    assert!(e.is_probably_not_root_cause());
+    let e = e.add().add_err("more").add_err_locationless("less");
+    check(&e);
+
+    // `replace_err` can remove and add tags
+    let mut e = Error::from_err("root").add_err(TimeoutError {}).add();
+    assert!(e.is_timeout());
+    assert!(e.replace_err(1, "not a timeout"));
+    check(&e);
+    assert!(!e.is_timeout());
+    assert!(e.replace_err(0, PanicError {}));
+    check(&e);
+    assert!(e.is_panic());
+
+    // chaining in both directions
+    let e = Error::from_err("root")
+        .chain_errors(Error::timeout().add())
+        .add();
+    check(&e);
+    assert!(e.is_timeout());
+    let e = Error::from_err("context")
+        .add()
+        .chain_errors_under(Error::probably_not_root_cause());
+    check(&e);
+    assert!(e.is_probably_not_root_cause());
+    let e = e.with_help("help");
+    check(&e);
+
+    // `strip_frames_in` only removes unit frames
+    let mut e = Error::timeout().add().add();
+    e.strip_frames_in("tests/");
+    assert_eq!(e.iter().len(), 1);
+    check(&e);
+
+    // arbitrary rearrangement through mutable access
+    let mut e = Error::from_err("root").add_err(TimeoutError {}).add();
+    e.as_mut_slice().swap(0, 2);
+    check(&e);
+    let mut other = Error::from_err("replaced");
+    mem::swap(&mut e.as_mut_slice()[1], &mut other.as_mut_slice()[0]);
+    check(&e);
+    assert!(!e.is_timeout());
+    let mut panics = stacked_errors::catch_stacked(|| -> Result<()> { panic!("boom") })
+        .unwrap_err()
+        .add_err(PanicError {})
+        .add_err(PanicError {});
+    for (item, panic) in e.iter_mut().zip(panics.iter_mut()) {
+        mem::swap(item, panic);
+    }
+    check(&e);
+    check(&panics);
+    assert!(e.is_panic());
+    // the next push brings back the cache
+    e.push();
+    check(&e);
+    let mut e0 = Error::timeout();
+    let mut e1 = Error::from_err("other");
+    mem::swap(&mut e0.as_mut_slice()[0], &mut e1.as_mut_slice()[0]);
+    check(&e0);
+    check(&e1);
+    assert!(!e0.is_timeout());
+    assert!(e1.is_timeout());
+
+    // through `StackableErr` and the macros
+    let e = Err::<(), _>(Error::timeout())
+        .stack_err_secondary("waiting")
+        .stack()
+        .unwrap_err();
+    check(&e);
+    assert!(e.is_timeout() && e.is_probably_not_root_cause());
+    fn bail_timeout() -> Result<()> {
+        bail!(TimeoutError {})
+    }
+    let e = bail_timeout().stack().unwrap_err();
+    check(&e);
+    assert!(e.is_timeout());
+    let e = Error::from_err("root").chain_errors(panics);
+    check(&e);
+    assert!(e.is_panic() && !e.is_timeout());
+}
+
+#[test]
+fn test_split_at_marker() {
+    let e = Error::from_err("root")
+        .add()
+        .add_err(TimeoutError {})
+        .add_err("after the timeout")
+        .add_err(TimeoutError {})
+        .add();
+    let (below, above) = e.split_at_marker::<TimeoutError>().unwrap();
+    assert_eq!(below.len(), 2);
+    assert_eq!(*below[0].downcast_ref::<&str>().unwrap(), "root");
+    below[1].downcast_ref::<UnitError>().unwrap();
+    // only the first marker splits
+    assert_eq!(above.len(), 3);
+    assert_eq!(
+        *above[0].downcast_ref::<&str>().unwrap(),
+        "after the timeout"
+    );
+    above[1].downcast_ref::<TimeoutError>().unwrap();
+
+    // markers at the ends
+    let (below, above) = e.split_at_marker::<&str>().unwrap();
+    assert!(below.is_empty());
+    assert_eq!(above.len(), 5);
+    let (below, above) = Error::from_err("root")
+        .add_err(TimeoutError {})
+        .split_at_marker::<TimeoutError>()
+        .map(|(b, a)| (b.len(), a.len()))
+        .unwrap();
+    assert_eq!((below, above), (1, 0));
+    assert!(e.split_at_marker::<PanicError>().is_none());
+}
+
+#[test]
+fn test_push_dedup() {
+    let mut e = Error::from_err("root");
+    for _ in 0..50 {
+        e.push_err_dedup("retrying");
+    }
+    assert_eq!(e.iter().len(), 2);
+    let top = e.iter().last().unwrap();
+    assert_eq!(top.occurrences(), 50);
+    assert_eq!(top.meta().occurrences(), 50);
+    assert_eq!(e.iter().next().unwrap().occurrences(), 1);
+    let s = format!("{e}");
+    assert!(s.contains("retrying at tests/test.rs"));
+    assert!(s.contains(" (x50)\n    root at "));
+
+    // a single context stays length 1
+    let mut e = Error::empty();
+    for _ in 0..50 {
+        e.push_err_dedup("context");
+    }
+    assert_eq!(e.iter().len(), 1);
+    assert_eq!(e.iter().next().unwrap().occurrences(), 50);
+
+    // different messages or locations are not deduplicated
+    let mut e = Error::empty();
+    for i in 0..3 {
+        e.push_err_dedup(i);
+    }
+    e.push_err_dedup(2);
+    assert_eq!(e.iter().len(), 4);
+    let mut e = Error::empty();
+    e.push_dedup();
+    e.push_dedup();
+    assert_eq!(e.iter().len(), 2);
+    assert!(e.iter().all(|e| e.occurrences() == 1));
+    assert!(!format!("{e}").contains("(x"));
+
+    // prefixes and extensions of the message are different messages
+    let mut e = Error::empty();
+    for msg in ["retry", "retrying", "retry", "retry"] {
+        e.push_err_dedup(String::from(msg));
+    }
+    assert_eq!(e.iter().len(), 3);
+    assert_eq!(e.iter().last().unwrap().occurrences(), 2);
+    // an equal message from a different type is deduplicated
+    fn push<E: core::fmt::Display + Send + Sync + 'static>(e: &mut Error, msg: E) {
+        e.push_err_dedup(msg);
+    }
+    let mut e = Error::empty();
+    push(&mut e, String::from("1"));
+    push(&mut e, String::from("1"));
+    push(&mut e, "1");
+    push(&mut e, 1);
+    assert_eq!(e.iter().len(), 1);
+    assert_eq!(e.iter().next().unwrap().occurrences(), 4);
+}
+
+#[test]
+fn test_display_wrapped() {
+    let e = Error::from_err_locationless(
+        "the quick brown fox jumps over the lazy dog and then keeps running through the überlong \
+         field of grass",
+    )
+    .add_err_locationless("short");
+    let s = format!("{}", e.display_wrapped(40));
+    assert_eq!(
+        s,
+        "\n    short\n    the quick brown fox jumps over the\n      lazy dog and then keeps \
+         running\n      through the überlong field of\n      grass"
+    );
+    for line in s.lines() {
+        assert!(line.chars().count() <= 40);
+    }
+
+    // words longer than a line are broken between characters, never inside
+    // of a codepoint
+    let e = Error::from_err_locationless("ééééééééééééééé");
+    assert_eq!(
+        format!("{}", e.display_wrapped(10)),
+        "\n    éééééé\n      éééé\n      éééé\n      é"
+    );
+
+    // the location goes on its own line if it doesn't fit
+    let e = Error::from_err("a message that fills up most of the line");
+    let s = format!("{}", e.display_wrapped(48));
+    assert!(s.starts_with("\n    a message that fills up most of the line\n  at tests/test.rs "));
+    let s = format!("{}", e.display_wrapped(100));
+    assert!(s.starts_with("\n    a message that fills up most of the line at tests/test.rs "));
+}
+
+#[test]
+fn test_into_error() {
+    use std::borrow::Cow;
+
+    use stacked_errors::IntoError;
+
+    let e = "static message".into_error();
+    let item = e.iter().next().unwrap();
+    assert_eq!(*item.downcast_ref::<&str>().unwrap(), "static message");
+    assert_eq!(item.get_location().unwrap().file(), "tests/test.rs");
+
+    let e = format!("owned {}", 1).into_error();
+    let item = e.iter().next().unwrap();
+    assert_eq!(item.downcast_ref::<String>().unwrap(), "owned 1");
+    assert!(item.get_location().is_some());
+
+    // the `Cow` is stored directly instead of being converted to a `String`
+    let e = Cow::Borrowed("borrowed").into_error();
+    assert!(matches!(
+        e.iter().next().unwrap().downcast_ref::<Cow<'static, str>>(),
+        Some(Cow::Borrowed("borrowed"))
+    ));
+    let e = Cow::<str>::Owned(String::from("cow"))
+        .into_error()
+        .add_err("context");
+    assert_eq!(e.iter().len(), 2);
+    assert_eq!(
+        e.iter()
+            .next()
+            .unwrap()
+            .downcast_ref::<Cow<'static, str>>()
+            .unwrap(),
+        "cow"
+    );
+
+    // `StackableErr` still works on results of strings
+    let e = Err::<(), _>("result").stack().unwrap_err();
+    assert_eq!(e.iter().len(), 1);
+}
+
+#[test]
+fn test_report_lines() {
+    let e = Error::from_err("root")
+        .add()
+        .add_err_locationless("a multi-line\nmessage")
+        .add_err("context")
+        .with_help("try again");
+    let lines = e.report_lines();
+    assert_eq!(lines.len(), 6);
+    assert_eq!(lines[0].split(" at ").next().unwrap(), "    context");
+    assert_eq!(lines[1], "    a multi-line");
+    assert_eq!(lines[2], "message");
+    assert!(lines[3].starts_with("  at tests/test.rs "));
+    assert!(lines[4].starts_with("    root at tests/test.rs "));
+    assert_eq!(lines[5], "  help: try again");
+    assert_eq!(format!("\n{}", lines.join("\n")), format!("{e}"));
+    assert!(lines.iter().all(|l| !l.contains('\u{1b}')));
+
+    assert!(Error::empty().report_lines().is_empty());
+}
+
+#[test]
+fn test_capacity() {
+    let mut e = Error::with_capacity(10);
+    assert!(e.capacity() >= 10);
+    let cap = e.capacity();
+    for i in 0..10u64 {
+        e.push_err(i);
+    }
+    // no reallocation happened
+    assert_eq!(e.capacity(), cap);
+    e.reserve(100);
+    assert!(e.capacity() >= 110);
+
+    // give memory back after removing frames
+    let mut e = Error::from_err("root").chain_errors(e);
+    for _ in 0..20 {
+        e.push();
+    }
+    e.strip_frames_in("tests/");
+    assert_eq!(e.iter().len(), 11);
+    e.shrink_to_fit();
+    assert_eq!(e.capacity(), 11);
+    assert_eq!(e.iter().len(), 11);
+    assert!(format!("{e}").contains("root"));
+
+    assert_eq!(Error::empty().capacity(), 0);
+    let mut e = Error::empty();
+    e.shrink_to_fit();
+    assert_eq!(e.capacity(), 0);
+    assert_eq!(mem::size_of::<Error>(), mem::size_of::<usize>());
+    assert_eq!(mem::size_of::<Result<()>>(), mem::size_of::<usize>());
+}
+
+#[test]
+fn test_sanitize_locations() {
+    use stacked_errors::sanitize_location;
+
+    for (path, expected) in [
+        (
+            "/home/alice/projects/app/src/main.rs",
+            "~/projects/app/src/main.rs",
+        ),
+        ("/Users/bob/code/lib.rs", "~/code/lib.rs"),
+        ("/root/build/src/lib.rs", "~/build/src/lib.rs"),
+        ("D:\\Users\\carol\\app\\main.rs", "~\\app\\main.rs"),
+        // not home directories
+        ("/homes/alice/lib.rs", "/homes/alice/lib.rs"),
+        ("/home/alice", "/home/alice"),
+        ("/opt/home/alice/lib.rs", "/opt/home/alice/lib.rs"),
+        ("src/lib.rs", "src/lib.rs"),
+        ("C:\\Program Files\\lib.rs", "C:\\Program Files\\lib.rs"),
+    ] {
+        assert_eq!(sanitize_location(path), expected);
+    }
+    // registry paths are shortened before sanitizing, so they are unaffected
+    let registry =
+        "/home/alice/.cargo/registry/src/index.crates.io-6f17d22bba15001f/a-0.1.0/src/lib.rs";
+    assert_eq!(
+        sanitize_location(stacked_errors::shorten_location(registry)),
+        "a-0.1.0/src/lib.rs"
+    );
+
+    let mut e = Error::from_err("root").add_err("context");
+    let before = format!("{e}");
+    e.sanitize_locations();
+    e.push();
+    // relative locations are not changed
+    assert!(format!("{e}").ends_with(&before));
+    assert!(e
+        .iter()
+        .all(|e| e.get_location().unwrap().file() == "tests/test.rs"));
+
+    let mut e: Error = include!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/absolute.rs"
+    ));
+    let file = e.iter().next().unwrap().get_location().unwrap().file();
+    assert!(file.starts_with(env!("CARGO_MANIFEST_DIR")));
+    assert!(format!("{}", e.display_github_annotations()).contains(&format!("file={file},")));
+    assert!(format!("{}", e.display_json_lines()).contains(&format!("\"file\":\"{file}\"")));
+    e.sanitize_locations();
+    let sanitized = sanitize_location(file);
+    assert!(format!("{e}").contains(&*sanitized));
+    assert!(format!("{}", e.display_github_annotations()).contains(&format!("file={sanitized},")));
+    assert!(format!("{}", e.display_json_lines()).contains(&format!("\"file\":\"{sanitized}\"")));
+}
+
+// layout regressions should fail CI, `ErrorItem` is checked in the crate
+#[cfg(target_pointer_width = "64")]
+#[test]
+fn result_size() {
+    assert_eq!(mem::size_of::<Result<u64>>(), 16);
+}
+
+#[test]
+fn test_distinct_locations() {
+    fn attempt(e: Error) -> Error {
+        e.add_err("retrying")
+    }
+    assert_eq!(Error::empty().distinct_locations(), 0);
+    let mut e = Error::from_err_locationless("root");
+    assert_eq!(e.distinct_locations(), 0);
+    for _ in 0..5 {
+        e = attempt(e);
+    }
+    assert_eq!(e.iter().len(), 6);
+    assert_eq!(e.distinct_locations(), 1);
+    let e = e.add().add_err_locationless("no location");
+    assert_eq!(e.distinct_locations(), 2);
+}
+
+#[test]
+fn test_stacked_log() {
+    use core::fmt::Write;
+
+    use stacked_errors::stacked_log;
+
+    fn log<W: Write>(w: &mut W, e: &Error) {
+        stacked_log!(w, e).unwrap();
+    }
+
+    let e = Error::from_err("root").add_err("context").add();
+    let mut s = String::new();
+    log(&mut s, &e);
+    assert_eq!(s, format!("{e}"));
+    stacked_log!(s, &e).unwrap();
+    assert_eq!(s, format!("{e}{e}"));
+}
+
+#[test]
+fn test_estimate_heap_usage() {
+    assert_eq!(Error::empty().estimate_heap_usage(), 0);
+
+    let small = Error::from_err("root");
+    let frame = small.iter().next().unwrap();
+    assert_eq!(frame.payload_size(), mem::size_of::<&str>());
+    let small_usage = small.estimate_heap_usage();
+    assert!(small_usage > 0);
+    assert!(small_usage < 256);
+
+    let mut large = Error::from_err("root");
+    for i in 0..99 {
+        large = large.add_err(format!("{i:0>100}"));
+    }
+    let large_usage = large.estimate_heap_usage();
+    assert!(large_usage > small_usage * 50);
+    // at least the strings themselves
+    assert!(large_usage > 99 * 100);
+    assert!(large_usage < 100 * 1024);
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+fn test_stacked_get_location() {
+    use serde_json::{json, Value};
+    use stacked_errors::{stacked_get, stacked_get_mut};
+
+    fn assert_line(res: Result<()>, line: u32) {
+        let e = res.unwrap_err();
+        assert_eq!(e.iter().len(), 1);
+        let loc = e.iter().next().unwrap().get_location().unwrap();
+        assert_eq!((loc.file(), loc.line()), (file!(), line));
+    }
+
+    let mut v = json!({"a": {"b": 1}});
+    // the first index is applied separately from the rest
+    let first = |v: &Value| -> Result<()> {
+        let _ = stacked_get!(v["missing"]["b"]);
+        Ok(())
+    };
+    assert_line(first(&v), line!() - 3);
+    let chained = |v: &Value| -> Result<()> {
+        let _ = stacked_get!(v["a"]["missing"]);
+        Ok(())
+    };
+    assert_line(chained(&v), line!() - 3);
+    let chained_mut = |v: &mut Value| -> Result<()> {
+        *stacked_get_mut!(v["a"]["missing"]) = 2.into();
+        Ok(())
+    };
+    assert_line(chained_mut(&mut v), line!() - 3);
+}
+
+#[test]
+fn test_stackable_err_same() {
+    use stacked_errors::StackableErrSame;
+
+    fn inner(fail: bool) -> Result<u8> {
+        if fail {
+            Err(Error::from_err("root"))
+        } else {
+            Ok(1)
+        }
+    }
+
+    assert_eq!(inner(false).stack_same().unwrap(), 1);
+    assert_eq!(inner(false).stack_same_err("x").unwrap(), 1);
+    assert_eq!(
+        inner(false)
+            .stack_same_err_with(|| -> &str { unreachable!() })
+            .unwrap(),
+        1
+    );
+
+    let x = 5;
+    let same = [
+        inner(true).stack_same(),
+        inner(true).stack_same_err("ctx"),
+        inner(true).stack_same_err_with(|| format!("ctx {x}")),
+    ];
+    let line = line!() - 4;
+    let dyn_path = [
+        inner(true).stack(),
+        inner(true).stack_err("ctx"),
+        inner(true).stack_err_with(|| format!("ctx {x}")),
+    ];
+    for (i, (same, dyn_path)) in same.into_iter().zip(dyn_path).enumerate() {
+        let (same, dyn_path) = (same.unwrap_err(), dyn_path.unwrap_err());
+        assert_eq!(
+            format!("{}", same.display_no_locations()),
+            format!("{}", dyn_path.display_no_locations())
+        );
+        assert_eq!(same.iter().len(), 2);
+        let loc = same.iter().nth(1).unwrap().get_location().unwrap();
+        assert_eq!((loc.file(), loc.line()), (file!(), line + i as u32));
+        assert!(dyn_path.iter().nth(1).unwrap().get_location().is_some());
+    }
+}
+
+#[test]
+fn test_try_alternatives() {
+    use core::cell::Cell;
+
+    use stacked_errors::{try_alternatives, MultipleErrors};
+
+    let calls = Cell::new(0);
+    let alt = |i: u8, ok: bool| -> Result<u8> {
+        calls.set(calls.get() + 1);
+        if ok {
+            Ok(i)
+        } else {
+            Err(Error::from_err(format!("alternative {i} failed")))
+        }
+    };
+
+    // first success, the rest are not evaluated
+    let res = try_alternatives([(0, true), (1, true)].into_iter().map(|(i, ok)| alt(i, ok)));
+    assert_eq!(res.unwrap(), 0);
+    assert_eq!(calls.get(), 1);
+
+    // second success
+    let res = try_alternatives(
+        [(0, false), (1, true), (2, false)]
+            .into_iter()
+            .map(|(i, ok)| alt(i, ok)),
+    );
+    assert_eq!(res.unwrap(), 1);
+    assert_eq!(calls.get(), 3);
+
+    // all fail
+    let line = line!() + 1;
+    let e = try_alternatives(
+        [(0, false), (1, false), (2, false)]
+            .into_iter()
+            .map(|(i, ok)| alt(i, ok)),
+    )
+    .unwrap_err();
+    assert_eq!(calls.get(), 6);
+    assert_eq!(e.iter().len(), 4);
+    let head = e.iter().last().unwrap();
+    assert_eq!(head.downcast_ref::<MultipleErrors>().unwrap().count, 3);
+    assert_eq!(head.get_location().unwrap().line(), line);
+    assert_eq!(
+        format!("{}", e.display_no_locations()),
+        "\n    all 3 alternatives failed\n    alternative 2 failed\n    alternative 1 failed\n    \
+         alternative 0 failed"
+    );
+
+    let e = try_alternatives(core::iter::empty::<Result<()>>()).unwrap_err();
+    assert_eq!(e.iter().len(), 1);
+}
+
+#[test]
+fn test_stack_typed() {
+    use stacked_errors::TypeContext;
+
+    fn load<T>(fail: bool, value: T) -> Result<T> {
+        let res: core::result::Result<T, &str> = if fail { Err("load failed") } else { Ok(value) };
+        res.stack_typed()
+    }
+    fn type_name(e: &Error) -> &'static str {
+        e.iter()
+            .last()
+            .unwrap()
+            .downcast_ref::<TypeContext>()
+            .unwrap()
+            .type_name
+    }
+
+    assert_eq!(load(false, 1u8).unwrap(), 1);
+    let a = load(true, 1u8).unwrap_err();
+    let b = load(true, String::new()).unwrap_err();
+    assert_eq!(type_name(&a), "u8");
+    assert!(type_name(&b).ends_with("String"));
+    assert_ne!(type_name(&a), type_name(&b));
+    assert!(format!("{a}").contains("while producing u8 at tests/test.rs"));
+    assert!(a.iter().last().unwrap().get_location().is_some());
+
+    let e = None::<Vec<u8>>.stack_typed().unwrap_err();
+    assert!(type_name(&e).contains("Vec<u8>"));
+    let e = Error::from_err("root").stack_typed().unwrap_err();
+    assert_eq!(type_name(&e), "()");
+}
+
+#[test]
+fn test_kind_at_root() {
+    use stacked_errors::RootKind;
+
+    assert_eq!(Error::empty().kind_at_root(), RootKind::Unit);
+    assert_eq!(
+        Error::new().add_err("context").kind_at_root(),
+        RootKind::Unit
+    );
+    assert_eq!(Error::timeout().add().kind_at_root(), RootKind::Timeout);
+    assert_eq!(
+        Error::probably_not_root_cause()
+            .add_err("context")
+            .kind_at_root(),
+        RootKind::NotRootCause
+    );
+    assert_eq!(
+        Error::from_err(PanicError {}).kind_at_root(),
+        RootKind::Panic
+    );
+    assert_eq!(
+        Error::from_err("root").add_err("context").kind_at_root(),
+        RootKind::Other
+    );
+    // a timeout that is not at the root does not count
+    assert_eq!(
+        Error::from_err("root")
+            .add_err(TimeoutError {})
+            .kind_at_root(),
+        RootKind::Other
+    );
+    assert_eq!(
+        Error::from_err(stacked_errors::AllocFailedError {}).kind_at_root(),
+        RootKind::AllocFailed
+    );
+
+    #[cfg(feature = "std")]
+    {
+        let res = stacked_errors::catch_stacked(|| -> Result<()> { panic!("boom") });
+        let e = res.unwrap_err();
+        assert_eq!(e.kind_at_root(), RootKind::Panic);
+    }
+}
+
+#[test]
+fn test_error_payloads_merge() {
+    fn assert_flat(e: &Error) {
+        assert!(e.iter().all(|e| e.downcast_ref::<Error>().is_none()));
+    }
+    fn inner() -> Error {
+        Error::from_err("root").add_err("context")
+    }
+
+    let line = line!() + 1;
+    let e = Error::from_err(inner());
+    assert_flat(&e);
+    assert_eq!(e.iter().len(), 3);
+    assert!(e
+        .iter()
+        .last()
+        .unwrap()
+        .downcast_ref::<UnitError>()
+        .is_some());
+    assert_eq!(
+        e.iter().last().unwrap().get_location().unwrap().line(),
+        line
+    );
+    let e = Error::from_err_locationless(inner());
+    assert_flat(&e);
+    assert_eq!(e.iter().len(), 2);
+
+    fn bails() -> Result<()> {
+        bail!(inner())
+    }
+    let e = bails().unwrap_err();
+    assert_flat(&e);
+    assert_eq!(e.iter().len(), 3);
+    assert_eq!(
+        e.iter().next().unwrap().downcast_ref::<&str>(),
+        Some(&"root")
+    );
+    assert_flat(&eyre!(inner()));
+    assert_flat(&anyhow!(inner()));
+
+    // another `Error` used as context goes on top, followed by the location
+    let res: core::result::Result<(), &str> = Err("cause");
+    let e = res.stack_err(inner()).unwrap_err();
+    assert_flat(&e);
+    assert_eq!(
+        format!("{}", e.display_no_locations()),
+        "\n    context\n    root\n    cause"
+    );
+    assert_eq!(e.iter().len(), 4);
+    let e = None::<()>.stack_err(inner()).unwrap_err();
+    assert_flat(&e);
+    assert_eq!(e.iter().len(), 3);
+    let e = Error::from_err("cause").stack_err(inner()).unwrap_err();
+    assert_flat(&e);
+    assert_eq!(e.iter().len(), 4);
+    let e = Error::from_err("cause").add_err_locationless(inner());
+    assert_flat(&e);
+    assert_eq!(e.iter().len(), 3);
+}
+
+#[test]
+fn test_push_messages() {
+    let msgs: Vec<String> = vec!["name is empty".to_owned(), "age is negative".to_owned()];
+    let mut e = Error::from_err("validation failed");
+    e.push_messages(msgs);
+    assert_eq!(e.iter().len(), 3);
+    assert!(e.iter().skip(1).all(|e| e.get_location().is_none()));
+    assert_eq!(
+        e.iter()
+            .map(|e| e.downcast_ref::<String>().map(|s| s.as_str()))
+            .collect::<Vec<_>>(),
+        [None, Some("name is empty"), Some("age is negative")]
+    );
+    assert_eq!(
+        format!("{}", e.display_no_locations()),
+        "\n    age is negative\n    name is empty\n    validation failed"
+    );
+
+    let mut e = Error::empty();
+    e.push_messages(Vec::<&str>::new());
+    assert_eq!(e.iter().len(), 0);
+}
+
+#[test]
+fn test_display_json_lines() {
+    use serde_json::Value;
+
+    let mut e = Error::from_err_locationless("root\twith \"quotes\"\nand\u{1}control");
+    let line = line!() + 1;
+    e.push();
+    e.push_err(String::from("context \\ backslash"));
+    let s = format!("{}", e.display_json_lines());
+    let records: Vec<Value> = s
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(records.len(), 3);
+    for record in &records {
+        let keys: Vec<&str> = record
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(|k| k.as_str())
+            .collect();
+        assert_eq!(keys.len(), 4);
+        for key in ["message", "file", "line", "col"] {
+            assert!(keys.contains(&key));
+        }
+    }
+    assert_eq!(records[0]["message"], "context \\ backslash");
+    assert_eq!(records[0]["file"], "tests/test.rs");
+    assert_eq!(records[1]["message"], Value::Null);
+    assert_eq!(records[1]["line"], line);
+    assert_eq!(
+        records[2]["message"],
+        "root\twith \"quotes\"\nand\u{1}control"
+    );
+    assert_eq!(records[2]["file"], Value::Null);
+    assert_eq!(records[2]["col"], Value::Null);
+
+    // locationless location-only frames render as nothing, and the help is its
+    // own record
+    let e = Error::from_err_locationless("root")
+        .add_err_locationless(UnitError {})
+        .with_help("try \"again\"");
+    assert_eq!(
+        format!("{}", e.display_json_lines()),
+        "{\"message\":\"root\",\"file\":null,\"line\":null,\"col\":null}\n{\"help\":\"try \
+         \\\"again\\\"\"}"
+    );
+    let s = format!(
+        "{}",
+        Error::empty().with_help("only help").display_json_lines()
+    );
+    let record: Value = serde_json::from_str(&s).unwrap();
+    assert_eq!(record["help"], "only help");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_io_timeout() {
+    use std::io;
+
+    use stacked_errors::RootKind;
+
+    for kind in [io::ErrorKind::TimedOut, io::ErrorKind::WouldBlock] {
+        let e = Error::from_err(io::Error::from(kind)).add_err("reading the socket");
+        assert!(e.is_timeout());
+        assert_eq!(e.kind_at_root(), RootKind::Timeout);
+        let e = Error::from_err("root").add_err(io::Error::new(kind, "slow"));
+        assert!(e.is_timeout());
+        assert_eq!(e.kind_at_root(), RootKind::Other);
+    }
+    let e = Error::from_err(io::Error::from(io::ErrorKind::NotFound));
+    assert!(!e.is_timeout());
+    assert_eq!(e.kind_at_root(), RootKind::Other);
+    let res: std::result::Result<(), io::Error> = Err(io::ErrorKind::TimedOut.into());
+    assert!(res.stack().unwrap_err().is_timeout());
+}
+
+#[test]
+fn test_unwind_safe() {
+    use std::panic::{RefUnwindSafe, UnwindSafe};
+
+    use stacked_errors::{ErrorItem, SharedError};
+
+    // these must hold no matter the payloads, so that they can't regress with
+    // the auto traits of other crates
+    fn assert_unwind_safe<T: UnwindSafe + RefUnwindSafe>() {}
+    assert_unwind_safe::<Error>();
+    assert_unwind_safe::<ErrorItem>();
+    assert_unwind_safe::<SharedError>();
+    assert_unwind_safe::<Result<u64>>();
+    assert_unwind_safe::<&Error>();
+
+    // a payload that is not unwind safe by itself
+    struct NotUnwindSafe(core::marker::PhantomData<&'static mut u8>);
+    impl core::fmt::Display for NotUnwindSafe {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            f.write_str("not unwind safe")
+        }
+    }
+    let e = Error::from_err("root").add_err(NotUnwindSafe(core::marker::PhantomData));
+    let e = std::panic::catch_unwind(move || e.add_err("moved across")).unwrap();
+    assert_eq!(e.iter().len(), 3);
+    let e = Error::from_err(String::from("root"));
+    let s = std::panic::catch_unwind(|| format!("{e}")).unwrap();
+    assert!(s.contains("root"));
+}
+
+#[test]
+fn test_retain_meaningful() {
+    let mut e = Error::from_err_locationless(UnitError {})
+        .add_err_locationless("root")
+        .add_err_locationless(UnitError {})
+        .add()
+        .add_err_locationless(UnitError {})
+        .add_err(TimeoutError {})
+        .add_err_locationless(UnitError {});
+    assert_eq!(e.iter().len(), 7);
+    e.retain_meaningful();
+    assert_eq!(e.iter().len(), 3);
+    let mut iter = e.iter();
+    assert_eq!(
+        *iter.next().unwrap().downcast_ref::<&str>().unwrap(),
+        "root"
+    );
+    let located = iter.next().unwrap();
+    assert!(located.downcast_ref::<UnitError>().is_some());
+    assert!(located.get_location().is_some());
+    assert!(iter
+        .next()
+        .unwrap()
+        .downcast_ref::<TimeoutError>()
+        .is_some());
+    assert!(e.is_timeout());
+
+    // only no-op frames
+    let mut e = Error::from_err_locationless(UnitError {});
+    e.retain_meaningful();
+    assert_eq!(e.iter().len(), 0);
+    assert!(!e.is_timeout());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_catch_stacked_result() {
+    use stacked_errors::catch_stacked_result;
+
+    assert_eq!(catch_stacked_result(|| Ok(42u8)).unwrap(), 42);
+    // an inner `Err` passes through without a `PanicError`
+    let e =
+        catch_stacked_result(|| -> Result<()> { Err(Error::from_err("not a panic")) }).unwrap_err();
+    assert!(!e.is_panic());
+    assert_eq!(e.iter().len(), 1);
+
+    let line = line!() + 1;
+    let e = catch_stacked_result(|| -> Result<()> { panic!("test") }).unwrap_err();
+    assert!(e.is_panic());
+    let mut iter = e.iter();
+    assert_eq!(
+        *iter.next().unwrap().downcast_ref::<&str>().unwrap(),
+        "test"
+    );
+    let x = iter.next().unwrap();
+    x.downcast_ref::<PanicError>().unwrap();
+    assert_eq!(x.get_location().unwrap().line(), line);
+
+    let x = 5u64;
+    let e = catch_stacked_result(|| -> Result<()> { panic!("test {x}") }).unwrap_err();
+    let x = e.iter().next().unwrap();
+    assert_eq!(*x.downcast_ref::<String>().unwrap(), "test 5");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_from_panic_payload() {
+    use std::thread;
+
+    use stacked_errors::PanicPayload;
+
+    let payload = thread::spawn(|| panic!("test")).join().unwrap_err();
+    let line = line!() + 1;
+    let e = Error::from_panic_payload(payload);
+    assert!(e.is_panic());
+    let mut iter = e.iter();
+    assert_eq!(
+        *iter.next().unwrap().downcast_ref::<&str>().unwrap(),
+        "test"
+    );
+    let x = iter.next().unwrap();
+    x.downcast_ref::<PanicError>().unwrap();
+    assert_eq!(x.get_location().unwrap().file(), "tests/test.rs");
+    assert_eq!(x.get_location().unwrap().line(), line);
+
+    let x = 5u64;
+    let payload = thread::spawn(move || panic!("test {x}"))
+        .join()
+        .unwrap_err();
+    let e = Error::from_panic_payload(payload);
+    let x = e.iter().next().unwrap();
+    assert_eq!(*x.downcast_ref::<String>().unwrap(), "test 5");
+
+    // other payloads are retained
+    let payload = thread::spawn(|| std::panic::panic_any(vec![1u8, 2]))
+        .join()
+        .unwrap_err();
+    let mut e = Error::from_panic_payload(payload);
+    assert!(e.is_panic());
+    assert_eq!(
+        e.iter().next().unwrap().to_string(),
+        "non-string panic payload"
+    );
+    let x = e.iter_mut().next().unwrap();
+    let payload = x.downcast_mut::<PanicPayload>().unwrap();
+    assert_eq!(payload.get_mut().downcast_mut::<Vec<u8>>().unwrap(), &[
+        1, 2
+    ]);
+}
+
+#[test]
+fn test_iter_std_errors() {
+    #[derive(Debug, thiserror::Error)]
+    #[error("inner")]
+    struct Inner;
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("outer")]
+    struct Outer(#[source] Inner);
+
+    let e = Error::from_err("root")
+        .add_err(String::from("message"))
+        .add_err("x".parse::<u8>().unwrap_err())
+        .add_err("context")
+        .add_std_err(Outer(Inner))
+        .add();
+    let errs: Vec<_> = e.iter_std_errors().collect();
+    assert_eq!(errs.len(), 2);
+    assert_eq!(errs[0].to_string(), "invalid digit found in string");
+    assert_eq!(errs[1].to_string(), "outer");
+    // the view is the real error, so its source chain is available
+    assert_eq!(errs[1].source().unwrap().to_string(), "inner");
+    assert!(errs[1].downcast_ref::<Outer>().is_some());
+
+    // frames of string types and markers are not yielded
+    let e = Error::from_err("a").add_err(String::from("b")).add();
+    assert_eq!(e.iter_std_errors().count(), 0);
+    let items: Vec<_> = e.iter().collect();
+    assert!(items[0].as_std_error().is_none());
+
+    // the view is per frame, the same type added with `add_err` is not yielded
+    let mut e = Error::from_std_err(Inner).add_err(Inner);
+    e.push_std_err(Outer(Inner));
+    let errs: Vec<String> = e.iter_std_errors().map(|e| e.to_string()).collect();
+    assert_eq!(errs, ["inner", "outer"]);
+    assert!(e
+        .iter()
+        .all(|e| e.downcast_ref::<Inner>().is_some() || e.downcast_ref::<Outer>().is_some()));
+    assert_eq!(
+        format!("{}", e.display_no_locations()),
+        "\n    outer\n    inner\n    inner"
+    );
+    assert_eq!(
+        Error::from_std_err(Inner)
+            .try_into_single::<Inner>()
+            .unwrap()
+            .to_string(),
+        "inner"
+    );
+    // too large to be stored inline
+    #[derive(Debug, thiserror::Error)]
+    #[error("large {}", .0.len())]
+    struct Large(Vec<u8>, [u64; 8]);
+    let large = Error::from_std_err(Large(vec![1, 2], [3; 8]))
+        .try_into_single::<Large>()
+        .unwrap();
+    assert_eq!((large.0, large.1), (vec![1, 2], [3; 8]));
+
+    // merged instead of nested like with `from_err`
+    let e = Error::from_err("root").add_std_err(Error::from_err("other"));
+    assert_eq!(e.iter().len(), 3);
 }
@@ -0,0 +1,54 @@
+//! This is separate from the other tests because the predicates are global
+
+use stacked_errors::{
+    register_timeout_predicate, Error, ErrorItem, RootKind, StackedErrorDowncast,
+    MAX_TIMEOUT_PREDICATES,
+};
+
+#[derive(Debug, thiserror::Error)]
+#[error("deadline exceeded")]
+struct DeadlineExceeded;
+
+#[derive(Debug, thiserror::Error)]
+#[error("status {0}")]
+struct Status(u16);
+
+fn is_deadline(e: &ErrorItem) -> bool {
+    e.downcast_ref::<DeadlineExceeded>().is_some()
+}
+
+#[test]
+fn timeout_predicate() {
+    // not recognized before registering
+    assert!(!Error::from_err(DeadlineExceeded).is_timeout());
+
+    assert!(register_timeout_predicate(is_deadline));
+    // registering again does not take another slot
+    assert!(register_timeout_predicate(is_deadline));
+    assert!(register_timeout_predicate(|e| e
+        .downcast_ref::<Status>()
+        .is_some_and(|s| s.0 == 504)));
+
+    let e = Error::from_err(DeadlineExceeded).add_err("calling the service");
+    assert!(e.is_timeout());
+    assert_eq!(e.kind_at_root(), RootKind::Timeout);
+    assert!(Error::from_err(Status(504)).is_timeout());
+    assert!(!Error::from_err(Status(500)).is_timeout());
+    assert!(!Error::from_err("other").is_timeout());
+    // the built in recognition still works
+    assert!(Error::timeout().is_timeout());
+
+    // fill the remaining slots with distinct functions
+    macro_rules! fill {
+        ($($i:literal)*) => {
+            [$(|e: &ErrorItem| e.downcast_ref::<u64>() == Some(&$i)),*]
+        };
+    }
+    let extra: [fn(&ErrorItem) -> bool; MAX_TIMEOUT_PREDICATES - 2] = fill!(0 1 2 3 4 5);
+    for f in extra {
+        assert!(register_timeout_predicate(f));
+    }
+    assert!(!register_timeout_predicate(|_| true));
+    assert!(Error::from_err(5u64).is_timeout());
+    assert!(!Error::from_err(6u64).is_timeout());
+}
@@ -0,0 +1,20 @@
+#![cfg(feature = "tokio")]
+
+use std::time::Duration;
+
+use stacked_errors::{Error, RootKind, StackableErr};
+
+#[test]
+fn tokio_elapsed_is_timeout() {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap();
+    let res = rt.block_on(async {
+        tokio::time::timeout(Duration::from_millis(1), std::future::pending::<()>()).await
+    });
+    let e = res.stack_err("waiting for the server").unwrap_err();
+    assert!(e.is_timeout());
+    assert_eq!(e.kind_at_root(), RootKind::Timeout);
+    assert!(!Error::from_err("other").is_timeout());
+}
@@ -0,0 +1,103 @@
+#![cfg(feature = "toml")]
+
+use stacked_errors::{
+    stacked_get, stacked_get_mut, Result, StackableErr, StackedErrorDowncast, StackedGet,
+};
+use toml::Value;
+
+const CONFIG: &str = r#"
+name = "server"
+
+[limits]
+max = 8
+names = ["a", "b"]
+
+[[workers]]
+id = 0
+"#;
+
+#[test]
+fn toml_get() {
+    fn ex(value: &Value) -> Result<()> {
+        assert_eq!(stacked_get!(value["name"]).as_str(), Some("server"));
+        assert_eq!(stacked_get!(value["limits"]["max"]).as_integer(), Some(8));
+        assert_eq!(
+            stacked_get!(value["limits"]["names"][1]).as_str(),
+            Some("b")
+        );
+        assert_eq!(
+            stacked_get!(value["workers"][0]["id"]).as_integer(),
+            Some(0)
+        );
+        Ok(())
+    }
+
+    let value: Value = toml::from_str(CONFIG).stack().unwrap();
+    ex(&value).unwrap();
+
+    assert_eq!(value.stacked_kind(), "toml table");
+    assert_eq!(value["limits"]["names"].stacked_kind(), "toml array");
+    assert_eq!(value["name"].stacked_kind(), "toml string");
+}
+
+#[test]
+fn toml_get_failures() {
+    let value: Value = toml::from_str(CONFIG).unwrap();
+
+    fn missing_key(value: &Value) -> Result<&Value> {
+        Ok(stacked_get!(value["limits"]["min"]))
+    }
+    let e = missing_key(&value).unwrap_err();
+    assert_eq!(e.iter().len(), 1);
+    let frame = e.iter().next().unwrap();
+    assert_eq!(
+        format!("{}", frame.get_err()),
+        "stacked_get(value[\"limits\"][\"min\"]) -> indexing failed at [\"limits\"] (toml table)"
+    );
+    assert_eq!(frame.get_location().unwrap().file(), "tests/toml.rs");
+
+    fn out_of_bounds(value: &Value) -> Result<&Value> {
+        Ok(stacked_get!(value["workers"][3]["id"]))
+    }
+    let e = out_of_bounds(&value).unwrap_err();
+    assert!(format!("{e}").contains(
+        "stacked_get(value[\"workers\"][3]) -> indexing failed at [\"workers\"] (toml array)"
+    ));
+
+    // a key into an array or a position into a table fails
+    fn wrong_index(value: &Value) -> Result<&Value> {
+        Ok(stacked_get!(value["limits"][0]))
+    }
+    let e = wrong_index(&value).unwrap_err();
+    assert!(format!("{e}").contains("indexing failed at [\"limits\"] (toml table)"));
+
+    fn into_leaf(value: &Value) -> Result<&Value> {
+        Ok(stacked_get!(value["name"]["first"]))
+    }
+    let e = into_leaf(&value).unwrap_err();
+    assert!(format!("{e}").contains("indexing failed at [\"name\"] (toml string)"));
+}
+
+#[test]
+fn toml_get_mut() {
+    fn ex(value: &mut Value) -> Result<()> {
+        *stacked_get_mut!(value["limits"]["max"]) = Value::Integer(16);
+        *stacked_get_mut!(value["limits"]["names"][0]) = Value::String("c".to_owned());
+        Ok(())
+    }
+
+    let mut value: Value = toml::from_str(CONFIG).unwrap();
+    ex(&mut value).unwrap();
+    assert_eq!(value["limits"]["max"].as_integer(), Some(16));
+    assert_eq!(value["limits"]["names"][0].as_str(), Some("c"));
+
+    fn missing(value: &mut Value) -> Result<()> {
+        *stacked_get_mut!(value["limits"]["other"]["x"]) = Value::Integer(0);
+        Ok(())
+    }
+    let e = missing(&mut value).unwrap_err();
+    assert!(format!("{e}").contains(
+        "stacked_get_mut(value[\"limits\"][\"other\"]) -> indexing failed at [\"limits\"] (toml \
+         table)"
+    ));
+}
@@ -0,0 +1,42 @@
+#![cfg(feature = "tracing")]
+
+use stacked_errors::{Error, Result, SpanContext, StackableErr, StackedErrorDowncast, UnitError};
+
+#[test]
+fn stack_span() {
+    let f = || -> Result<()> {
+        let span = tracing::info_span!("my_span");
+        let _guard = span.enter();
+        Err(Error::from_err("root")).stack_span()
+    };
+    tracing::subscriber::with_default(tracing_subscriber::registry(), || {
+        let e = f().unwrap_err();
+        assert_eq!(e.iter().len(), 2);
+        let item = e.iter().nth(1).unwrap();
+        assert!(item.get_location().is_some());
+        let span = item.downcast_ref::<SpanContext>().unwrap();
+        assert_eq!(span.name, "my_span");
+        assert_eq!(span.target, "tracing");
+        assert!(format!("{e}").contains("in span my_span (tracing) at tests/tracing.rs"));
+
+        let e = tracing::info_span!("other").in_scope(|| None::<()>.stack_span().unwrap_err());
+        assert_eq!(
+            e.iter()
+                .next()
+                .unwrap()
+                .downcast_ref::<SpanContext>()
+                .unwrap()
+                .name,
+            "other"
+        );
+    });
+
+    // without a subscriber the span is disabled and only the location is pushed
+    let e = f().unwrap_err();
+    assert_eq!(e.iter().len(), 2);
+    e.iter()
+        .nth(1)
+        .unwrap()
+        .downcast_ref::<UnitError>()
+        .unwrap();
+}
@@ -0,0 +1,61 @@
+//! Every `StackableErr` method that records a location must record the
+//! location of the user's call site, not one inside this crate
+
+use stacked_errors::{Error, Result, StackableErr, StackedErrorDowncast};
+
+/// Calls `$method` on every kind of receiver in its error state and checks the
+/// location of the top frame against the line of the macro invocation
+macro_rules! check {
+    ($method:ident $(, $arg:expr)?) => {{
+        let line = line!();
+        let res: core::result::Result<(), &str> = Err("root");
+        check_top(res.$method($($arg)?), line);
+        let res: Result<()> = Err(Error::from_err_locationless("root"));
+        check_top(res.$method($($arg)?), line);
+        check_top(None::<()>.$method($($arg)?), line);
+        check_top(Error::from_err_locationless("root").$method($($arg)?), line);
+    }};
+}
+
+#[track_caller]
+fn check_top(res: Result<()>, line: u32) {
+    let e = res.unwrap_err();
+    let l = e.iter().last().unwrap().get_location().unwrap();
+    assert_eq!((l.file(), l.line()), (file!(), line), "{e:?}");
+}
+
+#[test]
+fn locations_are_of_the_call_site() {
+    check!(stack);
+    check!(stack_err, "msg");
+    check!(stack_err_with, || "msg");
+    check!(stack_secondary);
+    check!(stack_err_secondary, "msg");
+    check!(stack_typed);
+    check!(stack_err_cow, std::borrow::Cow::Borrowed("msg"));
+    check!(wrap_err, "msg");
+    check!(wrap_err_with, || "msg");
+    check!(context, "msg");
+    check!(with_context, || "msg");
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn stack_span_location() {
+    check!(stack_span);
+}
+
+#[test]
+fn locationless_methods() {
+    let res: core::result::Result<(), &str> = Err("root");
+    let e = res.stack_err_locationless("msg").unwrap_err();
+    assert!(e.iter().all(|e| e.get_location().is_none()));
+    let e = None::<()>
+        .stack_err_with_locationless(|| "msg")
+        .unwrap_err();
+    assert!(e.iter().all(|e| e.get_location().is_none()));
+    let e = Error::from_err_locationless("root")
+        .into_stacked()
+        .unwrap_err();
+    assert!(e.iter().all(|e| e.get_location().is_none()));
+}